@@ -17,6 +17,7 @@ pub struct AuthConfig {
 
     pub TOKEN_KEY: Vec<u8>,
     pub TOKEN_DURATION_SEC: i64,
+    pub REFRESH_TOKEN_DURATION_SEC: i64,
 }
 
 impl AuthConfig {
@@ -27,6 +28,7 @@ impl AuthConfig {
 
             TOKEN_KEY: get_env_b64u_as_u8s("SERVICE_TOKEN_KEY")?,
             TOKEN_DURATION_SEC: get_env_parse("SERVICE_TOKEN_DURATION_SEC")?,
+            REFRESH_TOKEN_DURATION_SEC: get_env_parse("SERVICE_REFRESH_TOKEN_DURATION_SEC")?,
         })
     }
 }
@@ -13,6 +13,16 @@ pub enum Error {
     FailSpawnBlockForHash,
 
     PwdWithSchemeFailedParse,
+
+    // -- Strength
+    PwdTooShort {
+        min: usize,
+    },
+    PwdTooLong {
+        max: usize,
+    },
+    PwdMissingLetter,
+    PwdMissingDigit,
 }
 
 impl core::fmt::Display for Error {
@@ -40,6 +40,31 @@ pub async fn validate_pwd(to_hash: ContentToHash, pwd_ref: String) -> Result<Sch
     Ok(scheme_status)
 }
 
+/// Minimum number of characters of a clear password
+pub const PWD_MIN_LEN: usize = 8;
+/// Maximum number of characters of a clear password (bounds the hashing cost)
+pub const PWD_MAX_LEN: usize = 128;
+
+/// Check the password strength rules for a new clear password
+/// (length between PWD_MIN_LEN and PWD_MAX_LEN, at least one letter and one digit)
+pub fn check_pwd_strength(pwd_clear: &str) -> Result<()> {
+    let len = pwd_clear.chars().count();
+    if len < PWD_MIN_LEN {
+        return Err(Error::PwdTooShort { min: PWD_MIN_LEN });
+    }
+    if len > PWD_MAX_LEN {
+        return Err(Error::PwdTooLong { max: PWD_MAX_LEN });
+    }
+    if !pwd_clear.chars().any(char::is_alphabetic) {
+        return Err(Error::PwdMissingLetter);
+    }
+    if !pwd_clear.chars().any(|c| c.is_ascii_digit()) {
+        return Err(Error::PwdMissingDigit);
+    }
+
+    Ok(())
+}
+
 fn hash_for_scheme(scheme_name: &str, to_hash: ContentToHash) -> Result<String> {
     let scheme = get_scheme(scheme_name)?;
     let pwd_hashed = scheme.hash(&to_hash)?;
@@ -98,4 +123,28 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_check_pwd_strength() -> Result<()> {
+        check_pwd_strength("welcome2rust")?;
+
+        assert!(matches!(
+            check_pwd_strength("abc1"),
+            Err(Error::PwdTooShort { min: PWD_MIN_LEN })
+        ));
+        assert!(matches!(
+            check_pwd_strength(&format!("a1{}", "x".repeat(PWD_MAX_LEN))),
+            Err(Error::PwdTooLong { max: PWD_MAX_LEN })
+        ));
+        assert!(matches!(
+            check_pwd_strength("12345678"),
+            Err(Error::PwdMissingLetter)
+        ));
+        assert!(matches!(
+            check_pwd_strength("password"),
+            Err(Error::PwdMissingDigit)
+        ));
+
+        Ok(())
+    }
 }
@@ -9,16 +9,14 @@ pub enum Error {
     InvalidFormat,
     CannotDecodeIdent,
     CannotDecodeExp,
+    CannotDecodeGeneration,
     SignatureNotMatching,
     ExpNotIso, // ISO 8601 format
     Expired,
 }
 
 impl core::fmt::Display for Error {
-    fn fmt(
-        &self, 
-        fmt: &mut core::fmt::Formatter
-    ) -> core::result::Result<(), core::fmt::Error> {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::result::Result<(), core::fmt::Error> {
         write!(fmt, "{self:?}")
     }
 }
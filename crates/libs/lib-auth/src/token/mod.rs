@@ -5,7 +5,7 @@ pub use self::error::{Error, Result};
 use crate::config::auth_config;
 use hmac::{Hmac, Mac};
 use lib_utils::b64::{b64u_decode_to_string, b64u_encode};
-use lib_utils::time::{now_utc, now_utc_plus_sec_str, parse_utc};
+use lib_utils::time::{now_utc, now_utc_plus_sec, now_utc_plus_sec_str, parse_utc, OffsetDateTime};
 use sha2::Sha512;
 use std::fmt::Display;
 use std::str::FromStr;
@@ -51,6 +51,95 @@ impl Display for Token {
     }
 }
 
+// region: Refresh Token
+/// Long-lived token used to rotate the web token of a session.
+/// Format: `b64u(session_uuid).b64u(generation).sign_b64u`
+/// (The generation is incremented on each use, so a reused token is detected server side)
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct RefreshToken {
+    pub session_uuid: Uuid,
+    pub generation: i64,
+    pub sign_b64u: String,
+}
+
+impl FromStr for RefreshToken {
+    type Err = Error;
+
+    fn from_str(token_str: &str) -> std::result::Result<Self, Self::Err> {
+        let splits: Vec<&str> = token_str.split('.').collect();
+        if splits.len() != 3 {
+            return Err(Error::InvalidFormat);
+        }
+        let (uuid_b64u, generation_b64u, sign_b64u) = (splits[0], splits[1], splits[2]);
+
+        Ok(Self {
+            session_uuid: b64u_decode_to_string(uuid_b64u)
+                .ok()
+                .and_then(|uuid| Uuid::parse_str(&uuid).ok())
+                .ok_or(Error::CannotDecodeIdent)?,
+            generation: b64u_decode_to_string(generation_b64u)
+                .ok()
+                .and_then(|generation| generation.parse().ok())
+                .ok_or(Error::CannotDecodeGeneration)?,
+            sign_b64u: sign_b64u.to_string(),
+        })
+    }
+}
+
+impl Display for RefreshToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}",
+            b64u_encode(self.session_uuid.to_string()),
+            b64u_encode(self.generation.to_string()),
+            self.sign_b64u
+        )
+    }
+}
+
+/// Generate a refresh token for the given session generation
+pub fn generate_refresh_token(
+    session_uuid: Uuid,
+    generation: i64,
+    salt: Uuid,
+) -> Result<RefreshToken> {
+    let config = &auth_config();
+    let sign_b64u =
+        _refresh_token_sign_into_b64u(session_uuid, generation, salt, &config.TOKEN_KEY)?;
+
+    Ok(RefreshToken {
+        session_uuid,
+        generation,
+        sign_b64u,
+    })
+}
+
+/// Expiration time of a new (or rotated) refresh token
+pub fn refresh_token_exp() -> OffsetDateTime {
+    now_utc_plus_sec(auth_config().REFRESH_TOKEN_DURATION_SEC)
+}
+
+/// Validate the signature of a refresh token
+/// (Expiration, revocation and generation are checked against the stored session)
+pub fn validate_refresh_token_sign(origin_token: &RefreshToken, salt: Uuid) -> Result<()> {
+    let config = &auth_config();
+    let new_sign_b64u = _refresh_token_sign_into_b64u(
+        origin_token.session_uuid,
+        origin_token.generation,
+        salt,
+        &config.TOKEN_KEY,
+    )?;
+
+    if new_sign_b64u != origin_token.sign_b64u {
+        return Err(Error::SignatureNotMatching);
+    }
+
+    Ok(())
+}
+// endregion: Refresh Token
+
 /// Generate a web token for the user
 pub fn generate_web_token(user: &str, salt: Uuid) -> Result<Token> {
     let config = &auth_config();
@@ -66,12 +155,7 @@ pub fn validate_web_token(origin_token: &Token, salt: Uuid) -> Result<()> {
 }
 
 // region: Private functions
-fn _generate_token(
-    identifier: &str, 
-    duration_sec: i64, 
-    salt: Uuid, 
-    key: &[u8]
-) -> Result<Token> {
+fn _generate_token(identifier: &str, duration_sec: i64, salt: Uuid, key: &[u8]) -> Result<Token> {
     let identifier = identifier.to_string();
     let exp = now_utc_plus_sec_str(duration_sec);
     let sign_b64u = _token_sign_into_b64u(&identifier, &exp, salt, key)?;
@@ -83,11 +167,7 @@ fn _generate_token(
     })
 }
 
-fn _validate_token_sign_and_exp(
-    origin_token: &Token, 
-    salt: Uuid, 
-    key: &[u8]
-) -> Result<()> {
+fn _validate_token_sign_and_exp(origin_token: &Token, salt: Uuid, key: &[u8]) -> Result<()> {
     let new_sign_b64u =
         _token_sign_into_b64u(&origin_token.identifier, &origin_token.exp, salt, key)?;
 
@@ -104,13 +184,23 @@ fn _validate_token_sign_and_exp(
     Ok(())
 }
 
-// Create a token from the identifier, expiration date and salt.
-fn _token_sign_into_b64u(
-    identifier: &str, 
-    exp: &str, 
-    salt: Uuid, 
-    key: &[u8]
+// Sign a refresh token (prefixed, so it can never be used as a web token signature).
+fn _refresh_token_sign_into_b64u(
+    session_uuid: Uuid,
+    generation: i64,
+    salt: Uuid,
+    key: &[u8],
 ) -> Result<String> {
+    _token_sign_into_b64u(
+        &format!("refresh.{session_uuid}"),
+        &generation.to_string(),
+        salt,
+        key,
+    )
+}
+
+// Create a token from the identifier, expiration date and salt.
+fn _token_sign_into_b64u(identifier: &str, exp: &str, salt: Uuid, key: &[u8]) -> Result<String> {
     let content = format!("{}.{}", b64u_encode(identifier), b64u_encode(exp));
     // Hmac is a struct that represents the HMAC algorithm.
     // b64u encode is not encryption process, it is encoding process.
@@ -183,6 +273,38 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_refresh_token_display_and_parse_ok() -> Result<()> {
+        let fx_uuid = Uuid::parse_str("7b6ae2e1-6a84-4b7e-9a3e-39a4fb2d6e7c")?;
+        let fx_salt = Uuid::parse_str("e5d87716-65d9-4450-8f59-316ce50962fa")?;
+        let fx_token = generate_refresh_token(fx_uuid, 3, fx_salt)?;
+
+        let token: RefreshToken = fx_token.to_string().parse()?;
+
+        assert_eq!(token, fx_token);
+        validate_refresh_token_sign(&token, fx_salt)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_refresh_token_err_sign_not_matching() -> Result<()> {
+        let fx_uuid = Uuid::parse_str("7b6ae2e1-6a84-4b7e-9a3e-39a4fb2d6e7c")?;
+        let fx_salt = Uuid::parse_str("e5d87716-65d9-4450-8f59-316ce50962fa")?;
+        let fx_other_salt = Uuid::parse_str("0f0e6c2b-8a43-4c5f-9f0a-5fb0f9f1d2a1")?;
+        let mut fx_token = generate_refresh_token(fx_uuid, 3, fx_salt)?;
+
+        let res_salt = validate_refresh_token_sign(&fx_token, fx_other_salt);
+        // A forged generation does not match the signature
+        fx_token.generation = 4;
+        let res_generation = validate_refresh_token_sign(&fx_token, fx_salt);
+
+        assert!(matches!(res_salt, Err(Error::SignatureNotMatching)));
+        assert!(matches!(res_generation, Err(Error::SignatureNotMatching)));
+
+        Ok(())
+    }
+
     #[test]
     fn test_validate_web_token_expired() -> Result<()> {
         let fx_user = "user_01";
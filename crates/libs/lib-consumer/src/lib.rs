@@ -1,8 +1,9 @@
 mod config;
 
-use lib_core::model::scylla::db_conn;
+use lib_core::model::redis_cache::RedisManager;
 use lib_core::model::scylla::hnstory::add_hnstory;
 use lib_core::model::scylla::hnstory::HNStory;
+use lib_core::model::scylla::ScyllaManager;
 use lib_producer::token::BitcoinInfo;
 
 use std::pin::Pin;
@@ -89,10 +90,23 @@ pub async fn consume(topic_name: &str) {
     // Sleep for 2 seconds to allow the consumer to start consuming messages
     tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
 
-    // Create a ScyllaDB session
-    let session = db_conn()
+    // Create the ScyllaDB manager (session and prepared statements)
+    let sm = ScyllaManager::new()
         .await
-        .expect("--> Kafka Consumer: Failed to create ScyllaDB session");
+        .expect("--> Kafka Consumer: Failed to create ScyllaDB manager");
+
+    // Create the Redis manager (the cached stories are invalidated when a story is added)
+    // (Without Redis, the stories are still added, the cached ones expire with their TTL)
+    let rm = match RedisManager::initialize().await {
+        Ok(rm) => Some(rm),
+        Err(e) => {
+            error!(
+                "--> Kafka Consumer: Failed to create Redis manager, no cache invalidation: {:?}",
+                e
+            );
+            None
+        }
+    };
 
     // Infinite loop to continuously consume messages
     loop {
@@ -137,7 +151,7 @@ pub async fn consume(topic_name: &str) {
                 if !payload.is_empty() {
                     match serde_json::from_str::<HNStory>(payload) {
                         Ok(hnstory) => {
-                            if let Err(e) = add_hnstory(&session, hnstory).await {
+                            if let Err(e) = add_hnstory(&sm, rm.as_deref(), hnstory).await {
                                 error!("--> Kafka Consumer: Failed to add {}: {}", m.topic(), e);
                             }
                         }
@@ -0,0 +1,4 @@
+// Rebuild when the embedded sql migrations change (see `model::psql::migration`).
+fn main() {
+    println!("cargo:rerun-if-changed=../../../sql/psql/migrations");
+}
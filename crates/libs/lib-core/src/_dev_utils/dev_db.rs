@@ -1,5 +1,6 @@
 use crate::core_config;
 use crate::ctx::Ctx;
+use crate::model::psql::migration;
 use crate::model::psql::user::{User, UserBmc};
 use crate::model::psql::ModelManager;
 use sqlx::postgres::PgPoolOptions;
@@ -59,7 +60,11 @@ pub async fn init_dev_db() -> Result<(), Box<dyn Error>> {
         p_exec(&root_db, &sql_recreate_db_file).await?;
     }
 
-    // Get sql files
+    // Apply the schema migrations
+    let mm = ModelManager::new().await?;
+    migration::migrate_up(&mm).await?;
+
+    // Get sql files (dev seed)
     let mut paths: Vec<PathBuf> = fs::read_dir(sql_dir)?
         .filter_map(|entry| entry.ok().map(|e| e.path()))
         .collect();
@@ -76,7 +81,6 @@ pub async fn init_dev_db() -> Result<(), Box<dyn Error>> {
         }
     }
 
-    let mm = ModelManager::new().await?;
     let ctx = Ctx::root_ctx();
 
     let demo1_user: User = UserBmc::first_by_username(&ctx, &mm, "demo1")
@@ -94,6 +98,8 @@ async fn p_exec(db: &Db, file: &Path) -> Result<(), sqlx::Error> {
     let content = fs::read_to_string(file)?;
 
     // Split the content by semicolon character
+    // (each statement runs on its own, e.g., CREATE DATABASE cannot run in a transaction block.
+    //  Schema changes with function bodies belong to the migrations)
     let sqls: Vec<&str> = content.split(';').collect();
 
     for sql in sqls {
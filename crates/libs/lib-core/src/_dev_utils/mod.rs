@@ -4,6 +4,7 @@ mod dev_db;
 
 use crate::ctx::Ctx;
 use crate::model;
+use crate::model::psql::project::{ProjectBmc, ProjectForCreate};
 use crate::model::psql::task::{Task, TaskBmc, TaskForCreate};
 use crate::model::psql::ModelManager;
 use tokio::sync::OnceCell;
@@ -42,10 +43,23 @@ pub async fn init_test() -> ModelManager {
     mm.clone()
 }
 
+/// Seed a project for testing.
+pub async fn seed_project(ctx: &Ctx, mm: &ModelManager, name: &str) -> model::psql::Result<i64> {
+    ProjectBmc::create(
+        ctx,
+        mm,
+        ProjectForCreate {
+            name: name.to_string(),
+        },
+    )
+    .await
+}
+
 /// Seed tasks for testing.
 pub async fn seed_tasks(
     ctx: &Ctx,
     mm: &ModelManager,
+    project_id: i64,
     titles: &[&str],
 ) -> model::psql::Result<Vec<Task>> {
     let mut tasks = Vec::new();
@@ -55,7 +69,9 @@ pub async fn seed_tasks(
             ctx,
             mm,
             TaskForCreate {
+                project_id,
                 title: title.to_string(),
+                ..Default::default()
             },
         )
         .await?;
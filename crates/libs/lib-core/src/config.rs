@@ -1,4 +1,4 @@
-use lib_utils::envs::get_env;
+use lib_utils::envs::{get_env, get_env_parse_positive};
 use std::sync::LazyLock;
 
 pub static CORE_CONFIG: LazyLock<CoreConfig> = LazyLock::new(|| {
@@ -20,9 +20,16 @@ pub struct CoreConfig {
     pub SCYLLA_DB_URL: String,
     pub SCYLLA_DB_USERNAME: String,
     pub SCYLLA_DB_PASSWORD: String,
+    pub SCYLLA_KEYSPACE: String,
+    // e.g., `SimpleStrategy:1`, `NetworkTopologyStrategy:3` or `NetworkTopologyStrategy:dc1=3,dc2=2`
+    pub SCYLLA_REPLICATION: String,
 
     // -- Redis
     pub REDIS_URL: String,
+
+    // -- Task trash
+    // Seconds a deleted task stays restorable before being purged (greater than zero)
+    pub TASK_TRASH_RETENTION_SEC: i64,
 }
 
 impl CoreConfig {
@@ -36,9 +43,14 @@ impl CoreConfig {
             SCYLLA_DB_URL: get_env("SERVICE_SCYLLA_DB_URL")?,
             SCYLLA_DB_USERNAME: get_env("SERVICE_SCYLLA_DB_USER")?,
             SCYLLA_DB_PASSWORD: get_env("SERVICE_SCYLLA_DB_PASSWORD")?,
+            SCYLLA_KEYSPACE: get_env("SERVICE_SCYLLA_KEYSPACE")?,
+            SCYLLA_REPLICATION: get_env("SERVICE_SCYLLA_REPLICATION")?,
 
             // -- Redis
             REDIS_URL: get_env("SERVICE_REDIS_URL")?,
+
+            // -- Task trash (zero or less would purge the tasks as soon as deleted)
+            TASK_TRASH_RETENTION_SEC: get_env_parse_positive("SERVICE_TASK_TRASH_RETENTION_SEC")?,
         })
     }
 }
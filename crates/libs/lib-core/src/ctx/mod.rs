@@ -3,19 +3,26 @@
 mod error;
 
 pub use self::error::{Error, Result};
+use std::collections::HashSet;
+use std::sync::Arc;
 
 // endregion: --- Modules
 
 #[derive(Clone, Debug)]
 pub struct Ctx {
     user_id: i64,
+    // Permissions of the user roles, e.g., `task:write` (loaded by the web layer)
+    permissions: Arc<HashSet<String>>,
 }
 
 // Constructor.
 impl Ctx {
     pub fn root_ctx() -> Self {
         // Root Context is created with user_id = 0 (System User)
-        Ctx { user_id: 0 }
+        Ctx {
+            user_id: 0,
+            permissions: Arc::default(),
+        }
     }
 
     pub fn new(user_id: i64) -> Result<Self> {
@@ -23,9 +30,18 @@ impl Ctx {
         if user_id == 0 {
             Err(Error::CtxCannotNewRootCtx)
         } else {
-            Ok(Self { user_id })
+            Ok(Self {
+                user_id,
+                permissions: Arc::default(),
+            })
         }
     }
+
+    /// Return the ctx with the given permissions
+    pub fn with_permissions(mut self, permissions: impl IntoIterator<Item = String>) -> Self {
+        self.permissions = Arc::new(permissions.into_iter().collect());
+        self
+    }
 }
 
 // Property Accessors.
@@ -33,4 +49,9 @@ impl Ctx {
     pub fn user_id(&self) -> i64 {
         self.user_id
     }
+
+    /// Check if the ctx has the permission (the root ctx has all the permissions)
+    pub fn has_permission(&self, permission: &str) -> bool {
+        self.user_id == 0 || self.permissions.contains(permission)
+    }
 }
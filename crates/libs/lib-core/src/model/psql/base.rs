@@ -2,31 +2,128 @@ use crate::ctx::Ctx;
 use crate::model::psql::error::Error;
 use crate::model::psql::ModelManager;
 use crate::model::psql::Result;
-use modql::field::HasFields;
-use modql::filter::{FilterGroups, ListOptions};
+use lib_utils::b64::{b64u_decode_to_string, b64u_encode};
+use lib_utils::time::now_utc;
+use modql::field::{Field, Fields, HasFields};
+use modql::filter::{FilterGroups, ListOptions, OrderBy};
 use modql::SIden;
-use sea_query::{Condition, Expr, Iden, IntoIden, PostgresQueryBuilder, Query, TableRef};
+use schemars::JsonSchema;
+use sea_query::{
+    Asterisk, Condition, Expr, Iden, IntoIden, Order, PostgresQueryBuilder, Query, SimpleExpr,
+    TableRef,
+};
 use sea_query_binder::SqlxBinder;
+use serde::{Deserialize, Serialize};
 use sqlx::postgres::PgRow;
-use sqlx::FromRow;
+use sqlx::{FromRow, PgConnection};
+use time::OffsetDateTime;
 
 const LIST_LIMIT_DEFAULT: i64 = 300;
 const LIST_LIMIT_MAX: i64 = 1000;
+const BATCH_SIZE_MAX: usize = 1000;
 
 #[derive(Iden)]
 pub enum CommonIden {
     Id,
+    Version,
+    DeletedAt,
+}
+
+/// Audit columns shared by all the tables
+#[derive(Iden)]
+pub enum TimestampIden {
+    Cid,
+    Ctime,
+    Mid,
+    Mtime,
 }
 
 pub trait DbBmc {
     const TABLE: &'static str;
 
+    /// The table has a `version` column, incremented on each update (optimistic concurrency)
+    const VERSIONED: bool = false;
+
+    /// The table has a `deleted_at` column, delete only marks the rows (restorable until purged)
+    const SOFT_DELETE: bool = false;
+
     fn table_ref() -> TableRef {
         // SIden is wrapper for Iden
         TableRef::Table(SIden(Self::TABLE).into_iden())
     }
+
+    /// Condition restricting the rows to the ones owned by the given user.
+    /// (None means the entity has no owner, e.g., user)
+    fn owner_cond(_user_id: i64) -> Option<SimpleExpr> {
+        None
+    }
+}
+
+/// Ownership condition of the entity for the ctx user.
+/// (The root ctx is not restricted)
+pub fn owner_cond<M: DbBmc>(ctx: &Ctx) -> Option<SimpleExpr> {
+    if ctx.user_id() == 0 {
+        None
+    } else {
+        M::owner_cond(ctx.user_id())
+    }
+}
+
+/// Condition excluding the soft deleted rows.
+/// (None if the entity is not soft deleted)
+pub fn not_deleted_cond<M: DbBmc>() -> Option<SimpleExpr> {
+    M::SOFT_DELETE.then(|| Expr::col(CommonIden::DeletedAt).is_null())
+}
+
+/// Entities that can be listed by page (the cursor is the id of the last entity of a page)
+pub trait HasId {
+    fn id(&self) -> i64;
 }
 
+// region:    --- Page Types
+/// Paging options of `list_page` (in addition to the list options)
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PageOptions {
+    /// The `next_cursor` of the previous page (absent for the first page)
+    pub cursor: Option<String>,
+    /// Count all the entities matching the filters (`total` of the page)
+    pub with_total: Option<bool>,
+}
+
+/// A page of entities
+#[derive(Debug, Serialize, JsonSchema)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Page<E> {
+    pub data: Vec<E>,
+    /// Cursor of the next page (None on the last page)
+    pub next_cursor: Option<String>,
+    /// Count of all the matching entities (only with `with_total`)
+    pub total: Option<i64>,
+}
+
+/// Opaque keyset cursor (b64u of the id of the last entity of a page)
+struct Cursor {
+    id: i64,
+}
+
+impl Cursor {
+    fn encode(&self) -> String {
+        b64u_encode(format!("id:{}", self.id))
+    }
+
+    fn decode(cursor: &str) -> Result<Self> {
+        b64u_decode_to_string(cursor)
+            .ok()
+            .and_then(|content| content.strip_prefix("id:")?.parse().ok())
+            .map(|id| Cursor { id })
+            .ok_or_else(|| Error::ListCursorInvalid {
+                cursor: cursor.to_string(),
+            })
+    }
+}
+// endregion: --- Page Types
+
 /// Finalize the list options with default values and check the limit
 pub fn finalize_list_options(list_options: Option<ListOptions>) -> Result<ListOptions> {
     if let Some(mut list_options) = list_options {
@@ -50,17 +147,33 @@ pub fn finalize_list_options(list_options: Option<ListOptions>) -> Result<ListOp
     }
 }
 
+/// Add the creator and modifier audit fields (cid, ctime, mid, mtime)
+pub fn prep_fields_for_create(fields: &mut Fields, ctx: &Ctx) {
+    let now = now_utc();
+    fields.push(Field::new(TimestampIden::Cid, ctx.user_id().into()));
+    fields.push(Field::new(TimestampIden::Ctime, now.into()));
+    fields.push(Field::new(TimestampIden::Mid, ctx.user_id().into()));
+    fields.push(Field::new(TimestampIden::Mtime, now.into()));
+}
+
+/// Add the modifier audit fields (mid, mtime)
+pub fn prep_fields_for_update(fields: &mut Fields, ctx: &Ctx) {
+    fields.push(Field::new(TimestampIden::Mid, ctx.user_id().into()));
+    fields.push(Field::new(TimestampIden::Mtime, now_utc().into()));
+}
+
 /// CRUD operations
 /// Insert a new entity
-pub async fn create<M, E>(_ctx: &Ctx, mm: &ModelManager, data: E) -> Result<i64>
+pub async fn create<M, E>(ctx: &Ctx, mm: &ModelManager, data: E) -> Result<i64>
 where
     M: DbBmc,
     E: HasFields,
 {
-    let db = mm.db();
+    let mut conn = mm.conn().await?;
 
     // Extract the fields and values
-    let fields = data.not_none_fields();
+    let mut fields = data.not_none_fields();
+    prep_fields_for_create(&mut fields, ctx);
     // Unzip
     let (columns, sea_values) = fields.for_sea_insert();
 
@@ -75,7 +188,7 @@ where
     // Execute the query
     let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
     let (id,) = sqlx::query_as_with::<_, (i64,), _>(&sql, values)
-        .fetch_one(db)
+        .fetch_one(&mut *conn)
         .await?;
 
     Ok(id)
@@ -83,25 +196,27 @@ where
 
 /// CRUD operations
 /// Get an entity by id
-pub async fn get<M, E>(_ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<E>
+pub async fn get<M, E>(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<E>
 where
     M: DbBmc,
     E: for<'r> FromRow<'r, PgRow> + Unpin + Send,
     E: HasFields,
 {
-    let db = mm.db();
+    let mut conn = mm.conn().await?;
 
     // Build the SQL query
     let mut query = Query::select();
     query
         .from(M::table_ref())
         .columns(E::field_column_refs())
-        .and_where(Expr::col(CommonIden::Id).eq(id));
+        .and_where(Expr::col(CommonIden::Id).eq(id))
+        .and_where_option(owner_cond::<M>(ctx))
+        .and_where_option(not_deleted_cond::<M>());
 
     // Execute the query
     let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
     let entity = sqlx::query_as_with::<_, E, _>(&sql, values)
-        .fetch_optional(db)
+        .fetch_optional(&mut *conn)
         .await?
         .ok_or(Error::EntityNotFound {
             entity: M::TABLE,
@@ -112,12 +227,28 @@ where
 }
 
 /// CRUD operations
-/// Get a list of entities with filters
+/// Get a list of entities with filters (without the soft deleted ones)
 pub async fn list<M, E, F>(
-    _ctx: &Ctx,
+    ctx: &Ctx,
+    mm: &ModelManager,
+    filters: Option<F>,
+    list_options: Option<ListOptions>,
+) -> Result<Vec<E>>
+where
+    M: DbBmc,
+    F: Into<FilterGroups>,
+    E: for<'r> FromRow<'r, PgRow> + Unpin + Send,
+    E: HasFields,
+{
+    list_where::<M, E, F>(ctx, mm, filters, list_options, not_deleted_cond::<M>()).await
+}
+
+async fn list_where<M, E, F>(
+    ctx: &Ctx,
     mm: &ModelManager,
     filters: Option<F>,
     list_options: Option<ListOptions>,
+    cond: Option<SimpleExpr>,
 ) -> Result<Vec<E>>
 where
     M: DbBmc,
@@ -125,7 +256,7 @@ where
     E: for<'r> FromRow<'r, PgRow> + Unpin + Send,
     E: HasFields,
 {
-    let db = mm.db();
+    let mut conn = mm.conn().await?;
 
     // Build the SQL query
     let mut query = Query::select();
@@ -138,6 +269,10 @@ where
         query.cond_where(cond);
     }
 
+    // condition from ownership and deletion
+    query.and_where_option(owner_cond::<M>(ctx));
+    query.and_where_option(cond);
+
     // list options
     let list_options = finalize_list_options(list_options)?;
     // apply list options(limit, offset, order_by)
@@ -146,21 +281,198 @@ where
     // Execute the query
     let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
     let entities = sqlx::query_as_with::<_, E, _>(&sql, values)
-        .fetch_all(db)
+        .fetch_all(&mut *conn)
         .await?;
 
     Ok(entities)
 }
 
-pub async fn update<M, E>(_ctx: &Ctx, mm: &ModelManager, id: i64, data: E) -> Result<()>
+/// CRUD operations
+/// Get a page of entities with filters (without the soft deleted ones)
+/// (Paged by keyset cursor, so only ordered by `id` or `!id` without offset,
+///  `ListCursorOrderUnsupported` otherwise)
+pub async fn list_page<M, E, F>(
+    ctx: &Ctx,
+    mm: &ModelManager,
+    filters: Option<F>,
+    list_options: Option<ListOptions>,
+    page_options: Option<PageOptions>,
+) -> Result<Page<E>>
+where
+    M: DbBmc,
+    F: Into<FilterGroups>,
+    E: for<'r> FromRow<'r, PgRow> + Unpin + Send,
+    E: HasFields + HasId,
+{
+    let cond = not_deleted_cond::<M>();
+    list_page_where::<M, E, F>(ctx, mm, filters, list_options, page_options, cond).await
+}
+
+/// Get a page of the soft deleted entities with filters (the trash, see `list_page`)
+pub async fn list_deleted_page<M, E, F>(
+    ctx: &Ctx,
+    mm: &ModelManager,
+    filters: Option<F>,
+    list_options: Option<ListOptions>,
+    page_options: Option<PageOptions>,
+) -> Result<Page<E>>
+where
+    M: DbBmc,
+    F: Into<FilterGroups>,
+    E: for<'r> FromRow<'r, PgRow> + Unpin + Send,
+    E: HasFields + HasId,
+{
+    // (No trash when not soft deleted)
+    let cond = if M::SOFT_DELETE {
+        Expr::col(CommonIden::DeletedAt).is_not_null()
+    } else {
+        Expr::val(false).into()
+    };
+    list_page_where::<M, E, F>(ctx, mm, filters, list_options, page_options, Some(cond)).await
+}
+
+async fn list_page_where<M, E, F>(
+    ctx: &Ctx,
+    mm: &ModelManager,
+    filters: Option<F>,
+    list_options: Option<ListOptions>,
+    page_options: Option<PageOptions>,
+    deletion_cond: Option<SimpleExpr>,
+) -> Result<Page<E>>
+where
+    M: DbBmc,
+    F: Into<FilterGroups>,
+    E: for<'r> FromRow<'r, PgRow> + Unpin + Send,
+    E: HasFields + HasId,
+{
+    let mut conn = mm.conn().await?;
+    let PageOptions { cursor, with_total } = page_options.unwrap_or_default();
+
+    // list options
+    let mut list_options = finalize_list_options(list_options)?;
+    let limit = list_options.limit.unwrap_or(LIST_LIMIT_DEFAULT);
+    let keyset_order = keyset_order(&list_options).ok_or(Error::ListCursorOrderUnsupported)?;
+    let cursor = cursor.as_deref().map(Cursor::decode).transpose()?;
+
+    // condition from filter, ownership and deletion (shared by the page and total queries)
+    let mut cond = Condition::all();
+    if let Some(filter) = filters {
+        let filters: FilterGroups = filter.into();
+        let filter_cond: Condition = filters.try_into()?;
+        cond = cond.add(filter_cond);
+    }
+    if let Some(owner_cond) = owner_cond::<M>(ctx) {
+        cond = cond.add(owner_cond);
+    }
+    if let Some(deletion_cond) = deletion_cond {
+        cond = cond.add(deletion_cond);
+    }
+
+    // Build the SQL query
+    let mut query = Query::select();
+    query.from(M::table_ref()).columns(E::field_column_refs());
+
+    // condition from cursor (the entities after the last one of the previous page)
+    let mut page_cond = cond.clone();
+    if let Some(cursor) = cursor {
+        let id_col = Expr::col(CommonIden::Id);
+        page_cond = match keyset_order {
+            Order::Desc => page_cond.add(id_col.lt(cursor.id)),
+            _ => page_cond.add(id_col.gt(cursor.id)),
+        };
+    }
+    query.cond_where(page_cond);
+
+    // apply list options (one more entity to know if there is a next page)
+    list_options.limit = Some(limit + 1);
+    list_options.apply_to_sea_query(&mut query);
+    if let Order::Asc = keyset_order {
+        // Make the default order explicit for the keyset paging
+        query.order_by(CommonIden::Id, Order::Asc);
+    }
+
+    // Execute the query
+    let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+    let mut entities = sqlx::query_as_with::<_, E, _>(&sql, values)
+        .fetch_all(&mut *conn)
+        .await?;
+
+    let mut next_cursor = None;
+    if entities.len() as i64 > limit {
+        entities.truncate(limit as usize);
+        next_cursor = entities.last().map(|e| Cursor { id: e.id() }.encode());
+    }
+
+    // total count (optional)
+    let total = if with_total.unwrap_or(false) {
+        let mut query = Query::select();
+        query
+            .from(M::table_ref())
+            .expr(Expr::col(Asterisk).count())
+            .cond_where(cond);
+
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+        let total = sqlx::query_scalar_with::<_, i64, _>(&sql, values)
+            .fetch_one(&mut *conn)
+            .await?;
+        Some(total)
+    } else {
+        None
+    };
+
+    Ok(Page {
+        data: entities,
+        next_cursor,
+        total,
+    })
+}
+
+/// The id order of the keyset paging, None if the list options do not allow it
+fn keyset_order(list_options: &ListOptions) -> Option<Order> {
+    if list_options.offset.is_some() {
+        return None;
+    }
+
+    let Some(order_bys) = &list_options.order_bys else {
+        return Some(Order::Asc);
+    };
+    let mut order_bys = order_bys.into_iter();
+    match (order_bys.next(), order_bys.next()) {
+        (None, _) => Some(Order::Asc),
+        (Some(OrderBy::Asc(col)), None) if col == "id" => Some(Order::Asc),
+        (Some(OrderBy::Desc(col)), None) if col == "id" => Some(Order::Desc),
+        _ => None,
+    }
+}
+
+pub async fn update<M, E>(ctx: &Ctx, mm: &ModelManager, id: i64, data: E) -> Result<()>
 where
     M: DbBmc,
     E: HasFields,
 {
-    let db = mm.db();
+    update_with_version::<M, E>(ctx, mm, id, data, None).await
+}
+
+/// Update an entity only if it is still at the given version (optimistic concurrency)
+/// (`VersionConflict` if it was updated since the client read it, the version is ignored
+///  if the table is not versioned)
+pub async fn update_with_version<M, E>(
+    ctx: &Ctx,
+    mm: &ModelManager,
+    id: i64,
+    data: E,
+    version: Option<i64>,
+) -> Result<()>
+where
+    M: DbBmc,
+    E: HasFields,
+{
+    let mut conn = mm.conn().await?;
+    let version = version.filter(|_| M::VERSIONED);
 
     // Prepare the fields and values
-    let fields = data.not_none_fields();
+    let mut fields = data.not_none_fields();
+    prep_fields_for_update(&mut fields, ctx);
     // Zip
     let fields = fields.for_sea_update();
 
@@ -169,16 +481,96 @@ where
     query
         .table(M::table_ref())
         .values(fields)
-        .and_where(Expr::col(CommonIden::Id).eq(id));
+        .and_where(Expr::col(CommonIden::Id).eq(id))
+        .and_where_option(owner_cond::<M>(ctx))
+        .and_where_option(not_deleted_cond::<M>());
+    if M::VERSIONED {
+        query.value(CommonIden::Version, Expr::col(CommonIden::Version).add(1));
+    }
+    if let Some(version) = version {
+        query.and_where(Expr::col(CommonIden::Version).eq(version));
+    }
 
     // Execute the query
     let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
     let count = sqlx::query_with(&sql, values)
-        .execute(db)
+        .execute(&mut *conn)
         .await?
         .rows_affected();
 
     // Check if the entity was updated
+    if count > 0 {
+        return Ok(());
+    }
+
+    // Not updated, because the entity is not found or it is not at the expected version
+    if let Some(expected) = version {
+        if let Some(actual) = get_version::<M>(ctx, &mut conn, id).await? {
+            return Err(Error::VersionConflict {
+                entity: M::TABLE,
+                id,
+                expected,
+                actual,
+            });
+        }
+    }
+
+    Err(Error::EntityNotFound {
+        entity: M::TABLE,
+        id,
+    })
+}
+
+/// Get the current version of an entity (None if not found)
+async fn get_version<M>(ctx: &Ctx, conn: &mut PgConnection, id: i64) -> Result<Option<i64>>
+where
+    M: DbBmc,
+{
+    // Build the SQL query
+    let mut query = Query::select();
+    query
+        .from(M::table_ref())
+        .column(CommonIden::Version)
+        .and_where(Expr::col(CommonIden::Id).eq(id))
+        .and_where_option(owner_cond::<M>(ctx))
+        .and_where_option(not_deleted_cond::<M>());
+
+    // Execute the query
+    let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+    let version = sqlx::query_scalar_with::<_, i64, _>(&sql, values)
+        .fetch_optional(conn)
+        .await?;
+
+    Ok(version)
+}
+
+/// Delete an entity
+/// (Only marked as deleted if the table is soft deleted, see `restore` and `purge_deleted`)
+pub async fn delete<M>(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()>
+where
+    M: DbBmc,
+{
+    if M::SOFT_DELETE {
+        return set_deleted_at::<M>(ctx, mm, id, Some(now_utc())).await;
+    }
+
+    let mut conn = mm.conn().await?;
+
+    // Build the SQL query
+    let mut query = Query::delete();
+    query
+        .from_table(M::table_ref())
+        .and_where(Expr::col(CommonIden::Id).eq(id))
+        .and_where_option(owner_cond::<M>(ctx));
+
+    // Execute the query
+    let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+    let count = sqlx::query_with(&sql, values)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+
+    // Check if the entity was deleted
     if count == 0 {
         Err(Error::EntityNotFound {
             entity: M::TABLE,
@@ -189,26 +581,64 @@ where
     }
 }
 
-pub async fn delete<M>(_ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()>
+/// Restore a soft deleted entity
+/// (`EntityNotFound` if it is not deleted, or already purged)
+pub async fn restore<M>(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()>
 where
     M: DbBmc,
 {
-    let db = mm.db();
+    if !M::SOFT_DELETE {
+        return Err(Error::EntityNotFound {
+            entity: M::TABLE,
+            id,
+        });
+    }
+
+    set_deleted_at::<M>(ctx, mm, id, None).await
+}
+
+/// Mark (Some) or unmark (None) an entity as deleted.
+/// (Only the entities in the opposite state are updated)
+async fn set_deleted_at<M>(
+    ctx: &Ctx,
+    mm: &ModelManager,
+    id: i64,
+    deleted_at: Option<OffsetDateTime>,
+) -> Result<()>
+where
+    M: DbBmc,
+{
+    // Prepare the fields and values
+    let mut fields = Fields::new(vec![Field::new(CommonIden::DeletedAt, deleted_at.into())]);
+    prep_fields_for_update(&mut fields, ctx);
+    let fields = fields.for_sea_update();
+
+    let state_cond = match deleted_at {
+        Some(_) => Expr::col(CommonIden::DeletedAt).is_null(),
+        None => Expr::col(CommonIden::DeletedAt).is_not_null(),
+    };
 
     // Build the SQL query
-    let mut query = Query::delete();
+    let mut query = Query::update();
     query
-        .from_table(M::table_ref())
-        .and_where(Expr::col(CommonIden::Id).eq(id));
+        .table(M::table_ref())
+        .values(fields)
+        .and_where(Expr::col(CommonIden::Id).eq(id))
+        .and_where_option(owner_cond::<M>(ctx))
+        .and_where(state_cond);
+    // (A delete or restore is a new version, the updates read before it are stale)
+    if M::VERSIONED {
+        query.value(CommonIden::Version, Expr::col(CommonIden::Version).add(1));
+    }
 
     // Execute the query
+    let mut conn = mm.conn().await?;
     let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
     let count = sqlx::query_with(&sql, values)
-        .execute(db)
+        .execute(&mut *conn)
         .await?
         .rows_affected();
 
-    // Check if the entity was deleted
     if count == 0 {
         Err(Error::EntityNotFound {
             entity: M::TABLE,
@@ -218,3 +648,170 @@ where
         Ok(())
     }
 }
+
+/// Permanently delete the entities soft deleted before the given time
+/// (Returns the ids of the purged entities)
+pub async fn purge_deleted<M>(
+    ctx: &Ctx,
+    mm: &ModelManager,
+    deleted_before: OffsetDateTime,
+) -> Result<Vec<i64>>
+where
+    M: DbBmc,
+{
+    if !M::SOFT_DELETE {
+        return Ok(Vec::new());
+    }
+
+    let mut conn = mm.conn().await?;
+
+    // Build the SQL query
+    let mut query = Query::delete();
+    query
+        .from_table(M::table_ref())
+        .and_where(Expr::col(CommonIden::DeletedAt).lt(deleted_before))
+        .and_where_option(owner_cond::<M>(ctx))
+        .returning_col(CommonIden::Id);
+
+    // Execute the query
+    let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+    let ids = sqlx::query_scalar_with::<_, i64, _>(&sql, values)
+        .fetch_all(&mut *conn)
+        .await?;
+
+    Ok(ids)
+}
+
+// region:    --- Batch
+/// Insert the entities in one transaction (all or none)
+/// (Returns the created entities in the data order)
+pub async fn create_many<M, E, R>(ctx: &Ctx, mm: &ModelManager, data: Vec<E>) -> Result<Vec<R>>
+where
+    M: DbBmc,
+    E: HasFields,
+    R: for<'r> FromRow<'r, PgRow> + Unpin + Send,
+    R: HasFields,
+{
+    check_batch_size(data.len())?;
+    let batch = BatchTxn::begin(mm).await?;
+
+    let mut entities = Vec::with_capacity(data.len());
+    for (index, item) in data.into_iter().enumerate() {
+        let entity = async {
+            let id = create::<M, E>(ctx, &batch.mm, item).await?;
+            get::<M, R>(ctx, &batch.mm, id).await
+        }
+        .await
+        .map_err(|err| Error::batch_item_failed(index, err))?;
+        entities.push(entity);
+    }
+
+    batch.commit().await?;
+
+    Ok(entities)
+}
+
+/// Update the entities in one transaction (all or none)
+/// The items are `(id, data, version)`, see `update_with_version` for the version.
+/// (Returns the updated entities in the items order)
+pub async fn update_many<M, E, R>(
+    ctx: &Ctx,
+    mm: &ModelManager,
+    items: Vec<(i64, E, Option<i64>)>,
+) -> Result<Vec<R>>
+where
+    M: DbBmc,
+    E: HasFields,
+    R: for<'r> FromRow<'r, PgRow> + Unpin + Send,
+    R: HasFields,
+{
+    check_batch_size(items.len())?;
+    let batch = BatchTxn::begin(mm).await?;
+
+    let mut entities = Vec::with_capacity(items.len());
+    for (index, (id, data, version)) in items.into_iter().enumerate() {
+        let entity = async {
+            update_with_version::<M, E>(ctx, &batch.mm, id, data, version).await?;
+            get::<M, R>(ctx, &batch.mm, id).await
+        }
+        .await
+        .map_err(|err| Error::batch_item_failed(index, err))?;
+        entities.push(entity);
+    }
+
+    batch.commit().await?;
+
+    Ok(entities)
+}
+
+/// Delete the entities in one transaction (all or none)
+/// (Returns the entities as they were before the delete, in the ids order)
+pub async fn delete_many<M, R>(ctx: &Ctx, mm: &ModelManager, ids: Vec<i64>) -> Result<Vec<R>>
+where
+    M: DbBmc,
+    R: for<'r> FromRow<'r, PgRow> + Unpin + Send,
+    R: HasFields,
+{
+    check_batch_size(ids.len())?;
+    let batch = BatchTxn::begin(mm).await?;
+
+    let mut entities = Vec::with_capacity(ids.len());
+    for (index, id) in ids.into_iter().enumerate() {
+        let entity = async {
+            let entity = get::<M, R>(ctx, &batch.mm, id).await?;
+            delete::<M>(ctx, &batch.mm, id).await?;
+            Ok(entity)
+        }
+        .await
+        .map_err(|err| Error::batch_item_failed(index, err))?;
+        entities.push(entity);
+    }
+
+    batch.commit().await?;
+
+    Ok(entities)
+}
+
+/// The transaction of a batch (or of the writes of a bmc call that must be atomic).
+/// Joins the open transaction of the given ModelManager (committed by its owner),
+/// or begins its own (rolled back if dropped before `commit`).
+pub(in crate::model::psql) struct BatchTxn {
+    pub mm: ModelManager,
+    own_txn: bool,
+}
+
+impl BatchTxn {
+    pub async fn begin(mm: &ModelManager) -> Result<Self> {
+        if mm.in_txn().await {
+            return Ok(BatchTxn {
+                mm: mm.clone(),
+                own_txn: false,
+            });
+        }
+
+        let mm = mm.new_with_txn();
+        mm.begin_txn().await?;
+
+        Ok(BatchTxn { mm, own_txn: true })
+    }
+
+    pub async fn commit(self) -> Result<()> {
+        if self.own_txn {
+            self.mm.commit_txn().await?;
+        }
+
+        Ok(())
+    }
+}
+
+fn check_batch_size(size: usize) -> Result<()> {
+    if size > BATCH_SIZE_MAX {
+        Err(Error::BatchSizeOverMax {
+            max: BATCH_SIZE_MAX,
+            actual: size,
+        })
+    } else {
+        Ok(())
+    }
+}
+// endregion: --- Batch
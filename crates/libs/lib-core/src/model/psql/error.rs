@@ -1,4 +1,5 @@
 use crate::model::psql::store;
+use crate::model::psql::task::TaskStatus;
 use derive_more::From;
 use lib_auth::pwd;
 use serde::Serialize;
@@ -18,6 +19,72 @@ pub enum Error {
         max: i64,
         actual: i64,
     },
+    ListLimitNotPositive {
+        actual: i64,
+    },
+    ListCursorInvalid {
+        cursor: String,
+    },
+    // The page listing (keyset cursor) needs the `id` order (asc or desc) without offset
+    ListCursorOrderUnsupported,
+    // The entity was updated since the client read it
+    VersionConflict {
+        entity: &'static str,
+        id: i64,
+        expected: i64,
+        actual: i64,
+    },
+    BatchSizeOverMax {
+        max: usize,
+        actual: usize,
+    },
+    // The same entity more than once in a batch
+    BatchIdDuplicate {
+        id: i64,
+    },
+    // A batch item failed, so the whole batch was rolled back
+    BatchItemFailed {
+        index: usize,
+        cause: Box<Error>,
+    },
+    TaskPriorityOutOfRange {
+        max: i32,
+        actual: i32,
+    },
+    // The legacy `done` of a task update disagrees with its `status`
+    TaskDoneStatusConflict {
+        done: bool,
+        status: TaskStatus,
+    },
+    // A task can only have the labels of its project
+    LabelNotInTaskProject {
+        label_id: i64,
+        task_id: i64,
+    },
+    // Empty, or longer than the column
+    LabelNameInvalid {
+        name: String,
+    },
+    // The label names are unique in a project
+    LabelAlreadyExists {
+        project_id: i64,
+        name: String,
+    },
+    MigrationChecksumMismatch {
+        version: i64,
+    },
+    UserAlreadyExists {
+        username: String,
+    },
+    UsernameInvalid {
+        username: String,
+    },
+    UserPwdNotMatching {
+        user_id: i64,
+    },
+    RoleNotFound {
+        name: String,
+    },
     // -- Modules
     #[from]
     Pwd(pwd::Error),
@@ -28,11 +95,24 @@ pub enum Error {
     #[from]
     Sqlx(#[serde_as(as = "DisplayFromStr")] sqlx::Error),
     #[from]
+    Migrate(#[serde_as(as = "DisplayFromStr")] sqlx::migrate::MigrateError),
+    #[from]
+    SerdeJson(#[serde_as(as = "DisplayFromStr")] serde_json::Error),
+    #[from]
     SeaQuery(#[serde_as(as = "DisplayFromStr")] sea_query::error::Error),
     #[from]
     ModqlIntoSea(#[serde_as(as = "DisplayFromStr")] modql::filter::IntoSeaError),
 }
 
+impl Error {
+    pub fn batch_item_failed(index: usize, cause: Error) -> Self {
+        Error::BatchItemFailed {
+            index,
+            cause: Box::new(cause),
+        }
+    }
+}
+
 // region:    --- Error Boilerplate
 impl core::fmt::Display for Error {
     fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::result::Result<(), core::fmt::Error> {
@@ -0,0 +1,206 @@
+use crate::ctx::Ctx;
+use crate::model::psql::base::DbBmc;
+use crate::model::psql::project::{ProjectBmc, ProjectIden};
+use crate::model::psql::{base, ModelManager};
+use crate::model::psql::{Error, Result};
+use lib_utils::time::Rfc3339;
+use modql::field::{Fields, HasFields};
+use modql::filter::{FilterNodes, ListOptions, OpValsInt64, OpValsString};
+use schemars::JsonSchema;
+use sea_query::{Expr, Iden, PostgresQueryBuilder, Query, SimpleExpr};
+use sea_query_binder::SqlxBinder;
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use sqlx::FromRow;
+use time::OffsetDateTime;
+
+// region:    --- Label Types
+#[serde_as]
+#[derive(Debug, Clone, Fields, FromRow, Serialize, JsonSchema)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Label {
+    pub id: i64,
+    pub project_id: i64,
+    pub name: String,
+
+    // -- Timestamps
+    pub cid: i64,
+    #[serde_as(as = "Rfc3339")]
+    #[schemars(with = "String")]
+    pub ctime: OffsetDateTime,
+    pub mid: i64,
+    #[serde_as(as = "Rfc3339")]
+    #[schemars(with = "String")]
+    pub mtime: OffsetDateTime,
+}
+
+#[derive(Fields, Deserialize, JsonSchema)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LabelForCreate {
+    pub project_id: i64,
+    /// Unique in the project
+    pub name: String,
+}
+
+#[derive(Fields, Default, Deserialize, JsonSchema)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LabelForUpdate {
+    pub name: Option<String>,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct LabelFilter {
+    id: Option<OpValsInt64>,
+    project_id: Option<OpValsInt64>,
+    name: Option<OpValsString>,
+}
+
+#[derive(Iden)]
+enum LabelIden {
+    Id,
+    ProjectId,
+    Name,
+}
+
+/// The many-to-many table between task and label
+#[derive(Iden)]
+pub(in crate::model::psql) enum TaskLabelIden {
+    #[iden = "task_label"]
+    Table,
+    TaskId,
+    LabelId,
+}
+// endregion: --- Label Types
+
+// region:    --- LabelBmc
+/// Max length of a label name (in characters, see the `label.name` column)
+const LABEL_NAME_MAX_LEN: usize = 64;
+
+pub struct LabelBmc;
+
+impl DbBmc for LabelBmc {
+    // Table name is constant
+    const TABLE: &'static str = "label";
+
+    // A label is owned by the owner of its project
+    fn owner_cond(user_id: i64) -> Option<SimpleExpr> {
+        Some(
+            Expr::col(LabelIden::ProjectId).in_subquery(
+                Query::select()
+                    .column(ProjectIden::Id)
+                    .from(ProjectBmc::table_ref())
+                    .and_where(Expr::col(ProjectIden::OwnerId).eq(user_id))
+                    .to_owned(),
+            ),
+        )
+    }
+}
+
+impl LabelBmc {
+    pub async fn create(ctx: &Ctx, mm: &ModelManager, label_c: LabelForCreate) -> Result<i64> {
+        // Check that the project is accessible by the ctx user
+        ProjectBmc::get(ctx, mm, label_c.project_id).await?;
+        validate_name(&label_c.name)?;
+
+        let project_id = label_c.project_id;
+        let name = label_c.name.clone();
+        base::create::<Self, _>(ctx, mm, label_c)
+            .await
+            .map_err(|ex| already_exists_error(ex, project_id, name))
+    }
+
+    pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<Label> {
+        base::get::<Self, _>(ctx, mm, id).await
+    }
+
+    pub async fn list(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        filters: Option<Vec<LabelFilter>>,
+        list_options: Option<ListOptions>,
+    ) -> Result<Vec<Label>> {
+        base::list::<Self, _, _>(ctx, mm, filters, list_options).await
+    }
+
+    pub async fn update(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i64,
+        label_u: LabelForUpdate,
+    ) -> Result<()> {
+        let Some(name) = label_u.name.clone() else {
+            return base::update::<Self, _>(ctx, mm, id, label_u).await;
+        };
+        validate_name(&name)?;
+
+        let label = Self::get(ctx, mm, id).await?;
+        base::update::<Self, _>(ctx, mm, id, label_u)
+            .await
+            .map_err(|ex| already_exists_error(ex, label.project_id, name))
+    }
+
+    /// Delete the label (and remove it from its tasks, by cascade)
+    pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+        base::delete::<Self>(ctx, mm, id).await
+    }
+
+    /// List the labels of a task, ordered by name
+    /// (The task access is checked by the caller, see `TaskBmc::list_labels`)
+    pub(in crate::model::psql) async fn list_for_task(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        task_id: i64,
+    ) -> Result<Vec<Label>> {
+        let mut conn = mm.conn().await?;
+
+        // Build the SQL query
+        let mut query = Query::select();
+        query
+            .from(Self::table_ref())
+            .columns(Label::field_column_refs())
+            .and_where(
+                Expr::col(LabelIden::Id).in_subquery(
+                    Query::select()
+                        .column(TaskLabelIden::LabelId)
+                        .from(TaskLabelIden::Table)
+                        .and_where(Expr::col(TaskLabelIden::TaskId).eq(task_id))
+                        .to_owned(),
+                ),
+            )
+            .and_where_option(base::owner_cond::<Self>(ctx))
+            .order_by(LabelIden::Name, sea_query::Order::Asc);
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+
+        // Execute the query
+        let labels = sqlx::query_as_with::<_, Label, _>(&sql, values)
+            .fetch_all(&mut *conn)
+            .await?;
+
+        Ok(labels)
+    }
+}
+// endregion: --- LabelBmc
+
+// region:    --- Support
+/// Check that the label name is not empty and fits the column
+fn validate_name(name: &str) -> Result<()> {
+    let len = name.trim().chars().count();
+    if len == 0 || name.chars().count() > LABEL_NAME_MAX_LEN {
+        return Err(Error::LabelNameInvalid {
+            name: name.to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Map the unique violation (`uq_label_project_name`) to `LabelAlreadyExists`
+fn already_exists_error(ex: Error, project_id: i64, name: String) -> Error {
+    match ex {
+        Error::Sqlx(sqlx::Error::Database(db_err)) if db_err.is_unique_violation() => {
+            Error::LabelAlreadyExists { project_id, name }
+        }
+        ex => ex,
+    }
+}
+// endregion: --- Support
@@ -0,0 +1,126 @@
+//! Versioned schema migrations for the PostgreSQL database.
+//!
+//! Migrations are the `sql/psql/migrations/<version>_<name>.{up,down}.sql` files.
+//! They are embedded in the binary at compile time, applied in version order,
+//! and recorded (with their checksum) in the `_sqlx_migrations` table.
+
+use crate::model::psql::{Error, ModelManager, Result};
+use serde::Serialize;
+use sqlx::migrate::{Migrate, Migrator};
+use std::collections::HashMap;
+use tracing::info;
+
+/// Embedded migrations (path relative to the lib-core Cargo.toml)
+static MIGRATOR: Migrator = sqlx::migrate!("../../../sql/psql/migrations");
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    pub applied: bool,
+}
+
+/// Apply all the pending migrations.
+/// Fails if an already applied migration was modified or is unknown to this binary.
+pub async fn migrate_up(mm: &ModelManager) -> Result<()> {
+    info!("{:<12} - migrate_up", "MIGRATION");
+
+    MIGRATOR.run(mm.db()).await?;
+
+    Ok(())
+}
+
+/// Revert the applied migrations down to the `target` version (excluded).
+/// (e.g., `target = 0` reverts all the migrations)
+pub async fn migrate_down(mm: &ModelManager, target: i64) -> Result<()> {
+    info!("{:<12} - migrate_down - target: {target}", "MIGRATION");
+
+    MIGRATOR.undo(mm.db(), target).await?;
+
+    Ok(())
+}
+
+/// Return the status of each known migration.
+/// Fails if an applied migration does not match the embedded one.
+pub async fn status(mm: &ModelManager) -> Result<Vec<MigrationStatus>> {
+    let mut conn = mm.db().acquire().await?;
+    conn.ensure_migrations_table().await?;
+    let applied: HashMap<i64, _> = conn
+        .list_applied_migrations()
+        .await?
+        .into_iter()
+        .map(|m| (m.version, m.checksum))
+        .collect();
+
+    let mut statuses = Vec::new();
+    for migration in MIGRATOR
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration())
+    {
+        let applied = match applied.get(&migration.version) {
+            Some(checksum) if *checksum != migration.checksum => {
+                return Err(Error::MigrationChecksumMismatch {
+                    version: migration.version,
+                });
+            }
+            Some(_) => true,
+            None => false,
+        };
+
+        statuses.push(MigrationStatus {
+            version: migration.version,
+            description: migration.description.to_string(),
+            applied,
+        });
+    }
+
+    Ok(statuses)
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::_dev_utils;
+    use anyhow::Result;
+    use serial_test::serial;
+
+    #[serial]
+    #[tokio::test]
+    async fn test_status_all_applied_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+
+        // -- Exec
+        let statuses = status(&mm).await?;
+
+        // -- Check
+        assert!(!statuses.is_empty(), "should have migrations");
+        assert!(
+            statuses.iter().all(|s| s.applied),
+            "all migrations should be applied: {statuses:?}"
+        );
+        assert!(
+            statuses.windows(2).all(|w| w[0].version < w[1].version),
+            "migrations should be ordered by version"
+        );
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_migrate_up_idempotent_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+
+        // -- Exec
+        migrate_up(&mm).await?;
+
+        // -- Check
+        assert!(status(&mm).await?.iter().all(|s| s.applied));
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
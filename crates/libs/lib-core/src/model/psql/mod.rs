@@ -2,31 +2,104 @@
 
 mod base;
 mod error;
+pub mod label;
+pub mod migration;
+mod modql_utils;
+pub mod project;
+pub mod role;
 mod store;
 pub mod task;
+pub mod task_activity;
+pub mod task_comment;
 
 pub mod user;
+pub mod user_session;
 
+pub use self::base::{Page, PageOptions};
 pub use self::error::{Error, Result};
 // Database Field
-use crate::model::psql::store::{new_db_pool, Db};
+use crate::model::psql::store::{new_db_pool, Db, DbConn, TxnHolder};
 use std::sync::Arc;
 
 #[derive(Clone)]
 pub struct ModelManager {
     db: Arc<Db>,
+    // Only for a transactional ModelManager (see `new_with_txn`)
+    txn_holder: Option<TxnHolder>,
 }
 
 impl ModelManager {
     /// Create Constructor
     pub async fn new() -> Result<Self> {
         let db = Arc::new(new_db_pool().await?);
-        Ok(ModelManager { db })
+        Ok(ModelManager {
+            db,
+            txn_holder: None,
+        })
+    }
+
+    /// A ModelManager on the same pool, able to run a transaction.
+    /// Between `begin_txn` and `commit_txn` (or `rollback_txn`), all the bmc calls with it
+    /// (or its clones) run in the transaction. Dropping it without commit rolls back.
+    pub fn new_with_txn(&self) -> ModelManager {
+        ModelManager {
+            db: self.db.clone(),
+            txn_holder: Some(TxnHolder::default()),
+        }
+    }
+
+    pub async fn begin_txn(&self) -> Result<()> {
+        let txn_holder = self
+            .txn_holder
+            .as_ref()
+            .ok_or(store::Error::TxnNotSupported)?;
+        txn_holder.begin(&self.db).await?;
+
+        Ok(())
+    }
+
+    pub async fn commit_txn(&self) -> Result<()> {
+        let txn_holder = self.txn_holder.as_ref().ok_or(store::Error::TxnNotBegun)?;
+        txn_holder.commit().await?;
+
+        Ok(())
+    }
+
+    pub async fn rollback_txn(&self) -> Result<()> {
+        let txn_holder = self.txn_holder.as_ref().ok_or(store::Error::TxnNotBegun)?;
+        txn_holder.rollback().await?;
+
+        Ok(())
+    }
+
+    /// Whether the bmc calls run in an open transaction
+    pub async fn in_txn(&self) -> bool {
+        match &self.txn_holder {
+            Some(txn_holder) => txn_holder.is_begun().await,
+            None => false,
+        }
     }
 
     /// Return the Db pool reference
-    /// (Only for the internal use of the model module)
+    /// (Only for the internal use of the model module, bypasses the transaction)
     pub(in crate::model) fn db(&self) -> &Db {
         &self.db
     }
+
+    /// Return the connection of a model call: the open transaction if any, a pool connection otherwise
+    /// (Only for the internal use of the model module, hold it only for the queries of one call)
+    ///
+    /// Never call another bmc function with the same `mm` while holding it: in a transaction,
+    /// the second call waits for this connection to be dropped, which never happens (deadlock).
+    /// Drop it first, or pass the connection to the helper (e.g., `base::get_version`).
+    pub(in crate::model) async fn conn(&self) -> Result<DbConn<'_>> {
+        if let Some(txn_holder) = &self.txn_holder {
+            if let Some(conn) = txn_holder.conn().await {
+                return Ok(conn);
+            }
+        }
+
+        let conn = self.db.acquire().await.map_err(store::Error::from)?;
+        Ok(DbConn::Pool(Box::new(conn)))
+    }
 }
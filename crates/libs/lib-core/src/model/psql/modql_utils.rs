@@ -0,0 +1,15 @@
+//! Helpers to convert the modql filter values into sea-query values
+
+use lib_utils::time::parse_utc;
+use modql::filter::{IntoSeaError, SeaResult};
+
+/// Convert a Rfc3339 json string into a time sea-query value (e.g., for `ctime` filters)
+pub fn time_to_sea_value(json_value: serde_json::Value) -> SeaResult<sea_query::Value> {
+    let moment = json_value
+        .as_str()
+        .ok_or_else(|| IntoSeaError::custom("Time value should be a Rfc3339 string"))?;
+
+    parse_utc(moment)
+        .map(sea_query::Value::from)
+        .map_err(|ex| IntoSeaError::custom(ex.to_string()))
+}
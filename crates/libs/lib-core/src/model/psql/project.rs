@@ -0,0 +1,224 @@
+use crate::ctx::Ctx;
+use crate::model::psql::base::DbBmc;
+use crate::model::psql::Result;
+use crate::model::psql::{base, ModelManager};
+use lib_utils::time::Rfc3339;
+use modql::field::Fields;
+use modql::filter::{FilterNodes, ListOptions, OpValsInt64, OpValsString};
+use schemars::JsonSchema;
+use sea_query::{Expr, Iden, SimpleExpr};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use sqlx::FromRow;
+use time::OffsetDateTime;
+
+// region:    --- Project Types
+#[serde_as]
+#[derive(Debug, Clone, Fields, FromRow, Serialize, JsonSchema)]
+pub struct Project {
+    pub id: i64,
+    pub owner_id: i64,
+    pub name: String,
+
+    // -- Timestamps
+    pub cid: i64,
+    #[serde_as(as = "Rfc3339")]
+    #[schemars(with = "String")]
+    pub ctime: OffsetDateTime,
+    pub mid: i64,
+    #[serde_as(as = "Rfc3339")]
+    #[schemars(with = "String")]
+    pub mtime: OffsetDateTime,
+}
+
+#[derive(Fields, Deserialize, JsonSchema)]
+pub struct ProjectForCreate {
+    pub name: String,
+}
+
+/// Project data inserted in the db
+/// (owner_id is taken from the ctx, not from the client)
+#[derive(Fields)]
+struct ProjectForInsert {
+    name: String,
+    owner_id: i64,
+}
+
+#[derive(Fields, Default, Deserialize, JsonSchema)]
+pub struct ProjectForUpdate {
+    pub name: Option<String>,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct ProjectFilter {
+    id: Option<OpValsInt64>,
+    name: Option<OpValsString>,
+}
+
+#[derive(Iden)]
+pub(in crate::model::psql) enum ProjectIden {
+    Id,
+    OwnerId,
+}
+// endregion: --- Project Types
+
+// region:    --- ProjectBmc
+pub struct ProjectBmc;
+
+impl DbBmc for ProjectBmc {
+    // Table name is constant
+    const TABLE: &'static str = "project";
+
+    // A project is owned by the user who created it
+    fn owner_cond(user_id: i64) -> Option<SimpleExpr> {
+        Some(Expr::col(ProjectIden::OwnerId).eq(user_id))
+    }
+}
+
+impl ProjectBmc {
+    pub async fn create(ctx: &Ctx, mm: &ModelManager, project_c: ProjectForCreate) -> Result<i64> {
+        let project_i = ProjectForInsert {
+            name: project_c.name,
+            owner_id: ctx.user_id(),
+        };
+
+        base::create::<Self, _>(ctx, mm, project_i).await
+    }
+
+    pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<Project> {
+        base::get::<Self, _>(ctx, mm, id).await
+    }
+
+    pub async fn list(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        filters: Option<Vec<ProjectFilter>>,
+        list_options: Option<ListOptions>,
+    ) -> Result<Vec<Project>> {
+        base::list::<Self, _, _>(ctx, mm, filters, list_options).await
+    }
+
+    pub async fn update(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i64,
+        project_u: ProjectForUpdate,
+    ) -> Result<()> {
+        base::update::<Self, _>(ctx, mm, id, project_u).await
+    }
+
+    /// Delete the project (and its tasks, by cascade)
+    pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+        base::delete::<Self>(ctx, mm, id).await
+    }
+}
+// endregion: --- ProjectBmc
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::_dev_utils;
+    use crate::model::psql::Error;
+    use anyhow::Result;
+    use serial_test::serial;
+
+    #[serial]
+    #[tokio::test]
+    async fn test_create_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::new(1000)?;
+        let fx_name = "test_create_ok project";
+
+        // -- Exec
+        let id = ProjectBmc::create(
+            &ctx,
+            &mm,
+            ProjectForCreate {
+                name: fx_name.to_string(),
+            },
+        )
+        .await?;
+
+        // -- Check
+        let project = ProjectBmc::get(&ctx, &mm, id).await?;
+        assert_eq!(project.name, fx_name);
+        assert_eq!(project.owner_id, ctx.user_id());
+
+        // -- Clean
+        ProjectBmc::delete(&ctx, &mm, id).await?;
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_get_err_not_owner() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx_owner = Ctx::new(1000)?;
+        let ctx_other = Ctx::new(1001)?;
+        let fx_id =
+            _dev_utils::seed_project(&ctx_owner, &mm, "test_get_err_not_owner project").await?;
+
+        // -- Exec
+        let res_get = ProjectBmc::get(&ctx_other, &mm, fx_id).await;
+        let res_delete = ProjectBmc::delete(&ctx_other, &mm, fx_id).await;
+
+        // -- Check
+        assert!(
+            matches!(
+                res_get,
+                Err(Error::EntityNotFound {
+                    entity: "project",
+                    ..
+                })
+            ),
+            "EntityNotFound not matching for get"
+        );
+        assert!(
+            matches!(
+                res_delete,
+                Err(Error::EntityNotFound {
+                    entity: "project",
+                    ..
+                })
+            ),
+            "EntityNotFound not matching for delete"
+        );
+
+        // -- Clean
+        ProjectBmc::delete(&ctx_owner, &mm, fx_id).await?;
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_list_only_owned_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx_owner = Ctx::new(1000)?;
+        let ctx_other = Ctx::new(1001)?;
+        let fx_id_owner =
+            _dev_utils::seed_project(&ctx_owner, &mm, "test_list_only_owned_ok project 01").await?;
+        let fx_id_other =
+            _dev_utils::seed_project(&ctx_other, &mm, "test_list_only_owned_ok project 02").await?;
+
+        // -- Exec
+        let projects = ProjectBmc::list(&ctx_owner, &mm, None, None).await?;
+
+        // -- Check
+        assert!(projects.iter().all(|p| p.owner_id == ctx_owner.user_id()));
+        assert!(projects.iter().any(|p| p.id == fx_id_owner));
+        assert!(!projects.iter().any(|p| p.id == fx_id_other));
+
+        // -- Clean
+        ProjectBmc::delete(&ctx_owner, &mm, fx_id_owner).await?;
+        ProjectBmc::delete(&ctx_other, &mm, fx_id_other).await?;
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
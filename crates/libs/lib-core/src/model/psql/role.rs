@@ -0,0 +1,298 @@
+//! Roles and their permissions (RBAC).
+//! A user has roles, a role grants permissions (`<resource>:<action>`).
+//! The built-in roles (viewer, member, admin) are created by the migrations.
+
+use crate::ctx::Ctx;
+use crate::model::psql::base::DbBmc;
+use crate::model::psql::{Error, ModelManager, Result};
+use modql::field::{Fields, HasFields};
+use schemars::JsonSchema;
+use sea_query::{Expr, Iden, OnConflict, Order, PostgresQueryBuilder, Query};
+use sea_query_binder::SqlxBinder;
+use serde::Serialize;
+use sqlx::FromRow;
+
+/// Permission names
+pub mod perm {
+    pub const PROJECT_READ: &str = "project:read";
+    pub const PROJECT_WRITE: &str = "project:write";
+    pub const TASK_READ: &str = "task:read";
+    pub const TASK_WRITE: &str = "task:write";
+    pub const ROLE_MANAGE: &str = "role:manage";
+}
+
+/// Role given to the new users
+pub const DEFAULT_ROLE: &str = "member";
+
+// region:    --- Role Types
+#[derive(Debug, Clone, Fields, FromRow, Serialize, JsonSchema)]
+pub struct Role {
+    pub id: i64,
+    pub name: String,
+}
+
+#[derive(Iden)]
+enum RoleIden {
+    #[iden = "role"]
+    Table,
+    Id,
+    Name,
+}
+
+#[derive(Iden)]
+enum RolePermissionIden {
+    #[iden = "role_permission"]
+    Table,
+    RoleId,
+    Permission,
+}
+
+#[derive(Iden)]
+enum UserRoleIden {
+    #[iden = "user_role"]
+    Table,
+    UserId,
+    RoleId,
+}
+// endregion: --- Role Types
+
+// region:    --- RoleBmc
+pub struct RoleBmc;
+
+impl DbBmc for RoleBmc {
+    const TABLE: &'static str = "role";
+}
+
+impl RoleBmc {
+    /// List all the roles
+    pub async fn list(_ctx: &Ctx, mm: &ModelManager) -> Result<Vec<Role>> {
+        // Build the SQL query
+        let mut query = Query::select();
+        query
+            .from(Self::table_ref())
+            .columns(Role::field_column_refs())
+            .order_by(RoleIden::Id, Order::Asc);
+
+        // Execute the query
+        let mut conn = mm.conn().await?;
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+        let roles = sqlx::query_as_with::<_, Role, _>(&sql, values)
+            .fetch_all(&mut *conn)
+            .await?;
+
+        Ok(roles)
+    }
+
+    /// Get a role by its name
+    pub async fn get_by_name(_ctx: &Ctx, mm: &ModelManager, name: &str) -> Result<Role> {
+        // Build the SQL query
+        let mut query = Query::select();
+        query
+            .from(Self::table_ref())
+            .columns(Role::field_column_refs())
+            .and_where(Expr::col(RoleIden::Name).eq(name));
+
+        // Execute the query
+        let mut conn = mm.conn().await?;
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+        let role = sqlx::query_as_with::<_, Role, _>(&sql, values)
+            .fetch_optional(&mut *conn)
+            .await?
+            .ok_or(Error::RoleNotFound {
+                name: name.to_string(),
+            })?;
+
+        Ok(role)
+    }
+
+    /// List the roles of the user
+    pub async fn list_for_user(_ctx: &Ctx, mm: &ModelManager, user_id: i64) -> Result<Vec<Role>> {
+        // Build the SQL query
+        let mut query = Query::select();
+        query
+            .from(Self::table_ref())
+            .columns(Role::field_column_refs())
+            .inner_join(
+                UserRoleIden::Table,
+                Expr::col((UserRoleIden::Table, UserRoleIden::RoleId))
+                    .equals((RoleIden::Table, RoleIden::Id)),
+            )
+            .and_where(Expr::col((UserRoleIden::Table, UserRoleIden::UserId)).eq(user_id))
+            .order_by((RoleIden::Table, RoleIden::Id), Order::Asc);
+
+        // Execute the query
+        let mut conn = mm.conn().await?;
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+        let roles = sqlx::query_as_with::<_, Role, _>(&sql, values)
+            .fetch_all(&mut *conn)
+            .await?;
+
+        Ok(roles)
+    }
+
+    /// List the permissions granted to the user by all its roles
+    pub async fn list_permissions_for_user(
+        _ctx: &Ctx,
+        mm: &ModelManager,
+        user_id: i64,
+    ) -> Result<Vec<String>> {
+        // Build the SQL query
+        let mut query = Query::select();
+        query
+            .distinct()
+            .column((RolePermissionIden::Table, RolePermissionIden::Permission))
+            .from(RolePermissionIden::Table)
+            .inner_join(
+                UserRoleIden::Table,
+                Expr::col((UserRoleIden::Table, UserRoleIden::RoleId))
+                    .equals((RolePermissionIden::Table, RolePermissionIden::RoleId)),
+            )
+            .and_where(Expr::col((UserRoleIden::Table, UserRoleIden::UserId)).eq(user_id))
+            .order_by(
+                (RolePermissionIden::Table, RolePermissionIden::Permission),
+                Order::Asc,
+            );
+
+        // Execute the query
+        let mut conn = mm.conn().await?;
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+        let permissions = sqlx::query_scalar_with::<_, String, _>(&sql, values)
+            .fetch_all(&mut *conn)
+            .await?;
+
+        Ok(permissions)
+    }
+
+    /// Give the role to the user (no-op if the user already has it)
+    pub async fn assign(ctx: &Ctx, mm: &ModelManager, user_id: i64, role_name: &str) -> Result<()> {
+        let role = Self::get_by_name(ctx, mm, role_name).await?;
+
+        // Build the SQL query
+        let mut query = Query::insert();
+        query
+            .into_table(UserRoleIden::Table)
+            .columns([UserRoleIden::UserId, UserRoleIden::RoleId])
+            .values([user_id.into(), role.id.into()])?
+            .on_conflict(
+                OnConflict::columns([UserRoleIden::UserId, UserRoleIden::RoleId])
+                    .do_nothing()
+                    .to_owned(),
+            );
+
+        // Execute the query
+        let mut conn = mm.conn().await?;
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+        sqlx::query_with(&sql, values).execute(&mut *conn).await?;
+
+        Ok(())
+    }
+
+    /// Remove the role from the user (no-op if the user does not have it)
+    pub async fn unassign(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        user_id: i64,
+        role_name: &str,
+    ) -> Result<()> {
+        let role = Self::get_by_name(ctx, mm, role_name).await?;
+
+        // Build the SQL query
+        let mut query = Query::delete();
+        query
+            .from_table(UserRoleIden::Table)
+            .and_where(Expr::col(UserRoleIden::UserId).eq(user_id))
+            .and_where(Expr::col(UserRoleIden::RoleId).eq(role.id));
+
+        // Execute the query
+        let mut conn = mm.conn().await?;
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+        sqlx::query_with(&sql, values).execute(&mut *conn).await?;
+
+        Ok(())
+    }
+}
+// endregion: --- RoleBmc
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::_dev_utils;
+    use crate::model::psql::user::{User, UserBmc, UserForCreate};
+    use anyhow::{Context, Result};
+    use serial_test::serial;
+
+    #[serial]
+    #[tokio::test]
+    async fn test_list_permissions_for_user_ok_demo1() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+        let user: User = UserBmc::first_by_username(&ctx, &mm, "demo1")
+            .await?
+            .context("Should have user demo1")?;
+
+        // -- Exec
+        let permissions = RoleBmc::list_permissions_for_user(&ctx, &mm, user.id).await?;
+
+        // -- Check
+        // demo1 is an admin (dev seed)
+        assert!(permissions.iter().any(|p| p == perm::ROLE_MANAGE));
+        assert!(permissions.iter().any(|p| p == perm::TASK_WRITE));
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_assign_unassign_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+        let fx_user_id = UserBmc::create(
+            &ctx,
+            &mm,
+            UserForCreate {
+                username: "test_assign_unassign_ok-user".to_string(),
+                password_clear: "welcome2rust".to_string(),
+            },
+        )
+        .await?;
+
+        // -- Exec
+        let roles_created = RoleBmc::list_for_user(&ctx, &mm, fx_user_id).await?;
+        RoleBmc::assign(&ctx, &mm, fx_user_id, "viewer").await?;
+        RoleBmc::unassign(&ctx, &mm, fx_user_id, DEFAULT_ROLE).await?;
+
+        // -- Check
+        let role_names: Vec<String> = roles_created.into_iter().map(|r| r.name).collect();
+        assert_eq!(role_names, [DEFAULT_ROLE]);
+        let permissions = RoleBmc::list_permissions_for_user(&ctx, &mm, fx_user_id).await?;
+        assert_eq!(permissions, [perm::PROJECT_READ, perm::TASK_READ]);
+
+        // -- Clean
+        UserBmc::delete(&ctx, &mm, fx_user_id).await?;
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_assign_err_role_not_found() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+
+        // -- Exec
+        let res = RoleBmc::assign(&ctx, &mm, 1000, "no-such-role").await;
+
+        // -- Check
+        assert!(
+            matches!(res, Err(Error::RoleNotFound { .. })),
+            "RoleNotFound not matching"
+        );
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
@@ -0,0 +1,82 @@
+//! Database connection of the model calls: a pool connection,
+//! or the open transaction of a transactional ModelManager.
+
+use crate::model::psql::store::{Db, Error, Result};
+use sqlx::pool::PoolConnection;
+use sqlx::{PgConnection, Postgres, Transaction};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use tokio::sync::{MappedMutexGuard, Mutex, MutexGuard};
+
+type Txn = Transaction<'static, Postgres>;
+
+/// The transaction of a transactional ModelManager (shared by its clones)
+#[derive(Clone, Default)]
+pub struct TxnHolder {
+    txn: Arc<Mutex<Option<Txn>>>,
+}
+
+impl TxnHolder {
+    pub async fn begin(&self, db: &Db) -> Result<()> {
+        let mut txn = self.txn.lock().await;
+        if txn.is_some() {
+            return Err(Error::TxnAlreadyBegun);
+        }
+        *txn = Some(db.begin().await?);
+
+        Ok(())
+    }
+
+    pub async fn commit(&self) -> Result<()> {
+        let txn = self.txn.lock().await.take().ok_or(Error::TxnNotBegun)?;
+        txn.commit().await?;
+
+        Ok(())
+    }
+
+    pub async fn rollback(&self) -> Result<()> {
+        let txn = self.txn.lock().await.take().ok_or(Error::TxnNotBegun)?;
+        txn.rollback().await?;
+
+        Ok(())
+    }
+
+    pub async fn is_begun(&self) -> bool {
+        self.txn.lock().await.is_some()
+    }
+
+    /// The connection of the open transaction (None if no transaction is open)
+    pub async fn conn(&self) -> Option<DbConn<'_>> {
+        MutexGuard::try_map(self.txn.lock().await, Option::as_mut)
+            .ok()
+            .map(DbConn::Txn)
+    }
+}
+
+/// A connection for one model call
+/// (The transaction is locked while the connection is held,
+///  see `ModelManager::conn` for the deadlock to avoid)
+pub enum DbConn<'a> {
+    Pool(Box<PoolConnection<Postgres>>),
+    Txn(MappedMutexGuard<'a, Txn>),
+}
+
+impl Deref for DbConn<'_> {
+    type Target = PgConnection;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            DbConn::Pool(conn) => conn,
+            DbConn::Txn(txn) => txn,
+        }
+    }
+}
+
+impl DerefMut for DbConn<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            DbConn::Pool(conn) => conn,
+            DbConn::Txn(txn) => txn,
+        }
+    }
+}
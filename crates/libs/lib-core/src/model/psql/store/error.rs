@@ -1,10 +1,23 @@
+use derive_more::From;
 use serde::Serialize;
+use serde_with::{serde_as, DisplayFromStr};
 
 pub type Result<T> = core::result::Result<T, Error>;
 
-#[derive(Debug, Serialize, Clone)]
+#[serde_as]
+#[derive(Debug, Serialize, From)]
 pub enum Error {
     FailToCreatePool(String),
+
+    // -- Transaction
+    // `begin_txn` on a ModelManager not created with `new_with_txn`
+    TxnNotSupported,
+    TxnAlreadyBegun,
+    TxnNotBegun,
+
+    // -- Externals
+    #[from]
+    Sqlx(#[serde_as(as = "DisplayFromStr")] sqlx::Error),
 }
 
 // region:    --- Error Boilerplate
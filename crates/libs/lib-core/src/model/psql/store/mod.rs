@@ -1,5 +1,7 @@
+mod dbx;
 mod error;
 
+pub use self::dbx::{DbConn, TxnHolder};
 pub use self::error::{Error, Result};
 use std::time::Duration;
 
@@ -1,35 +1,370 @@
+use crate::config::core_config;
 use crate::ctx::Ctx;
-use crate::model::psql::base::DbBmc;
-use crate::model::psql::Result;
+use crate::model::psql::base::{BatchTxn, DbBmc, HasId};
+use crate::model::psql::label::{Label, LabelBmc, TaskLabelIden};
+use crate::model::psql::modql_utils::time_to_sea_value;
+use crate::model::psql::project::{ProjectBmc, ProjectIden};
+use crate::model::psql::task_activity::{
+    deleted_at_changes, task_changes, TaskActivityBmc, TaskActivityKind,
+};
+use crate::model::psql::user::{User, UserBmc};
 use crate::model::psql::{base, ModelManager};
-use modql::field::Fields;
-use modql::filter::{FilterNodes, ListOptions, OpValsBool, OpValsInt64, OpValsString};
+use crate::model::psql::{Error, Page, PageOptions, Result};
+use lib_utils::time::{now_utc, Rfc3339};
+use modql::field::{Field, Fields, HasFields};
+use modql::filter::{
+    FilterNodes, IntoSeaError, ListOptions, OpValValue, OpValsBool, OpValsInt32, OpValsInt64,
+    OpValsString, OpValsValue, SeaResult,
+};
+use modql::SIden;
+use schemars::JsonSchema;
+use sea_query::{
+    ColumnRef, ConditionExpression, DynIden, Expr, Iden, IntoIden, Keyword, OnConflict, Order,
+    PostgresQueryBuilder, Query, SimpleExpr,
+};
+use sea_query_binder::SqlxBinder;
 use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
 use sqlx::FromRow;
+use std::collections::BTreeSet;
+use time::{Duration, OffsetDateTime};
 
 // region:    --- Task Types
-#[derive(Debug, Clone, Fields, FromRow, Serialize)]
+#[serde_as]
+#[derive(Debug, Clone, Fields, FromRow, Serialize, JsonSchema)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct Task {
     pub id: i64,
+    pub project_id: i64,
     pub title: String,
+    pub description: Option<String>,
+    #[serde_as(as = "Option<Rfc3339>", no_default)]
+    #[schemars(with = "Option<String>")]
+    pub due_at: Option<OffsetDateTime>,
+    /// 0 none, 1 low, 2 medium, 3 high, 4 urgent
+    pub priority: i32,
+    pub status: TaskStatus,
+    /// Derived from the status (read only)
+    pub done: bool,
+    pub assignee_id: Option<i64>,
+    /// Incremented on each update, delete and restore (see `TaskForUpdate::version`)
+    pub version: i64,
+    /// Set when the task is in the trash (see `TaskBmc::restore`)
+    // (no_default: serialize only, and the serde default conflicts with the JsonSchema derive)
+    #[serde_as(as = "Option<Rfc3339>", no_default)]
+    #[schemars(with = "Option<String>")]
+    pub deleted_at: Option<OffsetDateTime>,
+
+    // -- Timestamps
+    pub cid: i64,
+    #[serde_as(as = "Rfc3339")]
+    #[schemars(with = "String")]
+    pub ctime: OffsetDateTime,
+    pub mid: i64,
+    #[serde_as(as = "Rfc3339")]
+    #[schemars(with = "String")]
+    pub mtime: OffsetDateTime,
 }
 
-#[derive(Fields, Deserialize)]
+#[serde_as]
+#[derive(Fields, Default, Deserialize, JsonSchema)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct TaskForCreate {
+    pub project_id: i64,
     pub title: String,
+    pub description: Option<String>,
+    #[serde_as(deserialize_as = "Option<Rfc3339>")]
+    #[schemars(with = "Option<String>")]
+    pub due_at: Option<OffsetDateTime>,
+    /// 0 none (default), 1 low, 2 medium, 3 high, 4 urgent
+    pub priority: Option<i32>,
+    /// `todo` by default
+    pub status: Option<TaskStatus>,
+    /// A user id
+    pub assignee_id: Option<i64>,
 }
 
-#[derive(Fields, Default, Deserialize)]
+/// The nullable fields (`description`, `due_at` and `assignee_id`) are cleared
+/// with an explicit `null`, and left unchanged when absent
+/// (`HasFields` is implemented below, as the modql derive has no `Option<Option<T>>` support)
+#[derive(Default, Deserialize, JsonSchema)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct TaskForUpdate {
     pub title: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "::serde_with::rust::double_option::deserialize"
+    )]
+    #[schemars(with = "Option<String>")]
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<String>))]
+    pub description: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_due_at_for_update")]
+    #[schemars(with = "Option<String>")]
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<String>))]
+    pub due_at: Option<Option<OffsetDateTime>>,
+    pub priority: Option<i32>,
+    pub status: Option<TaskStatus>,
+    /// Legacy: `true` sets the status to `done`, `false` to `todo` (prefer `status`)
     pub done: Option<bool>,
+    #[serde(
+        default,
+        deserialize_with = "::serde_with::rust::double_option::deserialize"
+    )]
+    #[schemars(with = "Option<i64>")]
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<i64>))]
+    pub assignee_id: Option<Option<i64>>,
+    /// The version of the task read by the client
+    /// (if set, the update fails with a version conflict when the task was updated since)
+    pub version: Option<i64>,
+}
+
+const TASK_FOR_UPDATE_FIELDS: &[&str] = &[
+    "title",
+    "description",
+    "due_at",
+    "priority",
+    "status",
+    "assignee_id",
+];
+
+impl TaskForUpdate {
+    /// The values of the fields, `None` for the absent ones (`done` and `version` are not columns)
+    fn field_values(self) -> [(&'static str, Option<sea_query::Value>); 6] {
+        [
+            ("title", self.title.map(Into::into)),
+            ("description", self.description.map(Into::into)),
+            ("due_at", self.due_at.map(Into::into)),
+            ("priority", self.priority.map(Into::into)),
+            ("status", self.status.map(Into::into)),
+            ("assignee_id", self.assignee_id.map(Into::into)),
+        ]
+    }
+}
+
+impl HasFields for TaskForUpdate {
+    /// The present fields, with `NULL` for the cleared ones
+    fn not_none_fields(self) -> Fields {
+        let fields = self
+            .field_values()
+            .into_iter()
+            .filter_map(|(name, value)| Some(Field::new(SIden(name), value?.into())))
+            .collect();
+
+        Fields::new(fields)
+    }
+
+    fn all_fields(self) -> Fields {
+        let fields = self
+            .field_values()
+            .into_iter()
+            .map(|(name, value)| {
+                let value = value.map_or(SimpleExpr::Keyword(Keyword::Null), SimpleExpr::from);
+                Field::new(SIden(name), value)
+            })
+            .collect();
+
+        Fields::new(fields)
+    }
+
+    fn field_names() -> &'static [&'static str] {
+        TASK_FOR_UPDATE_FIELDS
+    }
+
+    fn field_idens() -> Vec<DynIden> {
+        TASK_FOR_UPDATE_FIELDS
+            .iter()
+            .map(|name| SIden(name).into_iden())
+            .collect()
+    }
+
+    fn field_column_refs() -> Vec<ColumnRef> {
+        TASK_FOR_UPDATE_FIELDS
+            .iter()
+            .map(|name| ColumnRef::Column(SIden(name).into_iden()))
+            .collect()
+    }
+
+    fn field_column_refs_with_rel(rel: impl IntoIden) -> Vec<ColumnRef> {
+        let rel = rel.into_iden();
+        TASK_FOR_UPDATE_FIELDS
+            .iter()
+            .map(|name| ColumnRef::TableColumn(rel.clone(), SIden(name).into_iden()))
+            .collect()
+    }
+}
+
+/// `due_at` of `TaskForUpdate`: `Some(None)` for `null`, `None` when absent (serde default)
+fn deserialize_due_at_for_update<'de, D>(
+    deserializer: D,
+) -> core::result::Result<Option<Option<OffsetDateTime>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    serde_with::As::<Option<Rfc3339>>::deserialize(deserializer).map(Some)
+}
+
+impl TaskForUpdate {
+    /// Map the legacy `done` onto the status
+    /// (`TaskDoneStatusConflict` if both are set and disagree)
+    fn resolve_done(&mut self) -> Result<()> {
+        let Some(done) = self.done.take() else {
+            return Ok(());
+        };
+
+        match self.status {
+            None => {
+                let status = if done {
+                    TaskStatus::Done
+                } else {
+                    TaskStatus::Todo
+                };
+                self.status = Some(status);
+                Ok(())
+            }
+            Some(status) if (status == TaskStatus::Done) == done => Ok(()),
+            Some(status) => Err(Error::TaskDoneStatusConflict { done, status }),
+        }
+    }
 }
 
 #[derive(FilterNodes, Deserialize, Default, Debug)]
 pub struct TaskFilter {
     id: Option<OpValsInt64>,
+    project_id: Option<OpValsInt64>,
     title: Option<OpValsString>,
+    description: Option<OpValsString>,
+    #[modql(to_sea_value_fn = "time_to_sea_value")]
+    due_at: Option<OpValsValue>,
+    priority: Option<OpValsInt32>,
+    status: Option<OpValsString>,
     done: Option<OpValsBool>,
+    assignee_id: Option<OpValsInt64>,
+    /// The tasks with (or without) the labels, e.g., `{"label_id": {"$in": [1000, 1001]}}`
+    #[modql(to_sea_condition_fn = "label_id_to_sea_condition")]
+    label_id: Option<OpValsValue>,
+
+    cid: Option<OpValsInt64>,
+    #[modql(to_sea_value_fn = "time_to_sea_value")]
+    ctime: Option<OpValsValue>,
+    mid: Option<OpValsInt64>,
+    #[modql(to_sea_value_fn = "time_to_sea_value")]
+    mtime: Option<OpValsValue>,
+}
+
+impl HasId for Task {
+    fn id(&self) -> i64 {
+        self.id
+    }
+}
+
+/// A task matching a search, with its relevance and the matches highlighted (`<mark>..</mark>`)
+/// (The highlights are HTML: the task text is escaped)
+#[derive(Debug, Clone, FromRow, Serialize, JsonSchema)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TaskSearchHit {
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub task: Task,
+    /// The higher, the more relevant (title matches weigh more than description ones)
+    pub rank: f32,
+    pub title_highlight: String,
+    pub description_highlight: Option<String>,
+}
+
+/// The workflow status of a task (stored as its snake_case name)
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, JsonSchema,
+)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum TaskStatus {
+    #[default]
+    Todo,
+    InProgress,
+    Done,
+}
+
+impl TaskStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskStatus::Todo => "todo",
+            TaskStatus::InProgress => "in_progress",
+            TaskStatus::Done => "done",
+        }
+    }
+}
+
+impl From<TaskStatus> for sea_query::Value {
+    fn from(status: TaskStatus) -> Self {
+        status.as_str().into()
+    }
+}
+
+impl sea_query::Nullable for TaskStatus {
+    fn null() -> sea_query::Value {
+        sea_query::Value::String(None)
+    }
+}
+
+#[derive(Iden)]
+enum TaskIden {
+    Id,
+    ProjectId,
+    // -- Search result columns
+    Rank,
+    TitleHighlight,
+    DescriptionHighlight,
+}
+
+/// Condition of the `label_id` filter: the tasks having (or not) the labels
+fn label_id_to_sea_condition(
+    _col: &ColumnRef,
+    op_val: OpValValue,
+) -> SeaResult<ConditionExpression> {
+    let label_id = |value: serde_json::Value| {
+        value
+            .as_i64()
+            .ok_or_else(|| IntoSeaError::custom("label_id should be an integer"))
+    };
+    let label_ids = |values: Vec<serde_json::Value>| -> SeaResult<Vec<i64>> {
+        values.into_iter().map(label_id).collect()
+    };
+    // The ids of the tasks having one of the labels matching the condition (all if None)
+    let task_ids = |label_cond: Option<SimpleExpr>| {
+        Query::select()
+            .column(TaskLabelIden::TaskId)
+            .from(TaskLabelIden::Table)
+            .and_where_option(label_cond)
+            .to_owned()
+    };
+    let id_col = Expr::col(TaskIden::Id);
+    let label_col = || Expr::col(TaskLabelIden::LabelId);
+
+    let cond = match op_val {
+        OpValValue::Eq(value) => {
+            id_col.in_subquery(task_ids(Some(label_col().eq(label_id(value)?))))
+        }
+        OpValValue::Not(value) => {
+            id_col.not_in_subquery(task_ids(Some(label_col().eq(label_id(value)?))))
+        }
+        OpValValue::In(values) => {
+            id_col.in_subquery(task_ids(Some(label_col().is_in(label_ids(values)?))))
+        }
+        OpValValue::NotIn(values) => {
+            id_col.not_in_subquery(task_ids(Some(label_col().is_in(label_ids(values)?))))
+        }
+        // `$null: true` for the tasks without labels
+        OpValValue::Null(true) => id_col.not_in_subquery(task_ids(None)),
+        OpValValue::Null(false) => id_col.in_subquery(task_ids(None)),
+        _ => {
+            return Err(IntoSeaError::custom(
+                "label_id supports only $eq, $not, $in, $notIn and $null",
+            ))
+        }
+    };
+
+    Ok(ConditionExpression::SimpleExpr(cond))
 }
 // endregion: --- Task Types
 
@@ -39,11 +374,68 @@ pub struct TaskBmc;
 impl DbBmc for TaskBmc {
     // Table name is constant
     const TABLE: &'static str = "task";
+    const VERSIONED: bool = true;
+    const SOFT_DELETE: bool = true;
+
+    // A task is owned by the owner of its project
+    fn owner_cond(user_id: i64) -> Option<SimpleExpr> {
+        Some(
+            Expr::col(TaskIden::ProjectId).in_subquery(
+                Query::select()
+                    .column(ProjectIden::Id)
+                    .from(ProjectBmc::table_ref())
+                    .and_where(Expr::col(ProjectIden::OwnerId).eq(user_id))
+                    .to_owned(),
+            ),
+        )
+    }
 }
 
+/// Priority bounds (0 none, 1 low, 2 medium, 3 high, 4 urgent)
+const TASK_PRIORITY_MIN: i32 = 0;
+const TASK_PRIORITY_MAX: i32 = 4;
+
+/// Number of search hits returned by default, and at most
+const TASK_SEARCH_LIMIT_DEFAULT: i64 = 20;
+const TASK_SEARCH_LIMIT_MAX: i64 = 100;
+
 impl TaskBmc {
+    /// Check the priority range and the assignee existence (when set)
+    async fn validate(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        priority: Option<i32>,
+        assignee_id: Option<i64>,
+    ) -> Result<()> {
+        if let Some(priority) = priority {
+            if !(TASK_PRIORITY_MIN..=TASK_PRIORITY_MAX).contains(&priority) {
+                return Err(Error::TaskPriorityOutOfRange {
+                    max: TASK_PRIORITY_MAX,
+                    actual: priority,
+                });
+            }
+        }
+        if let Some(assignee_id) = assignee_id {
+            UserBmc::get::<User>(ctx, mm, assignee_id).await?;
+        }
+
+        Ok(())
+    }
+
     pub async fn create(ctx: &Ctx, mm: &ModelManager, task_c: TaskForCreate) -> Result<i64> {
-        base::create::<Self, _>(ctx, mm, task_c).await
+        // Check that the project is accessible by the ctx user
+        ProjectBmc::get(ctx, mm, task_c.project_id).await?;
+        Self::validate(ctx, mm, task_c.priority, task_c.assignee_id).await?;
+
+        // The task and its activity are written together
+        let txn = BatchTxn::begin(mm).await?;
+        let id = base::create::<Self, _>(ctx, &txn.mm, task_c).await?;
+        let task = Self::get(ctx, &txn.mm, id).await?;
+        let changes = task_changes(None, &task)?;
+        TaskActivityBmc::record(ctx, &txn.mm, id, TaskActivityKind::Create, changes).await?;
+        txn.commit().await?;
+
+        Ok(id)
     }
 
     pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<Task> {
@@ -59,18 +451,352 @@ impl TaskBmc {
         base::list::<Self, _, _>(ctx, mm, filters, list_options).await
     }
 
+    /// Full-text search of the tasks (title and description), the most relevant first.
+    /// The query is in the web search syntax, e.g., `deploy -staging "release notes"`.
+    pub async fn search(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        text: &str,
+        limit: Option<i64>,
+    ) -> Result<Vec<TaskSearchHit>> {
+        let limit = limit.unwrap_or(TASK_SEARCH_LIMIT_DEFAULT);
+        if limit < 1 {
+            return Err(Error::ListLimitNotPositive { actual: limit });
+        }
+        if limit > TASK_SEARCH_LIMIT_MAX {
+            return Err(Error::ListLimitOverMax {
+                max: TASK_SEARCH_LIMIT_MAX,
+                actual: limit,
+            });
+        }
+
+        let mut conn = mm.conn().await?;
+        let ts_query = "websearch_to_tsquery('english', $1)";
+        // The text is HTML-escaped before highlighted, only the `<mark>` tags are markup
+        // (the entities, e.g., `&lt;`, are single tokens for the headline, never cut)
+        let headline = |column: &str| {
+            format!(
+                "ts_headline('english', {}, {ts_query}, \
+                 'StartSel=<mark>, StopSel=</mark>, HighlightAll=false')",
+                html_escape_sql(column)
+            )
+        };
+
+        // Build the SQL query
+        let mut query = Query::select();
+        query
+            .from(Self::table_ref())
+            .columns(Task::field_column_refs())
+            .expr_as(
+                Expr::cust_with_values(format!("ts_rank(search_tsv, {ts_query})"), [text]),
+                TaskIden::Rank,
+            )
+            .expr_as(
+                Expr::cust_with_values(headline("title"), [text]),
+                TaskIden::TitleHighlight,
+            )
+            .expr_as(
+                Expr::cust_with_values(headline("description"), [text]),
+                TaskIden::DescriptionHighlight,
+            )
+            .and_where(Expr::cust_with_values(
+                format!("search_tsv @@ {ts_query}"),
+                [text],
+            ))
+            .and_where_option(base::owner_cond::<Self>(ctx))
+            .and_where_option(base::not_deleted_cond::<Self>())
+            .order_by(TaskIden::Rank, Order::Desc)
+            .order_by(TaskIden::Id, Order::Asc)
+            .limit(limit as u64);
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+
+        // Execute the query
+        let hits = sqlx::query_as_with::<_, TaskSearchHit, _>(&sql, values)
+            .fetch_all(&mut *conn)
+            .await?;
+
+        Ok(hits)
+    }
+
+    /// List a page of tasks (see `base::list_page` for the cursor paging)
+    pub async fn list_page(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        filters: Option<Vec<TaskFilter>>,
+        list_options: Option<ListOptions>,
+        page_options: Option<PageOptions>,
+    ) -> Result<Page<Task>> {
+        base::list_page::<Self, _, _>(ctx, mm, filters, list_options, page_options).await
+    }
+
     pub async fn update(
         ctx: &Ctx,
         mm: &ModelManager,
         id: i64,
-        task_u: TaskForUpdate,
+        mut task_u: TaskForUpdate,
+    ) -> Result<()> {
+        task_u.resolve_done()?;
+        Self::validate(ctx, mm, task_u.priority, task_u.assignee_id.flatten()).await?;
+
+        // The task and its activity are written together
+        let txn = BatchTxn::begin(mm).await?;
+        let task_before = Self::get(ctx, &txn.mm, id).await?;
+        let version = task_u.version;
+        base::update_with_version::<Self, _>(ctx, &txn.mm, id, task_u, version).await?;
+        let task = Self::get(ctx, &txn.mm, id).await?;
+        Self::record_update(ctx, &txn.mm, &task_before, &task).await?;
+        txn.commit().await?;
+
+        Ok(())
+    }
+
+    /// Record the update activity of a task (if any field changed)
+    async fn record_update(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        task_before: &Task,
+        task: &Task,
     ) -> Result<()> {
-        base::update::<Self, _>(ctx, mm, id, task_u).await
+        let changes = task_changes(Some(task_before), task)?;
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        TaskActivityBmc::record(ctx, mm, task.id, TaskActivityKind::Update, changes).await
+    }
+
+    /// Create the tasks in one transaction (all or none)
+    pub async fn create_many(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        tasks_c: Vec<TaskForCreate>,
+    ) -> Result<Vec<Task>> {
+        let txn = BatchTxn::begin(mm).await?;
+
+        // Check that the projects are accessible by the ctx user
+        // (in the transaction, so a project deleted meanwhile fails the batch)
+        let project_ids: BTreeSet<i64> = tasks_c.iter().map(|task_c| task_c.project_id).collect();
+        for project_id in project_ids {
+            ProjectBmc::get(ctx, &txn.mm, project_id).await?;
+        }
+        for (index, task_c) in tasks_c.iter().enumerate() {
+            Self::validate(ctx, &txn.mm, task_c.priority, task_c.assignee_id)
+                .await
+                .map_err(|err| Error::batch_item_failed(index, err))?;
+        }
+
+        let tasks = base::create_many::<Self, _, _>(ctx, &txn.mm, tasks_c).await?;
+        for task in tasks.iter() {
+            let changes = task_changes(None, task)?;
+            TaskActivityBmc::record(ctx, &txn.mm, task.id, TaskActivityKind::Create, changes)
+                .await?;
+        }
+        txn.commit().await?;
+
+        Ok(tasks)
+    }
+
+    /// Update the tasks in one transaction (all or none)
+    /// (An id can only be once in the batch)
+    pub async fn update_many(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        mut tasks_u: Vec<(i64, TaskForUpdate)>,
+    ) -> Result<Vec<Task>> {
+        // (The activity of a repeated id would diff against the row before the batch)
+        let mut ids = BTreeSet::new();
+        for (index, (id, _)) in tasks_u.iter().enumerate() {
+            if !ids.insert(*id) {
+                return Err(Error::batch_item_failed(
+                    index,
+                    Error::BatchIdDuplicate { id: *id },
+                ));
+            }
+        }
+        for (index, (_, task_u)) in tasks_u.iter_mut().enumerate() {
+            task_u
+                .resolve_done()
+                .map_err(|err| Error::batch_item_failed(index, err))?;
+            Self::validate(ctx, mm, task_u.priority, task_u.assignee_id.flatten())
+                .await
+                .map_err(|err| Error::batch_item_failed(index, err))?;
+        }
+
+        let txn = BatchTxn::begin(mm).await?;
+        let mut tasks_before = Vec::with_capacity(tasks_u.len());
+        for (index, (id, _)) in tasks_u.iter().enumerate() {
+            let task_before = Self::get(ctx, &txn.mm, *id)
+                .await
+                .map_err(|err| Error::batch_item_failed(index, err))?;
+            tasks_before.push(task_before);
+        }
+
+        let items = tasks_u
+            .into_iter()
+            .map(|(id, task_u)| {
+                let version = task_u.version;
+                (id, task_u, version)
+            })
+            .collect();
+        let tasks = base::update_many::<Self, _, _>(ctx, &txn.mm, items).await?;
+        for (task_before, task) in tasks_before.iter().zip(tasks.iter()) {
+            Self::record_update(ctx, &txn.mm, task_before, task).await?;
+        }
+        txn.commit().await?;
+
+        Ok(tasks)
+    }
+
+    /// Move the tasks to the trash in one transaction (all or none)
+    pub async fn delete_many(ctx: &Ctx, mm: &ModelManager, ids: Vec<i64>) -> Result<Vec<Task>> {
+        let txn = BatchTxn::begin(mm).await?;
+        let tasks: Vec<Task> = base::delete_many::<Self, _>(ctx, &txn.mm, ids).await?;
+        for task_before in tasks.iter() {
+            let task = Self::get_deleted(ctx, &txn.mm, task_before.id).await?;
+            let changes = deleted_at_changes(task_before, &task)?;
+            TaskActivityBmc::record(ctx, &txn.mm, task.id, TaskActivityKind::Delete, changes)
+                .await?;
+        }
+        txn.commit().await?;
+
+        Ok(tasks)
     }
 
+    /// Move the task to the trash (restorable until purged)
     pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
-        base::delete::<Self>(ctx, mm, id).await
+        let txn = BatchTxn::begin(mm).await?;
+        let task_before = Self::get(ctx, &txn.mm, id).await?;
+        base::delete::<Self>(ctx, &txn.mm, id).await?;
+        let task = Self::get_deleted(ctx, &txn.mm, id).await?;
+        let changes = deleted_at_changes(&task_before, &task)?;
+        TaskActivityBmc::record(ctx, &txn.mm, id, TaskActivityKind::Delete, changes).await?;
+        txn.commit().await?;
+
+        Ok(())
+    }
+
+    /// Restore a task from the trash
+    pub async fn restore(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+        let txn = BatchTxn::begin(mm).await?;
+        let task_before = Self::get_deleted(ctx, &txn.mm, id).await?;
+        base::restore::<Self>(ctx, &txn.mm, id).await?;
+        let task = Self::get(ctx, &txn.mm, id).await?;
+        let changes = deleted_at_changes(&task_before, &task)?;
+        TaskActivityBmc::record(ctx, &txn.mm, id, TaskActivityKind::Restore, changes).await?;
+        txn.commit().await?;
+
+        Ok(())
+    }
+
+    /// List a page of the tasks in the trash (see `base::list_page` for the cursor paging)
+    pub async fn list_deleted(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        filters: Option<Vec<TaskFilter>>,
+        list_options: Option<ListOptions>,
+        page_options: Option<PageOptions>,
+    ) -> Result<Page<Task>> {
+        base::list_deleted_page::<Self, _, _>(ctx, mm, filters, list_options, page_options).await
+    }
+
+    /// Get a task in the trash
+    async fn get_deleted(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<Task> {
+        let filter = TaskFilter {
+            id: Some(id.into()),
+            ..Default::default()
+        };
+        let page = Self::list_deleted(ctx, mm, Some(vec![filter]), None, None).await?;
+
+        page.data.into_iter().next().ok_or(Error::EntityNotFound {
+            entity: Self::TABLE,
+            id,
+        })
+    }
+
+    /// Permanently delete the tasks in the trash for longer than the retention
+    /// (Returns the ids of the purged tasks)
+    pub async fn purge_deleted(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        retention: Duration,
+    ) -> Result<Vec<i64>> {
+        base::purge_deleted::<Self>(ctx, mm, now_utc() - retention).await
+    }
+
+    /// Purge the trash with the configured retention (`SERVICE_TASK_TRASH_RETENTION_SEC`)
+    pub async fn purge_trash(ctx: &Ctx, mm: &ModelManager) -> Result<Vec<i64>> {
+        let retention = Duration::seconds(core_config().TASK_TRASH_RETENTION_SEC);
+        Self::purge_deleted(ctx, mm, retention).await
+    }
+
+    /// Add a label (of the task project) to the task (no-op if already there)
+    pub async fn add_label(ctx: &Ctx, mm: &ModelManager, id: i64, label_id: i64) -> Result<()> {
+        let task = Self::get(ctx, mm, id).await?;
+        let label = LabelBmc::get(ctx, mm, label_id).await?;
+        if label.project_id != task.project_id {
+            return Err(Error::LabelNotInTaskProject {
+                label_id,
+                task_id: id,
+            });
+        }
+
+        let mut conn = mm.conn().await?;
+
+        // Build the SQL query
+        let mut query = Query::insert();
+        query
+            .into_table(TaskLabelIden::Table)
+            .columns([TaskLabelIden::TaskId, TaskLabelIden::LabelId])
+            .values([id.into(), label_id.into()])?
+            .on_conflict(
+                OnConflict::columns([TaskLabelIden::TaskId, TaskLabelIden::LabelId])
+                    .do_nothing()
+                    .to_owned(),
+            );
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+
+        // Execute the query
+        sqlx::query_with(&sql, values).execute(&mut *conn).await?;
+
+        Ok(())
+    }
+
+    /// Remove a label from the task (no-op if not there)
+    pub async fn remove_label(ctx: &Ctx, mm: &ModelManager, id: i64, label_id: i64) -> Result<()> {
+        // Check that the task is accessible by the ctx user
+        Self::get(ctx, mm, id).await?;
+
+        let mut conn = mm.conn().await?;
+
+        // Build the SQL query
+        let mut query = Query::delete();
+        query
+            .from_table(TaskLabelIden::Table)
+            .and_where(Expr::col(TaskLabelIden::TaskId).eq(id))
+            .and_where(Expr::col(TaskLabelIden::LabelId).eq(label_id));
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+
+        // Execute the query
+        sqlx::query_with(&sql, values).execute(&mut *conn).await?;
+
+        Ok(())
     }
+
+    /// List the labels of the task
+    pub async fn list_labels(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<Vec<Label>> {
+        // Check that the task is accessible by the ctx user
+        Self::get(ctx, mm, id).await?;
+
+        LabelBmc::list_for_task(ctx, mm, id).await
+    }
+}
+
+/// The SQL expression of the column text, HTML-escaped
+fn html_escape_sql(column: &str) -> String {
+    format!(
+        "replace(replace(replace(replace(replace({column}, \
+         '&', '&amp;'), '<', '&lt;'), '>', '&gt;'), '\"', '&quot;'), '''', '&#39;')"
+    )
 }
 // endregion: --- TaskBmc
 
@@ -79,8 +805,11 @@ impl TaskBmc {
 mod tests {
     use super::*;
     use crate::_dev_utils;
-    use crate::model::psql::Error;
+    use crate::model::psql::label::{LabelForCreate, LabelForUpdate};
+    use crate::model::psql::task_activity::TaskActivityFilter;
+    use crate::model::psql::{store, Error};
     use anyhow::Result;
+    use lib_utils::time::{format_time, now_utc};
     use serde_json::json;
     use serial_test::serial;
 
@@ -89,22 +818,29 @@ mod tests {
     async fn test_create_ok() -> Result<()> {
         // -- Setup & Fixtures
         let mm = _dev_utils::init_test().await;
-        // root_ctx
-        let ctx = Ctx::root_ctx();
+        // owner ctx (the projects are scoped to their owner)
+        let ctx = Ctx::new(1000)?;
         let fx_title = "test_create_ok title";
+        let fx_project_id = _dev_utils::seed_project(&ctx, &mm, "test_create_ok project").await?;
 
         // -- Exec
         let task_c = TaskForCreate {
+            project_id: fx_project_id,
             title: fx_title.to_string(),
+            ..Default::default()
         };
         let id = TaskBmc::create(&ctx, &mm, task_c).await?;
 
         // -- Check
         let task = TaskBmc::get(&ctx, &mm, id).await?;
         assert_eq!(task.title, fx_title);
+        assert_eq!(task.project_id, fx_project_id);
+        assert_eq!(task.cid, ctx.user_id());
+        assert_eq!(task.ctime, task.mtime);
 
         // Clean
         TaskBmc::delete(&ctx, &mm, id).await?;
+        ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
 
         Ok(())
     }
@@ -139,9 +875,10 @@ mod tests {
     async fn test_list_all_ok() -> Result<()> {
         // -- Setup & Fixtures
         let mm = _dev_utils::init_test().await;
-        let ctx = Ctx::root_ctx();
+        let ctx = Ctx::new(1000)?;
         let fx_titles = &["test_list_all_ok-task 01", "test_list_all_ok-task 02"];
-        _dev_utils::seed_tasks(&ctx, &mm, fx_titles).await?;
+        let fx_project_id = _dev_utils::seed_project(&ctx, &mm, "test_list_all_ok project").await?;
+        _dev_utils::seed_tasks(&ctx, &mm, fx_project_id, fx_titles).await?;
 
         // -- Exec
         let tasks = TaskBmc::list(&ctx, &mm, None, None).await?;
@@ -157,6 +894,7 @@ mod tests {
         for task in tasks.iter() {
             TaskBmc::delete(&ctx, &mm, task.id).await?;
         }
+        ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
 
         Ok(())
     }
@@ -166,7 +904,7 @@ mod tests {
     async fn test_list_by_filter_ok() -> Result<()> {
         // -- Setup & Fixtures
         let mm = _dev_utils::init_test().await;
-        let ctx = Ctx::root_ctx();
+        let ctx = Ctx::new(1000)?;
         let fx_titles = &[
             "test_list_by_filter_ok-task 01.a",
             "test_list_by_filter_ok-task 01.b",
@@ -174,7 +912,9 @@ mod tests {
             "test_list_by_filter_ok-task 02.b",
             "test_list_by_filter_ok-task 03",
         ];
-        _dev_utils::seed_tasks(&ctx, &mm, fx_titles).await?;
+        let fx_project_id =
+            _dev_utils::seed_project(&ctx, &mm, "test_list_by_filter_ok project").await?;
+        _dev_utils::seed_tasks(&ctx, &mm, fx_project_id, fx_titles).await?;
 
         // -- Exec
         let filters = serde_json::from_value(json!([
@@ -228,6 +968,176 @@ mod tests {
         for task in tasks.iter() {
             TaskBmc::delete(&ctx, &mm, task.id).await?;
         }
+        ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_list_page_cursor_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::new(1000)?;
+        let fx_titles = &[
+            "test_list_page_cursor_ok-task 01",
+            "test_list_page_cursor_ok-task 02",
+            "test_list_page_cursor_ok-task 03",
+            "test_list_page_cursor_ok-task 04",
+            "test_list_page_cursor_ok-task 05",
+        ];
+        let fx_project_id =
+            _dev_utils::seed_project(&ctx, &mm, "test_list_page_cursor_ok project").await?;
+        _dev_utils::seed_tasks(&ctx, &mm, fx_project_id, fx_titles).await?;
+        let fx_filters = json!([{"project_id": fx_project_id}]);
+        let fx_list_options: ListOptions =
+            serde_json::from_value(json!({"limit": 2, "order_bys": "!id"}))?;
+
+        // -- Exec
+        // Page through all the tasks (newest first)
+        let mut pages: Vec<Page<Task>> = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = TaskBmc::list_page(
+                &ctx,
+                &mm,
+                Some(serde_json::from_value(fx_filters.clone())?),
+                Some(fx_list_options.clone()),
+                Some(PageOptions {
+                    cursor,
+                    with_total: Some(true),
+                }),
+            )
+            .await?;
+            cursor = page.next_cursor.clone();
+            pages.push(page);
+            if cursor.is_none() {
+                break;
+            }
+        }
+
+        // -- Check
+        let titles: Vec<&str> = pages
+            .iter()
+            .flat_map(|page| page.data.iter().map(|t| t.title.as_str()))
+            .collect();
+        assert_eq!(pages.len(), 3);
+        assert!(pages.iter().all(|page| page.total == Some(5)));
+        let mut fx_titles_desc = fx_titles.to_vec();
+        fx_titles_desc.reverse();
+        assert_eq!(titles, fx_titles_desc);
+
+        // -- Clean
+        ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_list_page_err_cursor() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::new(1000)?;
+        let fx_titles = &[
+            "test_list_page_err_cursor-task 01",
+            "test_list_page_err_cursor-task 02",
+        ];
+        let fx_project_id =
+            _dev_utils::seed_project(&ctx, &mm, "test_list_page_err_cursor project").await?;
+        _dev_utils::seed_tasks(&ctx, &mm, fx_project_id, fx_titles).await?;
+        let fx_list_options: ListOptions = serde_json::from_value(json!({"order_bys": "title"}))?;
+        let page = TaskBmc::list_page(
+            &ctx,
+            &mm,
+            Some(serde_json::from_value(
+                json!([{"project_id": fx_project_id}]),
+            )?),
+            Some(serde_json::from_value(json!({"limit": 1}))?),
+            None,
+        )
+        .await?;
+        let fx_cursor = page.next_cursor;
+
+        // -- Exec
+        let res_invalid = TaskBmc::list_page(
+            &ctx,
+            &mm,
+            None,
+            None,
+            Some(PageOptions {
+                cursor: Some("not-a-cursor".to_string()),
+                with_total: None,
+            }),
+        )
+        .await;
+        let res_order_first = TaskBmc::list_page(
+            &ctx,
+            &mm,
+            None,
+            Some(serde_json::from_value(json!({"order_bys": "title"}))?),
+            None,
+        )
+        .await;
+        let res_order = TaskBmc::list_page(
+            &ctx,
+            &mm,
+            None,
+            Some(fx_list_options),
+            Some(PageOptions {
+                cursor: fx_cursor,
+                with_total: None,
+            }),
+        )
+        .await;
+
+        // -- Check
+        assert!(
+            matches!(res_invalid, Err(Error::ListCursorInvalid { .. })),
+            "ListCursorInvalid not matching"
+        );
+        assert!(
+            matches!(res_order_first, Err(Error::ListCursorOrderUnsupported)),
+            "ListCursorOrderUnsupported (first page) not matching"
+        );
+        assert!(
+            matches!(res_order, Err(Error::ListCursorOrderUnsupported)),
+            "ListCursorOrderUnsupported not matching"
+        );
+
+        // -- Clean
+        ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_list_by_ctime_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::new(1000)?;
+        let fx_project_id =
+            _dev_utils::seed_project(&ctx, &mm, "test_list_by_ctime_ok project").await?;
+        _dev_utils::seed_tasks(&ctx, &mm, fx_project_id, &["test_list_by_ctime_ok-task 01"])
+            .await?;
+        let fx_time = format_time(now_utc());
+        _dev_utils::seed_tasks(&ctx, &mm, fx_project_id, &["test_list_by_ctime_ok-task 02"])
+            .await?;
+
+        // -- Exec
+        let filters = serde_json::from_value(json!([{
+            "project_id": fx_project_id,
+            "ctime": { "$gt": fx_time },
+        }]))?;
+        let tasks = TaskBmc::list(&ctx, &mm, Some(filters), None).await?;
+
+        // -- Check
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].title, "test_list_by_ctime_ok-task 02");
+
+        // -- Clean
+        ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
 
         Ok(())
     }
@@ -236,10 +1146,11 @@ mod tests {
     #[tokio::test]
     async fn test_update_ok() -> Result<()> {
         let mm = _dev_utils::init_test().await;
-        let ctx = Ctx::root_ctx();
+        let ctx = Ctx::new(1000)?;
         let fx_title = "test_list_ok - task 01";
         let fx_title_updated = "test_list_ok - task 01 - updated";
-        let fx_task = _dev_utils::seed_tasks(&ctx, &mm, &[fx_title])
+        let fx_project_id = _dev_utils::seed_project(&ctx, &mm, "test_update_ok project").await?;
+        let fx_task = _dev_utils::seed_tasks(&ctx, &mm, fx_project_id, &[fx_title])
             .await?
             .remove(0);
 
@@ -256,6 +1167,843 @@ mod tests {
 
         let task = TaskBmc::get(&ctx, &mm, fx_task.id).await?;
         assert_eq!(task.title, fx_title_updated);
+        assert_eq!(task.ctime, fx_task.ctime);
+        assert!(task.mtime > fx_task.mtime, "mtime should be updated");
+        assert_eq!(task.version, fx_task.version + 1);
+
+        // The legacy `done` is mapped onto the status
+        let fx_task_u: TaskForUpdate = serde_json::from_value(json!({"done": true}))?;
+        TaskBmc::update(&ctx, &mm, fx_task.id, fx_task_u).await?;
+        let task = TaskBmc::get(&ctx, &mm, fx_task.id).await?;
+        assert_eq!(task.status, TaskStatus::Done);
+        assert!(task.done);
+
+        // -- Clean
+        ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_update_clear_nullable_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::new(1000)?;
+        let fx_project_id =
+            _dev_utils::seed_project(&ctx, &mm, "test_update_clear_nullable_ok project").await?;
+        let fx_task = _dev_utils::seed_tasks(
+            &ctx,
+            &mm,
+            fx_project_id,
+            &["test_update_clear_nullable_ok task"],
+        )
+        .await?
+        .remove(0);
+        let fx_task_u: TaskForUpdate = serde_json::from_value(json!({
+            "description": "some description",
+            "due_at": "2030-01-01T00:00:00Z",
+            "assignee_id": 1000,
+        }))?;
+        TaskBmc::update(&ctx, &mm, fx_task.id, fx_task_u).await?;
+
+        // -- Exec
+        // The absent fields are left unchanged
+        let fx_task_u: TaskForUpdate = serde_json::from_value(json!({"title": "new title"}))?;
+        TaskBmc::update(&ctx, &mm, fx_task.id, fx_task_u).await?;
+        let task_kept = TaskBmc::get(&ctx, &mm, fx_task.id).await?;
+        // An explicit null clears the field
+        let fx_task_u: TaskForUpdate = serde_json::from_value(json!({
+            "description": null,
+            "due_at": null,
+            "assignee_id": null,
+        }))?;
+        TaskBmc::update(&ctx, &mm, fx_task.id, fx_task_u).await?;
+        let task_cleared = TaskBmc::get(&ctx, &mm, fx_task.id).await?;
+
+        // -- Check
+        assert_eq!(task_kept.title, "new title");
+        assert_eq!(task_kept.description.as_deref(), Some("some description"));
+        assert!(task_kept.due_at.is_some());
+        assert_eq!(task_kept.assignee_id, Some(1000));
+        assert_eq!(task_cleared.title, "new title");
+        assert_eq!(task_cleared.description, None);
+        assert_eq!(task_cleared.due_at, None);
+        assert_eq!(task_cleared.assignee_id, None);
+
+        // -- Clean
+        ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_update_err_version_conflict() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::new(1000)?;
+        let fx_project_id =
+            _dev_utils::seed_project(&ctx, &mm, "test_update_err_version_conflict project").await?;
+        let fx_task = _dev_utils::seed_tasks(
+            &ctx,
+            &mm,
+            fx_project_id,
+            &["test_update_err_version_conflict task"],
+        )
+        .await?
+        .remove(0);
+
+        // -- Exec
+        // Two clients update the task read at the same version
+        let res_first = TaskBmc::update(
+            &ctx,
+            &mm,
+            fx_task.id,
+            TaskForUpdate {
+                title: Some("first".to_string()),
+                version: Some(fx_task.version),
+                ..Default::default()
+            },
+        )
+        .await;
+        let res_second = TaskBmc::update(
+            &ctx,
+            &mm,
+            fx_task.id,
+            TaskForUpdate {
+                title: Some("second".to_string()),
+                version: Some(fx_task.version),
+                ..Default::default()
+            },
+        )
+        .await;
+
+        // -- Check
+        assert!(res_first.is_ok(), "first update should succeed");
+        assert!(
+            matches!(
+                res_second,
+                Err(Error::VersionConflict { expected, actual, .. })
+                    if expected == fx_task.version && actual == fx_task.version + 1
+            ),
+            "VersionConflict not matching"
+        );
+        let task = TaskBmc::get(&ctx, &mm, fx_task.id).await?;
+        assert_eq!(task.title, "first");
+
+        // -- Clean
+        ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_access_err_not_project_owner() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx_owner = Ctx::new(1000)?;
+        let ctx_other = Ctx::new(1001)?;
+        let fx_project_id =
+            _dev_utils::seed_project(&ctx_owner, &mm, "test_access_err_not_project_owner project")
+                .await?;
+        let fx_task = _dev_utils::seed_tasks(
+            &ctx_owner,
+            &mm,
+            fx_project_id,
+            &["test_access_err_not_project_owner task"],
+        )
+        .await?
+        .remove(0);
+
+        // -- Exec
+        let res_create = TaskBmc::create(
+            &ctx_other,
+            &mm,
+            TaskForCreate {
+                project_id: fx_project_id,
+                title: "test_access_err_not_project_owner task other".to_string(),
+                ..Default::default()
+            },
+        )
+        .await;
+        let res_get = TaskBmc::get(&ctx_other, &mm, fx_task.id).await;
+        let res_update = TaskBmc::update(
+            &ctx_other,
+            &mm,
+            fx_task.id,
+            TaskForUpdate {
+                status: Some(TaskStatus::Done),
+                ..Default::default()
+            },
+        )
+        .await;
+        let res_delete = TaskBmc::delete(&ctx_other, &mm, fx_task.id).await;
+        let tasks = TaskBmc::list(&ctx_other, &mm, None, None).await?;
+
+        // -- Check
+        assert!(
+            matches!(
+                res_create,
+                Err(Error::EntityNotFound {
+                    entity: "project",
+                    ..
+                })
+            ),
+            "EntityNotFound not matching for create"
+        );
+        for res in [res_get.map(|_| ()), res_update, res_delete] {
+            assert!(
+                matches!(res, Err(Error::EntityNotFound { entity: "task", .. })),
+                "EntityNotFound not matching"
+            );
+        }
+        assert!(!tasks.iter().any(|t| t.id == fx_task.id));
+
+        // -- Clean
+        ProjectBmc::delete(&ctx_owner, &mm, fx_project_id).await?;
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_create_update_delete_many_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::new(1000)?;
+        let fx_project_id =
+            _dev_utils::seed_project(&ctx, &mm, "test_create_update_delete_many_ok project")
+                .await?;
+        let fx_titles = &["test_many_ok-task 01", "test_many_ok-task 02"];
+
+        // -- Exec
+        let tasks_c = fx_titles
+            .iter()
+            .map(|title| TaskForCreate {
+                project_id: fx_project_id,
+                title: title.to_string(),
+                ..Default::default()
+            })
+            .collect();
+        let created = TaskBmc::create_many(&ctx, &mm, tasks_c).await?;
+        let tasks_u = created
+            .iter()
+            .map(|task| {
+                let task_u = TaskForUpdate {
+                    title: Some(format!("{} updated", task.title)),
+                    version: Some(task.version),
+                    ..Default::default()
+                };
+                (task.id, task_u)
+            })
+            .collect();
+        let updated = TaskBmc::update_many(&ctx, &mm, tasks_u).await?;
+        let ids = updated.iter().map(|task| task.id).rev().collect();
+        let deleted = TaskBmc::delete_many(&ctx, &mm, ids).await?;
+
+        // -- Check
+        let titles: Vec<&str> = created.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(titles, fx_titles);
+        let titles: Vec<&str> = updated.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(
+            titles,
+            &[
+                "test_many_ok-task 01 updated",
+                "test_many_ok-task 02 updated"
+            ]
+        );
+        assert_eq!(deleted[0].id, updated[1].id);
+        for task in deleted {
+            let res = TaskBmc::get(&ctx, &mm, task.id).await;
+            assert!(matches!(res, Err(Error::EntityNotFound { .. })));
+        }
+
+        // -- Clean
+        ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_update_many_err_rollback() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::new(1000)?;
+        let fx_project_id =
+            _dev_utils::seed_project(&ctx, &mm, "test_update_many_err_rollback project").await?;
+        let fx_task = _dev_utils::seed_tasks(
+            &ctx,
+            &mm,
+            fx_project_id,
+            &["test_update_many_err_rollback task"],
+        )
+        .await?
+        .remove(0);
+        let fx_task_u = || TaskForUpdate {
+            title: Some("updated".to_string()),
+            ..Default::default()
+        };
+
+        // -- Exec
+        // The second item is not found, the first update is rolled back
+        let res = TaskBmc::update_many(
+            &ctx,
+            &mm,
+            vec![(fx_task.id, fx_task_u()), (100, fx_task_u())],
+        )
+        .await;
+        // The same task twice
+        let res_dup = TaskBmc::update_many(
+            &ctx,
+            &mm,
+            vec![(fx_task.id, fx_task_u()), (fx_task.id, fx_task_u())],
+        )
+        .await;
+
+        // -- Check
+        assert!(
+            matches!(
+                &res,
+                Err(Error::BatchItemFailed { index: 1, cause })
+                    if matches!(**cause, Error::EntityNotFound { id: 100, .. })
+            ),
+            "BatchItemFailed not matching"
+        );
+        assert!(
+            matches!(
+                &res_dup,
+                Err(Error::BatchItemFailed { index: 1, cause })
+                    if matches!(**cause, Error::BatchIdDuplicate { .. })
+            ),
+            "BatchIdDuplicate not matching"
+        );
+        let task = TaskBmc::get(&ctx, &mm, fx_task.id).await?;
+        assert_eq!(task.title, fx_task.title);
+        assert_eq!(task.version, fx_task.version);
+
+        // -- Clean
+        ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_txn_commit_rollback_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::new(1000)?;
+        let fx_project_id =
+            _dev_utils::seed_project(&ctx, &mm, "test_txn_commit_rollback_ok project").await?;
+        let fx_task_c = |title: &str| TaskForCreate {
+            project_id: fx_project_id,
+            title: title.to_string(),
+            ..Default::default()
+        };
+
+        // -- Exec
+        let mm_txn = mm.new_with_txn();
+        mm_txn.begin_txn().await?;
+        let id_rolled_back = TaskBmc::create(&ctx, &mm_txn, fx_task_c("rolled back")).await?;
+        // Seen in the transaction
+        TaskBmc::get(&ctx, &mm_txn, id_rolled_back).await?;
+        mm_txn.rollback_txn().await?;
+
+        mm_txn.begin_txn().await?;
+        let id_committed = TaskBmc::create(&ctx, &mm_txn, fx_task_c("committed")).await?;
+        let res_begin = mm_txn.begin_txn().await;
+        mm_txn.commit_txn().await?;
+        let res_commit = mm_txn.commit_txn().await;
+
+        // -- Check
+        let res = TaskBmc::get(&ctx, &mm, id_rolled_back).await;
+        assert!(matches!(res, Err(Error::EntityNotFound { .. })));
+        let task = TaskBmc::get(&ctx, &mm, id_committed).await?;
+        assert_eq!(task.title, "committed");
+        assert!(matches!(
+            res_begin,
+            Err(Error::Store(store::Error::TxnAlreadyBegun))
+        ));
+        assert!(matches!(
+            res_commit,
+            Err(Error::Store(store::Error::TxnNotBegun))
+        ));
+        assert!(matches!(
+            mm.begin_txn().await,
+            Err(Error::Store(store::Error::TxnNotSupported))
+        ));
+
+        // -- Clean
+        ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_delete_restore_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::new(1000)?;
+        let fx_titles = &[
+            "test_delete_restore_ok-task 01",
+            "test_delete_restore_ok-task 02",
+        ];
+        let fx_project_id =
+            _dev_utils::seed_project(&ctx, &mm, "test_delete_restore_ok project").await?;
+        let fx_tasks = _dev_utils::seed_tasks(&ctx, &mm, fx_project_id, fx_titles).await?;
+        let fx_id = fx_tasks[0].id;
+        let fx_filters = || -> Result<Option<Vec<TaskFilter>>> {
+            Ok(serde_json::from_value(json!([{
+                "title": {"$startsWith": "test_delete_restore_ok-task"}
+            }]))?)
+        };
+
+        // -- Exec & Check: delete (moved to the trash)
+        TaskBmc::delete(&ctx, &mm, fx_id).await?;
+
+        let res_get = TaskBmc::get(&ctx, &mm, fx_id).await;
+        assert!(matches!(res_get, Err(Error::EntityNotFound { .. })));
+        let res_update = TaskBmc::update(&ctx, &mm, fx_id, TaskForUpdate::default()).await;
+        assert!(matches!(res_update, Err(Error::EntityNotFound { .. })));
+        let res_delete = TaskBmc::delete(&ctx, &mm, fx_id).await;
+        assert!(matches!(res_delete, Err(Error::EntityNotFound { .. })));
+
+        let tasks = TaskBmc::list(&ctx, &mm, fx_filters()?, None).await?;
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].title, "test_delete_restore_ok-task 02");
+        let page = TaskBmc::list_page(
+            &ctx,
+            &mm,
+            fx_filters()?,
+            None,
+            Some(PageOptions {
+                cursor: None,
+                with_total: Some(true),
+            }),
+        )
+        .await?;
+        assert_eq!(page.data.len(), 1);
+        assert_eq!(page.total, Some(1));
+
+        let deleted_page = TaskBmc::list_deleted(&ctx, &mm, fx_filters()?, None, None).await?;
+        assert_eq!(deleted_page.data.len(), 1);
+        assert_eq!(deleted_page.data[0].id, fx_id);
+        assert!(deleted_page.data[0].deleted_at.is_some());
+
+        // -- Exec & Check: restore
+        TaskBmc::restore(&ctx, &mm, fx_id).await?;
+
+        let task = TaskBmc::get(&ctx, &mm, fx_id).await?;
+        assert!(task.deleted_at.is_none());
+        // The delete and the restore are new versions (the pre-delete version is stale)
+        assert_eq!(task.version, fx_tasks[0].version + 2);
+        let res_update_stale = TaskBmc::update(
+            &ctx,
+            &mm,
+            fx_id,
+            TaskForUpdate {
+                version: Some(fx_tasks[0].version),
+                ..Default::default()
+            },
+        )
+        .await;
+        assert!(matches!(
+            res_update_stale,
+            Err(Error::VersionConflict { .. })
+        ));
+        let deleted_page = TaskBmc::list_deleted(&ctx, &mm, fx_filters()?, None, None).await?;
+        assert!(deleted_page.data.is_empty());
+        let res_restore = TaskBmc::restore(&ctx, &mm, fx_id).await;
+        assert!(matches!(res_restore, Err(Error::EntityNotFound { .. })));
+
+        // -- Clean
+        ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_purge_deleted_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::new(1000)?;
+        let fx_titles = &[
+            "test_purge_deleted_ok-task 01",
+            "test_purge_deleted_ok-task 02",
+        ];
+        let fx_project_id =
+            _dev_utils::seed_project(&ctx, &mm, "test_purge_deleted_ok project").await?;
+        let fx_tasks = _dev_utils::seed_tasks(&ctx, &mm, fx_project_id, fx_titles).await?;
+        TaskBmc::delete(&ctx, &mm, fx_tasks[0].id).await?;
+
+        // -- Exec
+        // Within the retention, nothing is purged
+        let purged_ids_kept = TaskBmc::purge_deleted(&ctx, &mm, Duration::hours(1)).await?;
+        let res_restore_kept = TaskBmc::restore(&ctx, &mm, fx_tasks[0].id).await;
+        TaskBmc::delete(&ctx, &mm, fx_tasks[0].id).await?;
+        // Past the retention
+        let purged_ids = TaskBmc::purge_deleted(&ctx, &mm, Duration::seconds(-1)).await?;
+
+        // -- Check
+        assert!(purged_ids_kept.is_empty());
+        assert!(res_restore_kept.is_ok());
+        assert_eq!(purged_ids, vec![fx_tasks[0].id]);
+        let res_restore = TaskBmc::restore(&ctx, &mm, fx_tasks[0].id).await;
+        assert!(matches!(res_restore, Err(Error::EntityNotFound { .. })));
+        // The task not deleted is kept
+        TaskBmc::get(&ctx, &mm, fx_tasks[1].id).await?;
+        // The activities of the purged task are kept (only readable by root, without the task)
+        let filters: Vec<TaskActivityFilter> =
+            serde_json::from_value(json!([{"task_id": fx_tasks[0].id}]))?;
+        let activities = TaskActivityBmc::list(&Ctx::root_ctx(), &mm, Some(filters), None).await?;
+        assert!(!activities.is_empty());
+
+        // -- Clean
+        ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_details_filter_sort_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::new(1000)?;
+        let fx_project_id =
+            _dev_utils::seed_project(&ctx, &mm, "test_details_filter_sort_ok project").await?;
+        let fx_due_at = now_utc() + Duration::days(7);
+        // demo1 (see dev seed)
+        let fx_assignee_id = 1000;
+        let fx_task_c = |title: &str, priority: i32, status: TaskStatus| TaskForCreate {
+            project_id: fx_project_id,
+            title: title.to_string(),
+            description: Some(format!("{title} description")),
+            due_at: Some(fx_due_at),
+            priority: Some(priority),
+            status: Some(status),
+            assignee_id: Some(fx_assignee_id),
+        };
+        let fx_tasks_c = vec![
+            fx_task_c("task 01", 1, TaskStatus::Todo),
+            fx_task_c("task 02", 4, TaskStatus::InProgress),
+            fx_task_c("task 03", 2, TaskStatus::Done),
+        ];
+
+        // -- Exec
+        let tasks = TaskBmc::create_many(&ctx, &mm, fx_tasks_c).await?;
+        let filters: Vec<TaskFilter> = serde_json::from_value(json!([
+            {
+                "project_id": fx_project_id,
+                "status": {"$in": ["todo", "in_progress"]},
+                "assignee_id": fx_assignee_id,
+            }
+        ]))?;
+        let list_options: ListOptions = serde_json::from_value(json!({
+            "order_bys": "!priority"
+        }))?;
+        let tasks_open = TaskBmc::list(&ctx, &mm, Some(filters), Some(list_options)).await?;
+
+        // -- Check
+        let task = &tasks[2];
+        assert_eq!(task.description.as_deref(), Some("task 03 description"));
+        assert_eq!(
+            task.due_at.map(|t| t.unix_timestamp()),
+            Some(fx_due_at.unix_timestamp())
+        );
+        assert_eq!(task.priority, 2);
+        assert_eq!(task.status, TaskStatus::Done);
+        assert!(task.done, "done should be derived from the status");
+        assert_eq!(task.assignee_id, Some(fx_assignee_id));
+        let titles: Vec<&str> = tasks_open.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(titles, &["task 02", "task 01"]);
+
+        // -- Clean
+        ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_create_update_err_details_invalid() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::new(1000)?;
+        let fx_project_id =
+            _dev_utils::seed_project(&ctx, &mm, "test_create_update_err_details_invalid project")
+                .await?;
+        let fx_task = _dev_utils::seed_tasks(&ctx, &mm, fx_project_id, &["task 01"])
+            .await?
+            .remove(0);
+
+        // -- Exec
+        let res_create = TaskBmc::create(
+            &ctx,
+            &mm,
+            TaskForCreate {
+                project_id: fx_project_id,
+                title: "task 02".to_string(),
+                priority: Some(5),
+                ..Default::default()
+            },
+        )
+        .await;
+        let res_update = TaskBmc::update(
+            &ctx,
+            &mm,
+            fx_task.id,
+            TaskForUpdate {
+                assignee_id: Some(Some(999_999)),
+                ..Default::default()
+            },
+        )
+        .await;
+        let res_update_done = TaskBmc::update(
+            &ctx,
+            &mm,
+            fx_task.id,
+            TaskForUpdate {
+                status: Some(TaskStatus::Todo),
+                done: Some(true),
+                ..Default::default()
+            },
+        )
+        .await;
+
+        // -- Check
+        assert!(
+            matches!(
+                res_create,
+                Err(Error::TaskPriorityOutOfRange { max: 4, actual: 5 })
+            ),
+            "TaskPriorityOutOfRange not matching"
+        );
+        assert!(
+            matches!(
+                res_update,
+                Err(Error::EntityNotFound {
+                    entity: "user",
+                    id: 999_999
+                })
+            ),
+            "EntityNotFound not matching for the assignee"
+        );
+        assert!(
+            matches!(
+                res_update_done,
+                Err(Error::TaskDoneStatusConflict {
+                    done: true,
+                    status: TaskStatus::Todo
+                })
+            ),
+            "TaskDoneStatusConflict not matching"
+        );
+
+        // -- Clean
+        ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_labels_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::new(1000)?;
+        let fx_project_id = _dev_utils::seed_project(&ctx, &mm, "test_labels_ok project").await?;
+        let fx_other_project_id =
+            _dev_utils::seed_project(&ctx, &mm, "test_labels_ok other project").await?;
+        let fx_tasks =
+            _dev_utils::seed_tasks(&ctx, &mm, fx_project_id, &["task 01", "task 02"]).await?;
+        let fx_label = |project_id: i64, name: &str| LabelForCreate {
+            project_id,
+            name: name.to_string(),
+        };
+        let label_bug_id = LabelBmc::create(&ctx, &mm, fx_label(fx_project_id, "bug")).await?;
+        let label_ui_id = LabelBmc::create(&ctx, &mm, fx_label(fx_project_id, "ui")).await?;
+        let label_other_id =
+            LabelBmc::create(&ctx, &mm, fx_label(fx_other_project_id, "bug")).await?;
+
+        // -- Exec
+        TaskBmc::add_label(&ctx, &mm, fx_tasks[0].id, label_ui_id).await?;
+        TaskBmc::add_label(&ctx, &mm, fx_tasks[0].id, label_bug_id).await?;
+        // Adding it twice is a no-op
+        TaskBmc::add_label(&ctx, &mm, fx_tasks[0].id, label_bug_id).await?;
+        let res_other = TaskBmc::add_label(&ctx, &mm, fx_tasks[1].id, label_other_id).await;
+        let labels = TaskBmc::list_labels(&ctx, &mm, fx_tasks[0].id).await?;
+        let list_by_label = |filter: serde_json::Value| {
+            let filters: Vec<TaskFilter> = serde_json::from_value(json!([
+                {"project_id": fx_project_id, "label_id": filter}
+            ]))
+            .unwrap();
+            TaskBmc::list(&ctx, &mm, Some(filters), None)
+        };
+        let tasks_bug = list_by_label(json!(label_bug_id)).await?;
+        let tasks_unlabeled = list_by_label(json!({"$null": true})).await?;
+        TaskBmc::remove_label(&ctx, &mm, fx_tasks[0].id, label_bug_id).await?;
+        let labels_removed = TaskBmc::list_labels(&ctx, &mm, fx_tasks[0].id).await?;
+
+        // -- Check
+        let names: Vec<&str> = labels.iter().map(|l| l.name.as_str()).collect();
+        assert_eq!(names, &["bug", "ui"]);
+        assert!(
+            matches!(res_other, Err(Error::LabelNotInTaskProject { .. })),
+            "LabelNotInTaskProject not matching"
+        );
+        assert_eq!(tasks_bug.len(), 1);
+        assert_eq!(tasks_bug[0].id, fx_tasks[0].id);
+        assert_eq!(tasks_unlabeled.len(), 1);
+        assert_eq!(tasks_unlabeled[0].id, fx_tasks[1].id);
+        assert_eq!(labels_removed.len(), 1);
+        assert_eq!(labels_removed[0].id, label_ui_id);
+
+        // -- Clean
+        ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+        ProjectBmc::delete(&ctx, &mm, fx_other_project_id).await?;
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_labels_err_name() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::new(1000)?;
+        let fx_project_id =
+            _dev_utils::seed_project(&ctx, &mm, "test_labels_err_name project").await?;
+        let fx_label = |name: &str| LabelForCreate {
+            project_id: fx_project_id,
+            name: name.to_string(),
+        };
+        LabelBmc::create(&ctx, &mm, fx_label("bug")).await?;
+        let label_ui_id = LabelBmc::create(&ctx, &mm, fx_label("ui")).await?;
+
+        // -- Exec
+        let res_create_dup = LabelBmc::create(&ctx, &mm, fx_label("bug")).await;
+        let res_create_long = LabelBmc::create(&ctx, &mm, fx_label(&"a".repeat(65))).await;
+        let res_update_dup = LabelBmc::update(
+            &ctx,
+            &mm,
+            label_ui_id,
+            LabelForUpdate {
+                name: Some("bug".to_string()),
+            },
+        )
+        .await;
+        let res_update_empty = LabelBmc::update(
+            &ctx,
+            &mm,
+            label_ui_id,
+            LabelForUpdate {
+                name: Some(" ".to_string()),
+            },
+        )
+        .await;
+
+        // -- Check
+        assert!(
+            matches!(res_create_dup, Err(Error::LabelAlreadyExists { .. })),
+            "LabelAlreadyExists not matching on create"
+        );
+        assert!(
+            matches!(res_create_long, Err(Error::LabelNameInvalid { .. })),
+            "LabelNameInvalid not matching on create"
+        );
+        assert!(
+            matches!(res_update_dup, Err(Error::LabelAlreadyExists { .. })),
+            "LabelAlreadyExists not matching on update"
+        );
+        assert!(
+            matches!(res_update_empty, Err(Error::LabelNameInvalid { .. })),
+            "LabelNameInvalid not matching on update"
+        );
+
+        // -- Clean
+        ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_search_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::new(1000)?;
+        let fx_project_id = _dev_utils::seed_project(&ctx, &mm, "test_search_ok project").await?;
+        let fx_task_c = |title: &str, description: &str| TaskForCreate {
+            project_id: fx_project_id,
+            title: title.to_string(),
+            description: Some(description.to_string()),
+            ..Default::default()
+        };
+        let fx_tasks_c = vec![
+            fx_task_c(
+                "test_search_ok write notes",
+                "about the zeppelin deployment",
+            ),
+            fx_task_c("test_search_ok zeppelin deployment", "rollout plan"),
+            fx_task_c("test_search_ok other", "nothing to see"),
+            fx_task_c(
+                "test_search_ok <b>escaped</b>",
+                "<script>alert('x')</script> & escaped",
+            ),
+        ];
+        let tasks = TaskBmc::create_many(&ctx, &mm, fx_tasks_c).await?;
+
+        // -- Exec
+        let hits = TaskBmc::search(&ctx, &mm, "zeppelin deployments", None).await?;
+        let hits_excluded = TaskBmc::search(&ctx, &mm, "zeppelin -rollout", None).await?;
+        let hits_escaped = TaskBmc::search(&ctx, &mm, "escaped", None).await?;
+        let res_limit = TaskBmc::search(&ctx, &mm, "zeppelin", Some(1000)).await;
+        let res_limit_negative = TaskBmc::search(&ctx, &mm, "zeppelin", Some(-1)).await;
+
+        // -- Check
+        // The title match first (stemmed: deployments matches deployment)
+        let ids: Vec<i64> = hits.iter().map(|hit| hit.task.id).collect();
+        assert_eq!(ids, &[tasks[1].id, tasks[0].id]);
+        assert!(hits[0].rank > hits[1].rank);
+        assert_eq!(
+            hits[0].title_highlight,
+            "test_search_ok <mark>zeppelin</mark> <mark>deployment</mark>"
+        );
+        assert_eq!(
+            hits[1].description_highlight.as_deref(),
+            Some("about the <mark>zeppelin</mark> <mark>deployment</mark>")
+        );
+        assert_eq!(hits_excluded.len(), 1);
+        assert_eq!(hits_excluded[0].task.id, tasks[0].id);
+        // The task text is escaped, only the marks are tags
+        assert_eq!(
+            hits_escaped[0].title_highlight,
+            "test_search_ok &lt;b&gt;<mark>escaped</mark>&lt;/b&gt;"
+        );
+        assert_eq!(
+            hits_escaped[0].description_highlight.as_deref(),
+            Some("&lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt; &amp; <mark>escaped</mark>")
+        );
+        assert!(
+            matches!(res_limit, Err(Error::ListLimitOverMax { .. })),
+            "ListLimitOverMax not matching"
+        );
+        assert!(
+            matches!(
+                res_limit_negative,
+                Err(Error::ListLimitNotPositive { actual: -1 })
+            ),
+            "ListLimitNotPositive not matching"
+        );
+
+        // -- Clean
+        ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
 
         Ok(())
     }
@@ -0,0 +1,344 @@
+use crate::ctx::Ctx;
+use crate::model::psql::base::{CommonIden, DbBmc, TimestampIden};
+use crate::model::psql::modql_utils::time_to_sea_value;
+use crate::model::psql::task::{Task, TaskBmc};
+use crate::model::psql::Result;
+use crate::model::psql::{base, ModelManager};
+use lib_utils::time::{now_utc, Rfc3339};
+use modql::field::Fields;
+use modql::filter::{FilterNodes, ListOptions, OpValsInt64, OpValsString, OpValsValue};
+use schemars::JsonSchema;
+use sea_query::{Expr, Iden, IntoIden, PostgresQueryBuilder, Query, SimpleExpr};
+use sea_query_binder::SqlxBinder;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use serde_with::serde_as;
+use sqlx::FromRow;
+use time::OffsetDateTime;
+
+// region:    --- TaskActivity Types
+/// An entry of the task activity log (append only)
+#[serde_as]
+#[derive(Debug, Clone, Fields, FromRow, Serialize, JsonSchema)]
+pub struct TaskActivity {
+    pub id: i64,
+    pub task_id: i64,
+    pub kind: TaskActivityKind,
+    /// The changed fields, as `[{"field": "title", "before": "a", "after": "b"}]`
+    /// (`before` is null on create, only `deleted_at` changes on delete and restore)
+    pub changes: Value,
+
+    // -- Timestamps
+    // (cid is the user who made the change)
+    pub cid: i64,
+    #[serde_as(as = "Rfc3339")]
+    #[schemars(with = "String")]
+    pub ctime: OffsetDateTime,
+}
+
+/// The task write recorded by an activity (stored as its snake_case name)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum TaskActivityKind {
+    Create,
+    Update,
+    Delete,
+    Restore,
+}
+
+impl TaskActivityKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskActivityKind::Create => "create",
+            TaskActivityKind::Update => "update",
+            TaskActivityKind::Delete => "delete",
+            TaskActivityKind::Restore => "restore",
+        }
+    }
+}
+
+impl From<TaskActivityKind> for sea_query::Value {
+    fn from(kind: TaskActivityKind) -> Self {
+        kind.as_str().into()
+    }
+}
+
+impl sea_query::Nullable for TaskActivityKind {
+    fn null() -> sea_query::Value {
+        sea_query::Value::String(None)
+    }
+}
+
+/// The change of a task field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct TaskFieldChange {
+    pub field: String,
+    pub before: Value,
+    pub after: Value,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct TaskActivityFilter {
+    id: Option<OpValsInt64>,
+    task_id: Option<OpValsInt64>,
+    kind: Option<OpValsString>,
+    cid: Option<OpValsInt64>,
+    #[modql(to_sea_value_fn = "time_to_sea_value")]
+    ctime: Option<OpValsValue>,
+}
+
+#[derive(Iden)]
+enum TaskActivityIden {
+    TaskId,
+    Kind,
+    Changes,
+}
+
+/// The task fields not recorded in the changes
+/// (ids, audit and derived fields)
+const UNTRACKED_TASK_FIELDS: &[&str] = &[
+    "id",
+    "version",
+    "done",
+    "deleted_at",
+    "cid",
+    "ctime",
+    "mid",
+    "mtime",
+];
+// endregion: --- TaskActivity Types
+
+// region:    --- TaskActivityBmc
+pub struct TaskActivityBmc;
+
+impl DbBmc for TaskActivityBmc {
+    // Table name is constant
+    const TABLE: &'static str = "task_activity";
+
+    // An activity is owned by the owner of its task (not in the trash)
+    fn owner_cond(user_id: i64) -> Option<SimpleExpr> {
+        Some(
+            Expr::col(TaskActivityIden::TaskId).in_subquery(
+                Query::select()
+                    .column(CommonIden::Id)
+                    .from(TaskBmc::table_ref())
+                    .and_where_option(TaskBmc::owner_cond(user_id))
+                    .and_where_option(base::not_deleted_cond::<TaskBmc>())
+                    .to_owned(),
+            ),
+        )
+    }
+}
+
+impl TaskActivityBmc {
+    /// List the activities (ordered by `id` by default, i.e., the oldest first)
+    pub async fn list(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        filters: Option<Vec<TaskActivityFilter>>,
+        list_options: Option<ListOptions>,
+    ) -> Result<Vec<TaskActivity>> {
+        let mut list_options = list_options.unwrap_or_default();
+        if list_options.order_bys.is_none() {
+            list_options.order_bys = Some("id".into());
+        }
+
+        base::list::<Self, _, _>(ctx, mm, filters, Some(list_options)).await
+    }
+
+    /// Append an activity to the log of the task
+    /// (Called by the TaskBmc writes, in their transaction)
+    pub(in crate::model::psql) async fn record(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        task_id: i64,
+        kind: TaskActivityKind,
+        changes: Vec<TaskFieldChange>,
+    ) -> Result<()> {
+        let mut conn = mm.conn().await?;
+        let changes = serde_json::to_value(changes)?;
+
+        // Build the SQL query
+        let mut query = Query::insert();
+        query
+            .into_table(Self::table_ref())
+            .columns([
+                TaskActivityIden::TaskId.into_iden(),
+                TaskActivityIden::Kind.into_iden(),
+                TaskActivityIden::Changes.into_iden(),
+                TimestampIden::Cid.into_iden(),
+                TimestampIden::Ctime.into_iden(),
+            ])
+            .values([
+                task_id.into(),
+                kind.into(),
+                changes.into(),
+                ctx.user_id().into(),
+                now_utc().into(),
+            ])?;
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+
+        // Execute the query
+        sqlx::query_with(&sql, values).execute(&mut *conn).await?;
+
+        Ok(())
+    }
+}
+
+/// The field-level changes between two states of a task
+/// (`None` before for a created task: all the fields are changed from null)
+pub(in crate::model::psql) fn task_changes(
+    before: Option<&Task>,
+    after: &Task,
+) -> Result<Vec<TaskFieldChange>> {
+    let to_fields = |task: &Task| -> Result<serde_json::Map<String, Value>> {
+        match serde_json::to_value(task)? {
+            Value::Object(fields) => Ok(fields),
+            _ => Ok(serde_json::Map::new()),
+        }
+    };
+    let before = before.map(to_fields).transpose()?.unwrap_or_default();
+    let after = to_fields(after)?;
+
+    let changes = after
+        .into_iter()
+        .filter(|(field, _)| !UNTRACKED_TASK_FIELDS.contains(&field.as_str()))
+        .filter_map(|(field, after)| {
+            let before = before.get(&field).cloned().unwrap_or(Value::Null);
+            (before != after).then_some(TaskFieldChange {
+                field,
+                before,
+                after,
+            })
+        })
+        .collect();
+
+    Ok(changes)
+}
+
+/// The `deleted_at` change of a task delete or restore
+/// (`deleted_at` is not tracked by `task_changes`, the updates cannot change it)
+pub(in crate::model::psql) fn deleted_at_changes(
+    before: &Task,
+    after: &Task,
+) -> Result<Vec<TaskFieldChange>> {
+    let deleted_at = |task: &Task| -> Result<Value> {
+        Ok(serde_json::to_value(task)?
+            .get_mut("deleted_at")
+            .map(Value::take)
+            .unwrap_or_default())
+    };
+
+    Ok(vec![TaskFieldChange {
+        field: "deleted_at".to_string(),
+        before: deleted_at(before)?,
+        after: deleted_at(after)?,
+    }])
+}
+// endregion: --- TaskActivityBmc
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::_dev_utils;
+    use crate::model::psql::project::ProjectBmc;
+    use crate::model::psql::task::{TaskForUpdate, TaskStatus};
+    use anyhow::Result;
+    use serde_json::json;
+    use serial_test::serial;
+
+    #[serial]
+    #[tokio::test]
+    async fn test_record_on_task_writes_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::new(1000)?;
+        let fx_project_id =
+            _dev_utils::seed_project(&ctx, &mm, "test_record_on_task_writes_ok project").await?;
+        let fx_task = _dev_utils::seed_tasks(&ctx, &mm, fx_project_id, &["task 01"])
+            .await?
+            .remove(0);
+
+        // -- Exec
+        TaskBmc::update(
+            &ctx,
+            &mm,
+            fx_task.id,
+            TaskForUpdate {
+                title: Some("task 01 updated".to_string()),
+                status: Some(TaskStatus::InProgress),
+                ..Default::default()
+            },
+        )
+        .await?;
+        // Nothing changed, nothing recorded
+        TaskBmc::update(&ctx, &mm, fx_task.id, TaskForUpdate::default()).await?;
+        TaskBmc::delete(&ctx, &mm, fx_task.id).await?;
+        let fx_filters = || -> Result<Vec<TaskActivityFilter>> {
+            Ok(serde_json::from_value(json!([{"task_id": fx_task.id}]))?)
+        };
+        // The activities of a task in the trash are not listed
+        let activities_deleted =
+            TaskActivityBmc::list(&ctx, &mm, Some(fx_filters()?), None).await?;
+        TaskBmc::restore(&ctx, &mm, fx_task.id).await?;
+        let activities = TaskActivityBmc::list(&ctx, &mm, Some(fx_filters()?), None).await?;
+
+        // -- Check
+        assert!(activities_deleted.is_empty());
+        let kinds: Vec<TaskActivityKind> = activities.iter().map(|a| a.kind).collect();
+        assert_eq!(
+            kinds,
+            &[
+                TaskActivityKind::Create,
+                TaskActivityKind::Update,
+                TaskActivityKind::Delete,
+                TaskActivityKind::Restore,
+            ]
+        );
+        assert!(activities.iter().all(|a| a.cid == ctx.user_id()));
+        let created: Vec<TaskFieldChange> = serde_json::from_value(activities[0].changes.clone())?;
+        assert!(created.contains(&TaskFieldChange {
+            field: "title".to_string(),
+            before: json!(null),
+            after: json!("task 01"),
+        }));
+        let updated: Vec<TaskFieldChange> = serde_json::from_value(activities[1].changes.clone())?;
+        assert_eq!(
+            updated,
+            &[
+                TaskFieldChange {
+                    field: "status".to_string(),
+                    before: json!("todo"),
+                    after: json!("in_progress"),
+                },
+                TaskFieldChange {
+                    field: "title".to_string(),
+                    before: json!("task 01"),
+                    after: json!("task 01 updated"),
+                },
+            ]
+        );
+        let deleted: Vec<TaskFieldChange> = serde_json::from_value(activities[2].changes.clone())?;
+        let restored: Vec<TaskFieldChange> = serde_json::from_value(activities[3].changes.clone())?;
+        assert_eq!(deleted.len(), 1);
+        assert_eq!(deleted[0].field, "deleted_at");
+        assert_eq!(deleted[0].before, json!(null));
+        assert!(deleted[0].after.is_string());
+        assert_eq!(
+            restored,
+            &[TaskFieldChange {
+                field: "deleted_at".to_string(),
+                before: deleted[0].after.clone(),
+                after: json!(null),
+            }]
+        );
+
+        // -- Clean
+        ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
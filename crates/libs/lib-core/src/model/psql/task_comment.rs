@@ -0,0 +1,311 @@
+use crate::ctx::Ctx;
+use crate::model::psql::base::{CommonIden, DbBmc};
+use crate::model::psql::task::TaskBmc;
+use crate::model::psql::Result;
+use crate::model::psql::{base, ModelManager};
+use lib_utils::time::Rfc3339;
+use modql::field::Fields;
+use modql::filter::{FilterNodes, ListOptions, OpValsInt64, OpValsString};
+use schemars::JsonSchema;
+use sea_query::{Expr, Iden, Query, SimpleExpr};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use sqlx::FromRow;
+use time::OffsetDateTime;
+
+// region:    --- TaskComment Types
+#[serde_as]
+#[derive(Debug, Clone, Fields, FromRow, Serialize, JsonSchema)]
+pub struct TaskComment {
+    pub id: i64,
+    pub task_id: i64,
+    pub content: String,
+
+    // -- Timestamps
+    // (cid is the author of the comment)
+    pub cid: i64,
+    #[serde_as(as = "Rfc3339")]
+    #[schemars(with = "String")]
+    pub ctime: OffsetDateTime,
+    pub mid: i64,
+    #[serde_as(as = "Rfc3339")]
+    #[schemars(with = "String")]
+    pub mtime: OffsetDateTime,
+}
+
+#[derive(Fields, Deserialize, JsonSchema)]
+pub struct TaskCommentForCreate {
+    pub task_id: i64,
+    pub content: String,
+}
+
+#[derive(Fields, Default, Deserialize, JsonSchema)]
+pub struct TaskCommentForUpdate {
+    pub content: Option<String>,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct TaskCommentFilter {
+    id: Option<OpValsInt64>,
+    task_id: Option<OpValsInt64>,
+    content: Option<OpValsString>,
+    cid: Option<OpValsInt64>,
+}
+
+#[derive(Iden)]
+enum TaskCommentIden {
+    TaskId,
+}
+// endregion: --- TaskComment Types
+
+// region:    --- TaskCommentBmc
+pub struct TaskCommentBmc;
+
+impl DbBmc for TaskCommentBmc {
+    // Table name is constant
+    const TABLE: &'static str = "task_comment";
+
+    // A comment is owned by the owner of its task (not in the trash)
+    fn owner_cond(user_id: i64) -> Option<SimpleExpr> {
+        Some(
+            Expr::col(TaskCommentIden::TaskId).in_subquery(
+                Query::select()
+                    .column(CommonIden::Id)
+                    .from(TaskBmc::table_ref())
+                    .and_where_option(TaskBmc::owner_cond(user_id))
+                    .and_where_option(base::not_deleted_cond::<TaskBmc>())
+                    .to_owned(),
+            ),
+        )
+    }
+}
+
+impl TaskCommentBmc {
+    pub async fn create(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        comment_c: TaskCommentForCreate,
+    ) -> Result<i64> {
+        // Check that the task is accessible by the ctx user (and not in the trash)
+        TaskBmc::get(ctx, mm, comment_c.task_id).await?;
+
+        base::create::<Self, _>(ctx, mm, comment_c).await
+    }
+
+    pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<TaskComment> {
+        base::get::<Self, _>(ctx, mm, id).await
+    }
+
+    pub async fn list(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        filters: Option<Vec<TaskCommentFilter>>,
+        list_options: Option<ListOptions>,
+    ) -> Result<Vec<TaskComment>> {
+        base::list::<Self, _, _>(ctx, mm, filters, list_options).await
+    }
+
+    pub async fn update(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i64,
+        comment_u: TaskCommentForUpdate,
+    ) -> Result<()> {
+        base::update::<Self, _>(ctx, mm, id, comment_u).await
+    }
+
+    pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+        base::delete::<Self>(ctx, mm, id).await
+    }
+}
+// endregion: --- TaskCommentBmc
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::_dev_utils;
+    use crate::model::psql::project::ProjectBmc;
+    use crate::model::psql::Error;
+    use anyhow::Result;
+    use modql::filter::OrderBys;
+    use serde_json::json;
+    use serial_test::serial;
+
+    #[serial]
+    #[tokio::test]
+    async fn test_create_update_list_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::new(1000)?;
+        let fx_project_id =
+            _dev_utils::seed_project(&ctx, &mm, "test_create_update_list_ok project").await?;
+        let fx_task = _dev_utils::seed_tasks(&ctx, &mm, fx_project_id, &["task 01"])
+            .await?
+            .remove(0);
+        let fx_comment_c = |content: &str| TaskCommentForCreate {
+            task_id: fx_task.id,
+            content: content.to_string(),
+        };
+
+        // -- Exec
+        let id_01 = TaskCommentBmc::create(&ctx, &mm, fx_comment_c("comment 01")).await?;
+        TaskCommentBmc::create(&ctx, &mm, fx_comment_c("comment 02")).await?;
+        TaskCommentBmc::update(
+            &ctx,
+            &mm,
+            id_01,
+            TaskCommentForUpdate {
+                content: Some("comment 01 edited".to_string()),
+            },
+        )
+        .await?;
+        let filters: Vec<TaskCommentFilter> =
+            serde_json::from_value(json!([{"task_id": fx_task.id}]))?;
+        let comments =
+            TaskCommentBmc::list(&ctx, &mm, Some(filters), OrderBys::from("id").into()).await?;
+
+        // -- Check
+        let contents: Vec<&str> = comments.iter().map(|c| c.content.as_str()).collect();
+        assert_eq!(contents, &["comment 01 edited", "comment 02"]);
+        assert_eq!(comments[0].cid, ctx.user_id());
+
+        // -- Clean
+        ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_access_err_not_task_owner() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx_owner = Ctx::new(1000)?;
+        let ctx_other = Ctx::new(1001)?;
+        let fx_project_id =
+            _dev_utils::seed_project(&ctx_owner, &mm, "test_access_err_not_task_owner project")
+                .await?;
+        let fx_task = _dev_utils::seed_tasks(&ctx_owner, &mm, fx_project_id, &["task 01"])
+            .await?
+            .remove(0);
+        let fx_id = TaskCommentBmc::create(
+            &ctx_owner,
+            &mm,
+            TaskCommentForCreate {
+                task_id: fx_task.id,
+                content: "comment 01".to_string(),
+            },
+        )
+        .await?;
+
+        // -- Exec
+        let res_create = TaskCommentBmc::create(
+            &ctx_other,
+            &mm,
+            TaskCommentForCreate {
+                task_id: fx_task.id,
+                content: "comment other".to_string(),
+            },
+        )
+        .await;
+        let res_get = TaskCommentBmc::get(&ctx_other, &mm, fx_id).await;
+        let res_delete = TaskCommentBmc::delete(&ctx_other, &mm, fx_id).await;
+
+        // -- Check
+        assert!(
+            matches!(
+                res_create,
+                Err(Error::EntityNotFound { entity: "task", .. })
+            ),
+            "EntityNotFound not matching for create"
+        );
+        for res in [res_get.map(|_| ()), res_delete] {
+            assert!(
+                matches!(
+                    res,
+                    Err(Error::EntityNotFound {
+                        entity: "task_comment",
+                        ..
+                    })
+                ),
+                "EntityNotFound not matching"
+            );
+        }
+
+        // -- Clean
+        ProjectBmc::delete(&ctx_owner, &mm, fx_project_id).await?;
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_access_err_task_deleted() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::new(1000)?;
+        let fx_project_id =
+            _dev_utils::seed_project(&ctx, &mm, "test_access_err_task_deleted project").await?;
+        let fx_task = _dev_utils::seed_tasks(&ctx, &mm, fx_project_id, &["task 01"])
+            .await?
+            .remove(0);
+        let fx_comment_c = |content: &str| TaskCommentForCreate {
+            task_id: fx_task.id,
+            content: content.to_string(),
+        };
+        let fx_id = TaskCommentBmc::create(&ctx, &mm, fx_comment_c("comment 01")).await?;
+        let fx_filters = || -> Result<Vec<TaskCommentFilter>> {
+            Ok(serde_json::from_value(json!([{"task_id": fx_task.id}]))?)
+        };
+        TaskBmc::delete(&ctx, &mm, fx_task.id).await?;
+
+        // -- Exec
+        let res_create = TaskCommentBmc::create(&ctx, &mm, fx_comment_c("comment 02")).await;
+        let res_get = TaskCommentBmc::get(&ctx, &mm, fx_id).await;
+        let res_update = TaskCommentBmc::update(
+            &ctx,
+            &mm,
+            fx_id,
+            TaskCommentForUpdate {
+                content: Some("comment 01 edited".to_string()),
+            },
+        )
+        .await;
+        let res_delete = TaskCommentBmc::delete(&ctx, &mm, fx_id).await;
+        let comments = TaskCommentBmc::list(&ctx, &mm, Some(fx_filters()?), None).await?;
+        // The comments are back with the restored task
+        TaskBmc::restore(&ctx, &mm, fx_task.id).await?;
+        let comments_restored = TaskCommentBmc::list(&ctx, &mm, Some(fx_filters()?), None).await?;
+
+        // -- Check
+        assert!(
+            matches!(
+                res_create,
+                Err(Error::EntityNotFound { entity: "task", .. })
+            ),
+            "EntityNotFound not matching for create"
+        );
+        for res in [res_get.map(|_| ()), res_update, res_delete] {
+            assert!(
+                matches!(
+                    res,
+                    Err(Error::EntityNotFound {
+                        entity: "task_comment",
+                        ..
+                    })
+                ),
+                "EntityNotFound not matching"
+            );
+        }
+        assert!(comments.is_empty());
+        assert_eq!(comments_restored.len(), 1);
+        assert_eq!(comments_restored[0].content, "comment 01");
+
+        // -- Clean
+        ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
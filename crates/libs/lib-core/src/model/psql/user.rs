@@ -1,33 +1,54 @@
 use crate::ctx::Ctx;
-use crate::model::psql::base::{self, DbBmc};
-use crate::model::psql::{ModelManager, Result};
+use crate::model::psql::base::{self, prep_fields_for_update, BatchTxn, DbBmc};
+use crate::model::psql::role::{RoleBmc, DEFAULT_ROLE};
+use crate::model::psql::user_session::UserSessionBmc;
+use crate::model::psql::{Error, ModelManager, Result};
 use lib_auth::pwd::{self, ContentToHash};
-use modql::field::{Fields, HasFields};
-use sea_query::{Expr, Iden, PostgresQueryBuilder, SimpleExpr};
+use lib_utils::time::Rfc3339;
+use modql::field::{Field, Fields, HasFields};
+use schemars::JsonSchema;
+use sea_query::{Expr, Iden, PostgresQueryBuilder};
 use sea_query_binder::SqlxBinder;
 use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
 use sqlx::postgres::PgRow;
 use sqlx::FromRow;
+use time::OffsetDateTime;
 use uuid::Uuid;
 
 /// User model
 /// (FromRow trait is used to convert the result from the database to the struct)
 /// (Fields trait is used to get the fields of the struct. It is used in the orm libraries)
-#[derive(Debug, Clone, Fields, FromRow, Serialize)]
+#[serde_as]
+#[derive(Debug, Clone, Fields, FromRow, Serialize, JsonSchema)]
 pub struct User {
     pub id: i64,
     pub username: String,
+
+    // -- Timestamps
+    pub cid: i64,
+    #[serde_as(as = "Rfc3339")]
+    #[schemars(with = "String")]
+    pub ctime: OffsetDateTime,
+    pub mid: i64,
+    #[serde_as(as = "Rfc3339")]
+    #[schemars(with = "String")]
+    pub mtime: OffsetDateTime,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, JsonSchema)]
 pub struct UserForCreate {
     pub username: String,
     pub password_clear: String,
 }
 
+/// User data inserted in the db
+/// (the password is hashed with a salt generated before the insert)
 #[derive(Fields)]
 pub struct UserForInsert {
     username: String,
+    password: String,
+    password_salt: Uuid,
 }
 
 #[derive(Clone, FromRow, Fields, Debug)]
@@ -63,6 +84,7 @@ enum UserIden {
     Id,
     Username,
     Password,
+    TokenSalt,
 }
 
 pub struct UserBmc;
@@ -72,7 +94,50 @@ impl DbBmc for UserBmc {
     const TABLE: &'static str = "user";
 }
 
+/// Username length bounds (in characters)
+const USERNAME_MIN_LEN: usize = 3;
+const USERNAME_MAX_LEN: usize = 32;
+
 impl UserBmc {
+    /// Create a user with a password (sign up) and the default role
+    /// (the username and the password strength are validated first)
+    pub async fn create(ctx: &Ctx, mm: &ModelManager, user_c: UserForCreate) -> Result<i64> {
+        let UserForCreate {
+            username,
+            password_clear,
+        } = user_c;
+
+        validate_username(&username)?;
+        pwd::check_pwd_strength(&password_clear)?;
+
+        let password_salt = Uuid::new_v4();
+        let password = pwd::hash_pwd(ContentToHash {
+            content: password_clear,
+            salt: password_salt,
+        })
+        .await?;
+
+        let user_i = UserForInsert {
+            username: username.clone(),
+            password,
+            password_salt,
+        };
+
+        let id = base::create::<Self, _>(ctx, mm, user_i)
+            .await
+            .map_err(|ex| match ex {
+                Error::Sqlx(sqlx::Error::Database(db_err)) if db_err.is_unique_violation() => {
+                    Error::UserAlreadyExists { username }
+                }
+                ex => ex,
+            })?;
+
+        // New users get the default role
+        RoleBmc::assign(ctx, mm, id, DEFAULT_ROLE).await?;
+
+        Ok(id)
+    }
+
     pub async fn get<E>(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<E>
     where
         E: UserBy,
@@ -89,8 +154,6 @@ impl UserBmc {
     where
         E: UserBy,
     {
-        let db = mm.db();
-
         // Build the SQL query
         let mut query = sea_query::Query::select();
         query
@@ -99,17 +162,71 @@ impl UserBmc {
             .and_where(Expr::col(UserIden::Username).eq(username));
 
         // Execute the query
+        let mut conn = mm.conn().await?;
         let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
         let user = sqlx::query_as_with::<_, E, _>(&sql, values)
-            .fetch_optional(db)
+            .fetch_optional(&mut *conn)
             .await?;
 
         Ok(user)
     }
 
-    pub async fn update_pwd(ctx: &Ctx, mm: &ModelManager, id: i64, pwd_clear: &str) -> Result<()> {
-        let db = mm.db();
+    /// Check if the username is valid and not used by another user
+    pub async fn is_username_available(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        username: &str,
+    ) -> Result<bool> {
+        if validate_username(username).is_err() {
+            return Ok(false);
+        }
 
+        let user: Option<User> = Self::first_by_username(ctx, mm, username).await?;
+
+        Ok(user.is_none())
+    }
+
+    /// Check the clear password against the stored one
+    pub async fn check_pwd(ctx: &Ctx, mm: &ModelManager, id: i64, pwd_clear: &str) -> Result<()> {
+        let user: UserForLogin = Self::get(ctx, mm, id).await?;
+        let Some(pwd_ref) = user.password else {
+            return Err(Error::UserPwdNotMatching { user_id: id });
+        };
+
+        pwd::validate_pwd(
+            ContentToHash {
+                content: pwd_clear.to_string(),
+                salt: user.password_salt,
+            },
+            pwd_ref,
+        )
+        .await
+        .map_err(|_| Error::UserPwdNotMatching { user_id: id })?;
+
+        Ok(())
+    }
+
+    /// Change the password after checking the old one and the new password strength.
+    /// All the sessions of the user are revoked and its token salt rotated (in one transaction),
+    /// so the tokens issued before the change (possibly stolen) are not valid anymore.
+    pub async fn change_pwd(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i64,
+        pwd_old: &str,
+        pwd_new: &str,
+    ) -> Result<()> {
+        Self::check_pwd(ctx, mm, id, pwd_old).await?;
+        pwd::check_pwd_strength(pwd_new)?;
+
+        let txn = BatchTxn::begin(mm).await?;
+        Self::update_pwd(ctx, &txn.mm, id, pwd_new).await?;
+        Self::rotate_token_salt(ctx, &txn.mm, id).await?;
+        UserSessionBmc::revoke_all_for_user(ctx, &txn.mm, id, "pwd_changed").await?;
+        txn.commit().await
+    }
+
+    pub async fn update_pwd(ctx: &Ctx, mm: &ModelManager, id: i64, pwd_clear: &str) -> Result<()> {
         let user: UserForLogin = Self::get(ctx, mm, id).await?;
         let pwd_enc = pwd::hash_pwd(ContentToHash {
             content: pwd_clear.to_string(),
@@ -117,29 +234,82 @@ impl UserBmc {
         })
         .await?;
 
+        // Prepare the fields and values
+        let mut fields = Fields::new(vec![Field::new(UserIden::Password, pwd_enc.into())]);
+        prep_fields_for_update(&mut fields, ctx);
+
         // Build the SQL query
         let mut query = sea_query::Query::update();
         query
             .table(Self::table_ref())
-            .value(UserIden::Password, SimpleExpr::from(pwd_enc))
+            .values(fields.for_sea_update())
             .and_where(Expr::col(UserIden::Id).eq(id));
 
         // Execute the query
+        let mut conn = mm.conn().await?;
         let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
         let _count = sqlx::query_with(&sql, values)
-            .execute(db)
+            .execute(&mut *conn)
             .await?
             .rows_affected();
 
         Ok(())
     }
+
+    /// Replace the user token salt, invalidating all the tokens signed with the previous one
+    pub async fn rotate_token_salt(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+        // Prepare the fields and values
+        let mut fields = Fields::new(vec![Field::new(UserIden::TokenSalt, Uuid::new_v4().into())]);
+        prep_fields_for_update(&mut fields, ctx);
+
+        // Build the SQL query
+        let mut query = sea_query::Query::update();
+        query
+            .table(Self::table_ref())
+            .values(fields.for_sea_update())
+            .and_where(Expr::col(UserIden::Id).eq(id));
+
+        // Execute the query
+        let mut conn = mm.conn().await?;
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+        sqlx::query_with(&sql, values).execute(&mut *conn).await?;
+
+        Ok(())
+    }
+
+    /// Delete the user with its projects (and their tasks) and sessions
+    pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+        // Delete the user (its sessions and owned projects are deleted by cascade)
+        base::delete::<Self>(ctx, mm, id).await
+    }
+}
+
+/// Validate the username format
+/// (USERNAME_MIN_LEN to USERNAME_MAX_LEN ascii alphanumeric, `_`, `-` or `.` characters)
+fn validate_username(username: &str) -> Result<()> {
+    let len = username.chars().count();
+    let valid = (USERNAME_MIN_LEN..=USERNAME_MAX_LEN).contains(&len)
+        && username
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+
+    if !valid {
+        return Err(Error::UsernameInvalid {
+            username: username.to_string(),
+        });
+    }
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::_dev_utils;
+    use crate::model::psql::project::ProjectBmc;
+    use crate::model::psql::user_session::UserSessionForCreate;
     use anyhow::{Context, Result};
+    use lib_utils::time::now_utc_plus_sec;
     use serial_test::serial;
 
     #[serial]
@@ -157,4 +327,191 @@ mod tests {
 
         Ok(())
     }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_rotate_token_salt_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+        let fx_user: UserForAuth = UserBmc::first_by_username(&ctx, &mm, "demo1")
+            .await?
+            .context("Should have user demo1")?;
+
+        // -- Exec
+        UserBmc::rotate_token_salt(&ctx, &mm, fx_user.id).await?;
+
+        // -- Check
+        let user: UserForAuth = UserBmc::get(&ctx, &mm, fx_user.id).await?;
+        assert_ne!(user.token_salt, fx_user.token_salt);
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_create_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+        let fx_username = "test_create_ok-user";
+        let fx_pwd = "welcome2rust";
+
+        // -- Exec
+        let id = UserBmc::create(
+            &ctx,
+            &mm,
+            UserForCreate {
+                username: fx_username.to_string(),
+                password_clear: fx_pwd.to_string(),
+            },
+        )
+        .await?;
+
+        // -- Check
+        let user: User = UserBmc::get(&ctx, &mm, id).await?;
+        assert_eq!(user.username, fx_username);
+        UserBmc::check_pwd(&ctx, &mm, id, fx_pwd).await?;
+        assert!(!UserBmc::is_username_available(&ctx, &mm, fx_username).await?);
+
+        // -- Clean
+        UserBmc::delete(&ctx, &mm, id).await?;
+        assert!(UserBmc::is_username_available(&ctx, &mm, fx_username).await?);
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_create_err_invalid() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+
+        // -- Exec
+        let res_exists = UserBmc::create(
+            &ctx,
+            &mm,
+            UserForCreate {
+                username: "demo1".to_string(),
+                password_clear: "welcome2rust".to_string(),
+            },
+        )
+        .await;
+        let res_username = UserBmc::create(
+            &ctx,
+            &mm,
+            UserForCreate {
+                username: "no spaces allowed".to_string(),
+                password_clear: "welcome2rust".to_string(),
+            },
+        )
+        .await;
+        let res_pwd = UserBmc::create(
+            &ctx,
+            &mm,
+            UserForCreate {
+                username: "test_create_err_invalid".to_string(),
+                password_clear: "short".to_string(),
+            },
+        )
+        .await;
+
+        // -- Check
+        assert!(
+            matches!(res_exists, Err(Error::UserAlreadyExists { .. })),
+            "UserAlreadyExists not matching"
+        );
+        assert!(
+            matches!(res_username, Err(Error::UsernameInvalid { .. })),
+            "UsernameInvalid not matching"
+        );
+        assert!(
+            matches!(res_pwd, Err(Error::Pwd(pwd::Error::PwdTooShort { .. }))),
+            "PwdTooShort not matching"
+        );
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_change_pwd_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+        let fx_pwd_old = "welcome2rust";
+        let fx_pwd_new = "welcome2axum";
+        let id = UserBmc::create(
+            &ctx,
+            &mm,
+            UserForCreate {
+                username: "test_change_pwd_ok-user".to_string(),
+                password_clear: fx_pwd_old.to_string(),
+            },
+        )
+        .await?;
+        let fx_user: UserForAuth = UserBmc::get(&ctx, &mm, id).await?;
+        let fx_session_id = UserSessionBmc::create(
+            &ctx,
+            &mm,
+            UserSessionForCreate {
+                user_id: id,
+                expires_at: now_utc_plus_sec(3600),
+            },
+        )
+        .await?;
+
+        // -- Exec
+        let res_wrong_old = UserBmc::change_pwd(&ctx, &mm, id, "wrong2pwd", fx_pwd_new).await;
+        UserBmc::change_pwd(&ctx, &mm, id, fx_pwd_old, fx_pwd_new).await?;
+
+        // -- Check
+        assert!(
+            matches!(res_wrong_old, Err(Error::UserPwdNotMatching { .. })),
+            "UserPwdNotMatching not matching"
+        );
+        UserBmc::check_pwd(&ctx, &mm, id, fx_pwd_new).await?;
+        assert!(UserBmc::check_pwd(&ctx, &mm, id, fx_pwd_old).await.is_err());
+        let user: UserForAuth = UserBmc::get(&ctx, &mm, id).await?;
+        assert_ne!(user.token_salt, fx_user.token_salt);
+        let session = UserSessionBmc::get(&ctx, &mm, fx_session_id).await?;
+        assert!(session.revoked_at.is_some(), "session should be revoked");
+        assert_eq!(session.revoke_reason.as_deref(), Some("pwd_changed"));
+
+        // -- Clean
+        UserBmc::delete(&ctx, &mm, id).await?;
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_delete_ok_with_projects() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let root_ctx = Ctx::root_ctx();
+        let id = UserBmc::create(
+            &root_ctx,
+            &mm,
+            UserForCreate {
+                username: "test_delete_ok-user".to_string(),
+                password_clear: "welcome2rust".to_string(),
+            },
+        )
+        .await?;
+        let ctx = Ctx::new(id)?;
+        let fx_project_id = _dev_utils::seed_project(&ctx, &mm, "test_delete_ok project").await?;
+
+        // -- Exec
+        UserBmc::delete(&ctx, &mm, id).await?;
+
+        // -- Check
+        let res_user: Result<User, _> = UserBmc::get(&root_ctx, &mm, id).await;
+        assert!(matches!(res_user, Err(Error::EntityNotFound { .. })));
+        let res_project = ProjectBmc::get(&root_ctx, &mm, fx_project_id).await;
+        assert!(matches!(res_project, Err(Error::EntityNotFound { .. })));
+
+        Ok(())
+    }
 }
@@ -0,0 +1,386 @@
+//! User sessions, one per login (i.e., per refresh token family).
+//! The refresh token carries the session `uuid` and `generation`.
+//! The generation is incremented on each rotation, so presenting an older one means the token was reused.
+
+use crate::ctx::Ctx;
+use crate::model::psql::base::{self, owner_cond, prep_fields_for_update, DbBmc};
+use crate::model::psql::modql_utils::time_to_sea_value;
+use crate::model::psql::{ModelManager, Result};
+use lib_utils::time::{now_utc, Rfc3339};
+use modql::field::{Field, Fields, HasFields};
+use modql::filter::{FilterNodes, ListOptions, OpValsInt64, OpValsValue};
+use schemars::JsonSchema;
+use sea_query::{Expr, Iden, PostgresQueryBuilder, Query, SimpleExpr};
+use sea_query_binder::SqlxBinder;
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use sqlx::FromRow;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+// region:    --- UserSession Types
+#[serde_as]
+#[derive(Debug, Clone, Fields, FromRow, Serialize, JsonSchema)]
+pub struct UserSession {
+    pub id: i64,
+    pub uuid: Uuid,
+    pub user_id: i64,
+    pub generation: i64,
+    #[serde_as(as = "Rfc3339")]
+    #[schemars(with = "String")]
+    pub expires_at: OffsetDateTime,
+    // (no_default: serialize only, and the serde default conflicts with the JsonSchema derive)
+    #[serde_as(as = "Option<Rfc3339>", no_default)]
+    #[schemars(with = "Option<String>")]
+    pub revoked_at: Option<OffsetDateTime>,
+    pub revoke_reason: Option<String>,
+
+    // -- Timestamps
+    pub cid: i64,
+    #[serde_as(as = "Rfc3339")]
+    #[schemars(with = "String")]
+    pub ctime: OffsetDateTime,
+    pub mid: i64,
+    #[serde_as(as = "Rfc3339")]
+    #[schemars(with = "String")]
+    pub mtime: OffsetDateTime,
+}
+
+#[derive(Fields)]
+pub struct UserSessionForCreate {
+    pub user_id: i64,
+    pub expires_at: OffsetDateTime,
+}
+
+#[derive(Fields)]
+struct UserSessionForRevoke {
+    revoked_at: OffsetDateTime,
+    revoke_reason: String,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct UserSessionFilter {
+    id: Option<OpValsInt64>,
+    #[modql(to_sea_value_fn = "time_to_sea_value")]
+    expires_at: Option<OpValsValue>,
+    #[modql(to_sea_value_fn = "time_to_sea_value")]
+    revoked_at: Option<OpValsValue>,
+}
+
+#[derive(Iden)]
+enum UserSessionIden {
+    Id,
+    Uuid,
+    UserId,
+    Generation,
+    ExpiresAt,
+    RevokedAt,
+    RevokeReason,
+}
+// endregion: --- UserSession Types
+
+// region:    --- UserSessionBmc
+pub struct UserSessionBmc;
+
+impl DbBmc for UserSessionBmc {
+    const TABLE: &'static str = "user_session";
+
+    // A session is owned by its user
+    fn owner_cond(user_id: i64) -> Option<SimpleExpr> {
+        Some(Expr::col(UserSessionIden::UserId).eq(user_id))
+    }
+}
+
+impl UserSessionBmc {
+    pub async fn create(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        session_c: UserSessionForCreate,
+    ) -> Result<i64> {
+        base::create::<Self, _>(ctx, mm, session_c).await
+    }
+
+    pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<UserSession> {
+        base::get::<Self, _>(ctx, mm, id).await
+    }
+
+    /// Get the session by its uuid (the identifier carried by the refresh token)
+    pub async fn first_by_uuid(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        uuid: Uuid,
+    ) -> Result<Option<UserSession>> {
+        // Build the SQL query
+        let mut query = Query::select();
+        query
+            .from(Self::table_ref())
+            .columns(UserSession::field_column_refs())
+            .and_where(Expr::col(UserSessionIden::Uuid).eq(uuid))
+            .and_where_option(owner_cond::<Self>(ctx));
+
+        // Execute the query
+        let mut conn = mm.conn().await?;
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+        let session = sqlx::query_as_with::<_, UserSession, _>(&sql, values)
+            .fetch_optional(&mut *conn)
+            .await?;
+
+        Ok(session)
+    }
+
+    pub async fn list(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        filters: Option<Vec<UserSessionFilter>>,
+        list_options: Option<ListOptions>,
+    ) -> Result<Vec<UserSession>> {
+        base::list::<Self, _, _>(ctx, mm, filters, list_options).await
+    }
+
+    /// Move the active session to the next generation and extend its expiration.
+    /// Returns false if the session is not at the `generation` anymore or is revoked
+    /// (e.g., the same refresh token was used concurrently).
+    pub async fn rotate(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i64,
+        generation: i64,
+        expires_at: OffsetDateTime,
+    ) -> Result<bool> {
+        // Prepare the fields and values
+        let mut fields = Fields::new(vec![
+            Field::new(
+                UserSessionIden::Generation,
+                Expr::col(UserSessionIden::Generation).add(1),
+            ),
+            Field::new(UserSessionIden::ExpiresAt, expires_at.into()),
+        ]);
+        prep_fields_for_update(&mut fields, ctx);
+
+        // Build the SQL query
+        let mut query = Query::update();
+        query
+            .table(Self::table_ref())
+            .values(fields.for_sea_update())
+            .and_where(Expr::col(UserSessionIden::Id).eq(id))
+            .and_where(Expr::col(UserSessionIden::Generation).eq(generation))
+            .and_where(Expr::col(UserSessionIden::RevokedAt).is_null())
+            .and_where_option(owner_cond::<Self>(ctx));
+
+        // Execute the query
+        let mut conn = mm.conn().await?;
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+        let count = sqlx::query_with(&sql, values)
+            .execute(&mut *conn)
+            .await?
+            .rows_affected();
+
+        Ok(count == 1)
+    }
+
+    /// Revoke the session (its refresh token cannot be used anymore)
+    pub async fn revoke(ctx: &Ctx, mm: &ModelManager, id: i64, reason: &str) -> Result<()> {
+        let session_u = UserSessionForRevoke {
+            revoked_at: now_utc(),
+            revoke_reason: reason.to_string(),
+        };
+
+        base::update::<Self, _>(ctx, mm, id, session_u).await
+    }
+
+    /// Revoke all the active sessions of the user. Returns the number of revoked sessions.
+    pub async fn revoke_all_for_user(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        user_id: i64,
+        reason: &str,
+    ) -> Result<u64> {
+        // Prepare the fields and values
+        let mut fields = Fields::new(vec![
+            Field::new(UserSessionIden::RevokedAt, now_utc().into()),
+            Field::new(UserSessionIden::RevokeReason, reason.into()),
+        ]);
+        prep_fields_for_update(&mut fields, ctx);
+
+        // Build the SQL query
+        let mut query = Query::update();
+        query
+            .table(Self::table_ref())
+            .values(fields.for_sea_update())
+            .and_where(Expr::col(UserSessionIden::UserId).eq(user_id))
+            .and_where(Expr::col(UserSessionIden::RevokedAt).is_null())
+            .and_where_option(owner_cond::<Self>(ctx));
+
+        // Execute the query
+        let mut conn = mm.conn().await?;
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+        let count = sqlx::query_with(&sql, values)
+            .execute(&mut *conn)
+            .await?
+            .rows_affected();
+
+        Ok(count)
+    }
+}
+// endregion: --- UserSessionBmc
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::_dev_utils;
+    use crate::model::psql::user::{User, UserBmc};
+    use crate::model::psql::Error;
+    use anyhow::{Context, Result};
+    use lib_utils::time::now_utc_plus_sec;
+    use serial_test::serial;
+
+    async fn fx_demo1_ctx(mm: &ModelManager) -> Result<Ctx> {
+        let user: User = UserBmc::first_by_username(&Ctx::root_ctx(), mm, "demo1")
+            .await?
+            .context("Should have user demo1")?;
+        Ok(Ctx::new(user.id)?)
+    }
+
+    async fn fx_create_session(ctx: &Ctx, mm: &ModelManager) -> Result<UserSession> {
+        let id = UserSessionBmc::create(
+            ctx,
+            mm,
+            UserSessionForCreate {
+                user_id: ctx.user_id(),
+                expires_at: now_utc_plus_sec(60),
+            },
+        )
+        .await?;
+        Ok(UserSessionBmc::get(ctx, mm, id).await?)
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_rotate_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = fx_demo1_ctx(&mm).await?;
+        let fx_session = fx_create_session(&ctx, &mm).await?;
+
+        // -- Exec
+        let rotated = UserSessionBmc::rotate(
+            &ctx,
+            &mm,
+            fx_session.id,
+            fx_session.generation,
+            now_utc_plus_sec(120),
+        )
+        .await?;
+        // Rotating again from the same generation (reused token) fails
+        let rotated_again = UserSessionBmc::rotate(
+            &ctx,
+            &mm,
+            fx_session.id,
+            fx_session.generation,
+            now_utc_plus_sec(120),
+        )
+        .await?;
+
+        // -- Check
+        assert!(rotated);
+        assert!(!rotated_again);
+        let session = UserSessionBmc::first_by_uuid(&ctx, &mm, fx_session.uuid)
+            .await?
+            .context("Should have the session")?;
+        assert_eq!(session.generation, fx_session.generation + 1);
+        assert!(session.expires_at > fx_session.expires_at);
+
+        // -- Clean
+        UserSessionBmc::revoke(&ctx, &mm, fx_session.id, "test").await?;
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_revoke_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = fx_demo1_ctx(&mm).await?;
+        let fx_session = fx_create_session(&ctx, &mm).await?;
+
+        // -- Exec
+        UserSessionBmc::revoke(&ctx, &mm, fx_session.id, "test_revoke_ok").await?;
+
+        // -- Check
+        let session = UserSessionBmc::get(&ctx, &mm, fx_session.id).await?;
+        assert!(session.revoked_at.is_some());
+        assert_eq!(session.revoke_reason.as_deref(), Some("test_revoke_ok"));
+        let rotated = UserSessionBmc::rotate(
+            &ctx,
+            &mm,
+            fx_session.id,
+            fx_session.generation,
+            now_utc_plus_sec(120),
+        )
+        .await?;
+        assert!(!rotated, "revoked session should not rotate");
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_revoke_all_for_user_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = fx_demo1_ctx(&mm).await?;
+        fx_create_session(&ctx, &mm).await?;
+        fx_create_session(&ctx, &mm).await?;
+
+        // -- Exec
+        let count = UserSessionBmc::revoke_all_for_user(
+            &ctx,
+            &mm,
+            ctx.user_id(),
+            "test_revoke_all_for_user_ok",
+        )
+        .await?;
+
+        // -- Check
+        assert!(count >= 2);
+        let filters = serde_json::from_value(serde_json::json!([{
+            "revoked_at": { "$null": true }
+        }]))?;
+        let active = UserSessionBmc::list(&ctx, &mm, Some(filters), None).await?;
+        assert!(active.is_empty(), "should have no active session");
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_get_err_not_owner() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = fx_demo1_ctx(&mm).await?;
+        let ctx_other = Ctx::new(ctx.user_id() + 1)?;
+        let fx_session = fx_create_session(&ctx, &mm).await?;
+
+        // -- Exec
+        let res = UserSessionBmc::revoke(&ctx_other, &mm, fx_session.id, "other").await;
+        let by_uuid = UserSessionBmc::first_by_uuid(&ctx_other, &mm, fx_session.uuid).await?;
+
+        // -- Check
+        assert!(matches!(
+            res,
+            Err(Error::EntityNotFound {
+                entity: "user_session",
+                ..
+            })
+        ));
+        assert!(by_uuid.is_none());
+
+        // -- Clean
+        UserSessionBmc::revoke(&ctx, &mm, fx_session.id, "test").await?;
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
@@ -0,0 +1,194 @@
+//! Cache-aside on the `RedisManager`: `get_or_load` returns the cached value of a key,
+//! or loads it (e.g., from Scylla) and caches it for the TTL of its namespace.
+//!
+//! The keys are namespaced and versioned: `cache:<namespace>:v<generation>:<key>`.
+//! `invalidate_namespace` increments the generation of the namespace, so all its keys are
+//! dropped at once (the old ones expire with their TTL). A value loaded before an invalidation
+//! is cached under the old generation, so it is never read after it.
+//!
+//! The concurrent loads of a key are single-flight (in this process): one loads,
+//! the others wait for it and read the cached value.
+//! (If Redis fails, each caller loads on its own: nothing would be cached to wait for)
+
+use crate::model::redis_cache::{RedisManager, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::future::Future;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// A group of cached keys, invalidated together
+#[derive(Debug, Clone, Copy)]
+pub struct CacheNamespace {
+    pub name: &'static str,
+    /// Time to live of the cached values, in seconds
+    pub ttl_sec: usize,
+}
+
+/// A key of a namespace (e.g., the id of an entity, or the params of a list)
+#[derive(Debug, Clone)]
+pub struct CacheKey {
+    namespace: CacheNamespace,
+    key: String,
+}
+
+impl CacheKey {
+    pub fn new(namespace: CacheNamespace, key: impl Into<String>) -> Self {
+        CacheKey {
+            namespace,
+            key: key.into(),
+        }
+    }
+
+    fn redis_key(&self, generation: u64) -> String {
+        format!("cache:{}:v{}:{}", self.namespace.name, generation, self.key)
+    }
+}
+
+fn generation_key(namespace: &CacheNamespace) -> String {
+    format!("cache:{}:generation", namespace.name)
+}
+
+impl RedisManager {
+    /// The cached value of the key, else the value loaded by `load` (then cached).
+    /// (The cache errors are logged, never returned: without Redis, the values are always loaded)
+    pub async fn get_or_load<T, E, F, Fut>(
+        &self,
+        key: &CacheKey,
+        load: F,
+    ) -> core::result::Result<T, E>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = core::result::Result<T, E>>,
+    {
+        let generation = match self.generation(&key.namespace).await {
+            Ok(generation) => generation,
+            Err(e) => {
+                warn!("--> RedisCache: Failed to get the generation of {key:?}: {e:?}");
+                return load().await;
+            }
+        };
+        let redis_key = key.redis_key(generation);
+        match self.get::<T>(&redis_key).await {
+            Ok(Some(cached)) => return Ok(cached),
+            Ok(None) => {}
+            // (The error is logged by `get`)
+            Err(_) => return load().await,
+        }
+
+        // Single-flight: the first caller loads, the others wait, then read the cache
+        let flight = self.flight(&redis_key);
+        let _guard = flight.lock().await;
+        if let Ok(Some(cached)) = self.get::<T>(&redis_key).await {
+            self.end_flight(&redis_key, &flight);
+            return Ok(cached);
+        }
+
+        debug!("--> RedisCache: Loading key: {}", redis_key);
+        let loaded = load().await;
+        if let Ok(value) = &loaded {
+            if let Err(e) = self.set(&redis_key, value, key.namespace.ttl_sec).await {
+                warn!("--> RedisCache: Failed to cache key {}: {:?}", redis_key, e);
+            }
+        }
+        self.end_flight(&redis_key, &flight);
+
+        loaded
+    }
+
+    /// Drop the cached value of the key
+    pub async fn invalidate(&self, key: &CacheKey) -> Result<()> {
+        let generation = self.generation(&key.namespace).await?;
+        self.delete(&[&key.redis_key(generation)]).await?;
+
+        Ok(())
+    }
+
+    /// Drop all the cached values of the namespace
+    pub async fn invalidate_namespace(&self, namespace: CacheNamespace) -> Result<()> {
+        debug!("--> RedisCache: Invalidating namespace: {}", namespace.name);
+        self.incr(&generation_key(&namespace), 1).await?;
+
+        Ok(())
+    }
+
+    async fn generation(&self, namespace: &CacheNamespace) -> Result<u64> {
+        let generation = self.get::<u64>(&generation_key(namespace)).await?;
+
+        Ok(generation.unwrap_or_default())
+    }
+
+    fn flight(&self, redis_key: &str) -> Arc<tokio::sync::Mutex<()>> {
+        let mut flights = self.flights.lock().unwrap_or_else(|e| e.into_inner());
+        flights.entry(redis_key.to_string()).or_default().clone()
+    }
+
+    /// Forget the flight of the key when no other caller waits for it
+    fn end_flight(&self, redis_key: &str, flight: &Arc<tokio::sync::Mutex<()>>) {
+        let mut flights = self.flights.lock().unwrap_or_else(|e| e.into_inner());
+        // (One reference in the map, and this one)
+        if Arc::strong_count(flight) <= 2 {
+            flights.remove(redis_key);
+        }
+    }
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::sync::Barrier;
+
+    #[test]
+    fn test_cache_key_ok() {
+        let fx_namespace = CacheNamespace {
+            name: "hnstory",
+            ttl_sec: 60,
+        };
+
+        let key = CacheKey::new(fx_namespace, "42");
+
+        assert_eq!(key.redis_key(0), "cache:hnstory:v0:42");
+        assert_eq!(key.redis_key(3), "cache:hnstory:v3:42");
+        assert_eq!(generation_key(&fx_namespace), "cache:hnstory:generation");
+    }
+
+    #[tokio::test]
+    async fn test_get_or_load_redis_down_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        // (Nothing listens on port 1: the connection is refused)
+        let fx_redis = RedisManager::from_client(redis::Client::open("redis://127.0.0.1:1")?, None);
+        let fx_key = CacheKey::new(
+            CacheNamespace {
+                name: "test",
+                ttl_sec: 60,
+            },
+            "42",
+        );
+        // Both loads must run at once (they would wait for each other with the single-flight)
+        let fx_barrier = Barrier::new(2);
+        let load = || async {
+            fx_barrier.wait().await;
+            Ok::<_, ()>(42)
+        };
+
+        // -- Exec
+        let (res_a, res_b) = tokio::time::timeout(
+            Duration::from_secs(5),
+            futures::future::join(
+                fx_redis.get_or_load(&fx_key, load),
+                fx_redis.get_or_load(&fx_key, load),
+            ),
+        )
+        .await
+        .expect("the loads should not wait for each other");
+
+        // -- Check
+        assert_eq!((res_a, res_b), (Ok(42), Ok(42)));
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
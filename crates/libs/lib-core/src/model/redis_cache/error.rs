@@ -13,6 +13,9 @@ pub enum Error {
 
     #[from]
     Redis(#[serde_as(as = "DisplayFromStr")] RedisError),
+
+    #[from]
+    SerdeJson(#[serde_as(as = "DisplayFromStr")] serde_json::Error),
 }
 
 // region:    --- Error Boilerplate
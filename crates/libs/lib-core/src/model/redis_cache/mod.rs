@@ -1,16 +1,50 @@
+mod cache;
 mod error;
 
-use redis::AsyncCommands;
+use redis::aio::MultiplexedConnection;
+use redis::{AsyncCommands, RedisResult, Script};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, RwLock};
 
 use crate::config::core_config;
 
+pub use self::cache::{CacheKey, CacheNamespace};
 pub use self::error::{Error, Result};
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
+
+/// Number of keys fetched by each SCAN call
+const SCAN_COUNT: usize = 100;
+
+/// INCRBY, setting the expiry when the key has none (a fixed window, e.g., for rate limits).
+/// (The TTL is checked, not the value: a key created without expiry, e.g., by `incr`, gets one)
+const INCR_WITH_EXPIRY_LUA: &str = r"
+local value = redis.call('INCRBY', KEYS[1], ARGV[1])
+if redis.call('TTL', KEYS[1]) == -1 then
+    redis.call('EXPIRE', KEYS[1], ARGV[2])
+end
+return value
+";
 
 pub struct RedisManager {
-    client: Arc<Mutex<redis::Client>>,
+    client: redis::Client,
+    // The connection shared by all the commands (see `SharedConn`)
+    conn: RwLock<SharedConn>,
+    // The loads in flight, by key (see `cache`)
+    flights: std::sync::Mutex<HashMap<String, Arc<Mutex<()>>>>,
+}
+
+/// The connection shared by all the commands (multiplexed, cheap to clone),
+/// None until connected, or after it was dropped (reconnected on the next command).
+/// Its generation is incremented on each connection: a failed command only drops
+/// the connection it ran on, not a newer one.
+#[derive(Default)]
+struct SharedConn {
+    generation: u64,
+    conn: Option<MultiplexedConnection>,
 }
 
 impl RedisManager {
@@ -34,49 +68,128 @@ impl RedisManager {
                 redis_url
             );
         }
-        Ok(Self {
-            client: Arc::new(Mutex::new(client)),
-        })
+        Ok(Self::from_client(client, Some(conn)))
+    }
+
+    fn from_client(client: redis::Client, conn: Option<MultiplexedConnection>) -> Self {
+        Self {
+            client,
+            conn: RwLock::new(SharedConn {
+                generation: 0,
+                conn,
+            }),
+            flights: Default::default(),
+        }
+    }
+
+    pub async fn initialize() -> Result<Arc<Self>> {
+        // Get Redis URL from environment variable or use default
+        let redis_url = &core_config().REDIS_URL;
+
+        info!("--> RedisCache: Initializing with URL: {}", redis_url);
+
+        let manager = Self::new(redis_url).await?;
+        Ok(Arc::new(manager))
     }
 
-    pub async fn set<T: serde::Serialize>(
-        &self,
-        key: &str,
-        value: &T,
-        expiry: usize,
-    ) -> Result<()> {
+    // region:    --- Connection
+    /// The shared connection (connected if none), and its generation
+    async fn conn(&self) -> Result<(MultiplexedConnection, u64)> {
+        {
+            let shared = self.conn.read().await;
+            if let Some(conn) = shared.conn.as_ref() {
+                return Ok((conn.clone(), shared.generation));
+            }
+        }
+
+        let mut shared = self.conn.write().await;
+        // (Another command may have reconnected while waiting for the lock)
+        if let Some(conn) = shared.conn.as_ref() {
+            return Ok((conn.clone(), shared.generation));
+        }
+        info!("--> RedisCache: Connecting to Redis");
+        let new_conn = self.client.get_multiplexed_async_connection().await?;
+        shared.generation += 1;
+        shared.conn = Some(new_conn.clone());
+
+        Ok((new_conn, shared.generation))
+    }
+
+    /// Drop the shared connection, if still the one of the generation
+    /// (a concurrent failure may have reconnected already)
+    async fn drop_conn(&self, generation: u64) {
+        let mut shared = self.conn.write().await;
+        if shared.generation == generation {
+            shared.conn = None;
+        }
+    }
+
+    /// Run the command on the shared connection.
+    /// If the connection was dropped, reconnect and run it again (once).
+    /// (For the idempotent commands only, see `run_once`)
+    async fn run<T, F, Fut>(&self, command: F) -> Result<T>
+    where
+        F: Fn(MultiplexedConnection) -> Fut,
+        Fut: Future<Output = RedisResult<T>>,
+    {
+        self.run_with_retry(command, true).await
+    }
+
+    /// Run the command on the shared connection, without retry
+    /// (e.g., INCRBY: a command failing on a dropped connection may have been applied)
+    async fn run_once<T, F, Fut>(&self, command: F) -> Result<T>
+    where
+        F: Fn(MultiplexedConnection) -> Fut,
+        Fut: Future<Output = RedisResult<T>>,
+    {
+        self.run_with_retry(command, false).await
+    }
+
+    async fn run_with_retry<T, F, Fut>(&self, command: F, retry: bool) -> Result<T>
+    where
+        F: Fn(MultiplexedConnection) -> Fut,
+        Fut: Future<Output = RedisResult<T>>,
+    {
+        let (conn, generation) = self.conn().await?;
+        match command(conn).await {
+            Err(e) if e.is_io_error() || e.is_connection_dropped() => {
+                warn!("--> RedisCache: Connection lost ({}), reconnecting", e);
+                self.drop_conn(generation).await;
+                if !retry {
+                    return Err(e.into());
+                }
+                let (conn, _) = self.conn().await?;
+                Ok(command(conn).await?)
+            }
+            res => Ok(res?),
+        }
+    }
+    // endregion: --- Connection
+
+    // region:    --- Values
+    /// Set the value (as json), expiring after `expiry` seconds
+    pub async fn set<T: Serialize>(&self, key: &str, value: &T, expiry: usize) -> Result<()> {
         debug!("--> RedisCache: Attempting to set key: {}", key);
-        let client = self.client.lock().await;
-        let mut conn = client.get_multiplexed_async_connection().await?;
-        let serialized = serde_json::to_string(value).map_err(|e| {
-            redis::RedisError::from((
-                redis::ErrorKind::IoError,
-                "Serialization error",
-                e.to_string(),
-            ))
-        })?;
-        conn.set_ex::<_, _, ()>(key, serialized, expiry.try_into().unwrap())
-            .await?;
+        let serialized = serde_json::to_string(value)?;
+        self.run(|mut conn| {
+            let serialized = &serialized;
+            async move {
+                conn.set_ex::<_, _, ()>(key, serialized, expiry as u64)
+                    .await
+            }
+        })
+        .await?;
         debug!("--> RedisCache: Successfully set key: {}", key);
         Ok(())
     }
 
-    pub async fn get<T: serde::de::DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+    /// Get the value (from json)
+    pub async fn get<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
         debug!("--> RedisCache: Attempting to get key: {}", key);
-        let client = self.client.lock().await;
-        let mut conn = client.get_multiplexed_async_connection().await?;
-        let result: Option<String> = conn.get(key).await?;
-        let result = result
-            .map(|s| {
-                serde_json::from_str(&s).map_err(|e| {
-                    Error::Redis(redis::RedisError::from((
-                        redis::ErrorKind::IoError,
-                        "Deserialization error",
-                        e.to_string(),
-                    )))
-                })
-            })
-            .transpose();
+        let result = self
+            .run(|mut conn| async move { conn.get::<_, Option<String>>(key).await })
+            .await
+            .and_then(|value| from_json(value.as_deref()));
 
         match &result {
             Ok(Some(_)) => debug!("--> RedisCache: Successfully retrieved key: {}", key),
@@ -87,13 +200,303 @@ impl RedisManager {
         result
     }
 
-    pub async fn initialize() -> Result<Arc<Self>> {
-        // Get Redis URL from environment variable or use default
-        let redis_url = &core_config().REDIS_URL;
+    /// Delete the keys, returns the number of keys deleted
+    pub async fn delete(&self, keys: &[&str]) -> Result<u64> {
+        if keys.is_empty() {
+            return Ok(0);
+        }
+        self.run(|mut conn| async move { conn.del(keys).await })
+            .await
+    }
 
-        info!("--> RedisCache: Initializing with URL: {}", redis_url);
+    /// Get the values of the keys (None for the missing keys)
+    pub async fn mget<T: DeserializeOwned>(&self, keys: &[&str]) -> Result<Vec<Option<T>>> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        // (MGET of a single key must still return an array)
+        let values: Vec<Option<String>> = self
+            .run(
+                |mut conn| async move { redis::cmd("MGET").arg(keys).query_async(&mut conn).await },
+            )
+            .await?;
 
-        let manager = Self::new(redis_url).await?;
-        Ok(Arc::new(manager))
+        values
+            .iter()
+            .map(|value| from_json(value.as_deref()))
+            .collect()
+    }
+
+    /// Set the values, all expiring after `expiry` seconds (in one transaction)
+    pub async fn mset<T: Serialize>(&self, items: &[(&str, T)], expiry: usize) -> Result<()> {
+        if items.is_empty() {
+            return Ok(());
+        }
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        for (key, value) in items {
+            pipe.set_ex(*key, serde_json::to_string(value)?, expiry as u64)
+                .ignore();
+        }
+
+        self.run(|mut conn| {
+            let pipe = &pipe;
+            async move { pipe.query_async::<()>(&mut conn).await }
+        })
+        .await
+    }
+
+    /// Increment the integer of the key (0 if missing), returns the new value
+    /// (not retried on a dropped connection, it may have been applied)
+    pub async fn incr(&self, key: &str, delta: i64) -> Result<i64> {
+        self.run_once(|mut conn| async move { conn.incr(key, delta).await })
+            .await
+    }
+
+    /// Increment the integer of the key, which expires `expiry` seconds after its creation
+    /// (i.e., the count of a fixed time window), returns the new value
+    /// (not retried on a dropped connection, it may have been applied)
+    pub async fn incr_with_expiry(&self, key: &str, delta: i64, expiry: usize) -> Result<i64> {
+        let script = Script::new(INCR_WITH_EXPIRY_LUA);
+        self.run_once(|mut conn| {
+            let script = &script;
+            async move {
+                script
+                    .key(key)
+                    .arg(delta)
+                    .arg(expiry)
+                    .invoke_async(&mut conn)
+                    .await
+            }
+        })
+        .await
+    }
+    // endregion: --- Values
+
+    // region:    --- Hashes
+    /// Set the value (as json) of the field of the hash
+    pub async fn hset<T: Serialize>(&self, key: &str, field: &str, value: &T) -> Result<()> {
+        let serialized = serde_json::to_string(value)?;
+        self.run(|mut conn| {
+            let serialized = &serialized;
+            async move { conn.hset::<_, _, _, ()>(key, field, serialized).await }
+        })
+        .await
+    }
+
+    /// Get the value (from json) of the field of the hash
+    pub async fn hget<T: DeserializeOwned>(&self, key: &str, field: &str) -> Result<Option<T>> {
+        let value: Option<String> = self
+            .run(|mut conn| async move { conn.hget(key, field).await })
+            .await?;
+
+        from_json(value.as_deref())
+    }
+
+    /// Get all the fields of the hash (empty if missing)
+    pub async fn hgetall<T: DeserializeOwned>(&self, key: &str) -> Result<HashMap<String, T>> {
+        let fields: HashMap<String, String> = self
+            .run(|mut conn| async move { conn.hgetall(key).await })
+            .await?;
+
+        fields
+            .into_iter()
+            .map(|(field, value)| Ok((field, serde_json::from_str(&value)?)))
+            .collect()
+    }
+
+    /// Delete the fields of the hash, returns the number of fields deleted
+    pub async fn hdel(&self, key: &str, fields: &[&str]) -> Result<u64> {
+        if fields.is_empty() {
+            return Ok(0);
+        }
+        self.run(|mut conn| async move { conn.hdel(key, fields).await })
+            .await
+    }
+    // endregion: --- Hashes
+
+    // region:    --- Scan
+    /// The keys starting with the prefix
+    /// (SCAN, incremental on the server: the keys changed while scanning may be missed)
+    pub async fn scan_prefix(&self, prefix: &str) -> Result<Vec<String>> {
+        let pattern = format!("{}*", escape_glob(prefix));
+        self.run(|mut conn| {
+            let pattern = &pattern;
+            async move {
+                let mut keys = Vec::new();
+                let mut cursor: u64 = 0;
+                loop {
+                    let (next_cursor, batch): (u64, Vec<String>) = redis::cmd("SCAN")
+                        .arg(cursor)
+                        .arg("MATCH")
+                        .arg(pattern)
+                        .arg("COUNT")
+                        .arg(SCAN_COUNT)
+                        .query_async(&mut conn)
+                        .await?;
+                    keys.extend(batch);
+                    if next_cursor == 0 {
+                        return Ok(keys);
+                    }
+                    cursor = next_cursor;
+                }
+            }
+        })
+        .await
+    }
+    // endregion: --- Scan
+}
+
+// region:    --- Support
+fn from_json<T: DeserializeOwned>(value: Option<&str>) -> Result<Option<T>> {
+    value
+        .map(serde_json::from_str)
+        .transpose()
+        .map_err(Error::from)
+}
+
+/// Escape the glob characters of a SCAN MATCH pattern
+fn escape_glob(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+// endregion: --- Support
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The command tests need the Redis of `REDIS_URL`, they are ignored by default
+    // (run them with `cargo test -p lib-core -- --ignored`)
+
+    #[test]
+    fn test_escape_glob_ok() {
+        assert_eq!(escape_glob("cache:hnstory:"), "cache:hnstory:");
+        assert_eq!(escape_glob(r"a*b?[c]\d"), r"a\*b\?\[c\]\\d");
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis"]
+    async fn test_values_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let redis = RedisManager::initialize().await?;
+        let fx_keys = ["test_values_ok:a", "test_values_ok:b", "test_values_ok:c"];
+
+        // -- Exec
+        redis.set(fx_keys[0], &"value a", 60).await?;
+        redis
+            .mset(&[(fx_keys[1], vec![1, 2]), (fx_keys[2], vec![3])], 60)
+            .await?;
+        let value_a: Option<String> = redis.get(fx_keys[0]).await?;
+        let values: Vec<Option<Vec<i32>>> = redis
+            .mget(&[fx_keys[1], "test_values_ok:missing", fx_keys[2]])
+            .await?;
+        let deleted = redis.delete(&fx_keys).await?;
+        let value_a_deleted: Option<String> = redis.get(fx_keys[0]).await?;
+
+        // -- Check
+        assert_eq!(value_a.as_deref(), Some("value a"));
+        assert_eq!(values, vec![Some(vec![1, 2]), None, Some(vec![3])]);
+        assert_eq!(deleted, 3);
+        assert_eq!(value_a_deleted, None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis"]
+    async fn test_incr_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let redis = RedisManager::initialize().await?;
+        let fx_key = "test_incr_ok:counter";
+        let fx_window_key = "test_incr_ok:window";
+        redis.delete(&[fx_key, fx_window_key]).await?;
+
+        // -- Exec
+        let first = redis.incr(fx_key, 1).await?;
+        let second = redis.incr(fx_key, 2).await?;
+        // (The key has no expiry yet, e.g., created by `incr`: it gets one)
+        let with_expiry = redis.incr_with_expiry(fx_key, 1, 60).await?;
+        let window_first = redis.incr_with_expiry(fx_window_key, 5, 60).await?;
+        let window_second = redis.incr_with_expiry(fx_window_key, 5, 3600).await?;
+
+        // -- Check
+        let (mut conn, _) = redis.conn().await?;
+        let ttl: i64 = conn.ttl(fx_key).await?;
+        let window_ttl: i64 = conn.ttl(fx_window_key).await?;
+        assert_eq!((first, second, with_expiry), (1, 3, 4));
+        assert_eq!((window_first, window_second), (5, 10));
+        assert!((1..=60).contains(&ttl), "ttl: {ttl}");
+        // The window expiry is set once, when the key is created
+        assert!((1..=60).contains(&window_ttl), "window_ttl: {window_ttl}");
+
+        // -- Clean
+        redis.delete(&[fx_key, fx_window_key]).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis"]
+    async fn test_hashes_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let redis = RedisManager::initialize().await?;
+        let fx_key = "test_hashes_ok:hash";
+        redis.delete(&[fx_key]).await?;
+
+        // -- Exec
+        redis.hset(fx_key, "a", &1).await?;
+        redis.hset(fx_key, "b", &2).await?;
+        let a: Option<i32> = redis.hget(fx_key, "a").await?;
+        let missing: Option<i32> = redis.hget(fx_key, "missing").await?;
+        let all: HashMap<String, i32> = redis.hgetall(fx_key).await?;
+        let deleted = redis.hdel(fx_key, &["a", "missing"]).await?;
+        let all_after: HashMap<String, i32> = redis.hgetall(fx_key).await?;
+
+        // -- Check
+        assert_eq!((a, missing), (Some(1), None));
+        assert_eq!(all, HashMap::from([("a".into(), 1), ("b".into(), 2)]));
+        assert_eq!(deleted, 1);
+        assert_eq!(all_after, HashMap::from([("b".into(), 2)]));
+
+        // -- Clean
+        redis.delete(&[fx_key]).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis"]
+    async fn test_scan_prefix_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let redis = RedisManager::initialize().await?;
+        // (The glob characters of the prefix are matched as is)
+        let fx_prefix = "test_scan_prefix_ok:[a]*:";
+        let fx_keys = ["test_scan_prefix_ok:[a]*:1", "test_scan_prefix_ok:[a]*:2"];
+        let fx_other_key = "test_scan_prefix_ok:a:1";
+        for key in fx_keys.iter().chain([&fx_other_key]) {
+            redis.set(key, &0, 60).await?;
+        }
+
+        // -- Exec
+        let mut keys = redis.scan_prefix(fx_prefix).await?;
+
+        // -- Check
+        keys.sort();
+        assert_eq!(keys, fx_keys);
+
+        // -- Clean
+        redis.delete(&fx_keys).await?;
+        redis.delete(&[fx_other_key]).await?;
+
+        Ok(())
     }
 }
+// endregion: --- Tests
@@ -0,0 +1,348 @@
+//! The generic Scylla table access (the Scylla counterpart of the psql `DbBmc` and `base` functions).
+//!
+//! An entity declares its table and typed row with `ScyllaBmc`, e.g.:
+//!
+//! ```ignore
+//! pub struct HNStoryBmc;
+//!
+//! impl ScyllaBmc for HNStoryBmc {
+//!     const TABLE: &'static str = "hnstory";
+//!     const PARTITION_KEY: &'static [&'static str] = &["id"];
+//!     const COLUMNS: &'static [&'static str] = &["id", "title", ...];
+//!     type Row = HNStory;
+//! }
+//! ```
+//!
+//! The CQL of the functions below is built from these, and prepared once per `ScyllaManager`.
+//! (The table itself is created by a migration, see `migration`)
+
+use crate::model::scylla::error::{Error, Result};
+use crate::model::scylla::ScyllaManager;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use scylla::serialize::row::{RowSerializationContext, SerializeRow};
+use scylla::serialize::value::SerializeCql;
+use scylla::serialize::{RowWriter, SerializationError};
+use scylla::{Bytes, FromRow, IntoTypedRows};
+use scylla_cql::frame::response::result::PreparedMetadata;
+use serde::{Deserialize, Serialize};
+use std::io;
+
+pub trait ScyllaBmc {
+    const TABLE: &'static str;
+
+    /// The partition key columns
+    const PARTITION_KEY: &'static [&'static str];
+
+    /// The clustering columns (the rest of the primary key, in the clustering order)
+    const CLUSTERING_KEY: &'static [&'static str] = &[];
+
+    /// All the columns, in the field order of `Row`
+    /// (`FromRow` is positional, the selects list these columns)
+    const COLUMNS: &'static [&'static str];
+
+    /// The row type (serialized by column name, deserialized by position)
+    type Row: SerializeRow + FromRow;
+}
+
+// region:    --- Paging
+/// The opaque state of the next page (base64 of the driver paging state)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PagingState(pub String);
+
+impl PagingState {
+    pub fn new(encoded: String) -> Self {
+        PagingState(encoded)
+    }
+
+    pub fn from_bytes(bytes: &Bytes) -> Self {
+        PagingState(BASE64.encode(bytes))
+    }
+
+    pub fn into_bytes(&self) -> Result<Bytes> {
+        BASE64
+            .decode(&self.0)
+            .map(Bytes::from)
+            .map_err(Error::Base64)
+    }
+}
+// endregion: --- Paging
+
+/// Insert (or overwrite) the row
+pub async fn insert<M: ScyllaBmc>(sm: &ScyllaManager, row: &M::Row) -> Result<()> {
+    let prepared = sm.prepare(insert_cql::<M>(false)).await?;
+    sm.session().execute(&prepared, row).await?;
+
+    Ok(())
+}
+
+/// Insert (or overwrite) the row, expiring after `ttl_sec` seconds
+pub async fn insert_with_ttl<M: ScyllaBmc>(
+    sm: &ScyllaManager,
+    row: &M::Row,
+    ttl_sec: u32,
+) -> Result<()> {
+    // (The TTL is bound, one statement for all the TTLs)
+    let prepared = sm.prepare(insert_cql::<M>(true)).await?;
+    let ttl_sec = i32::try_from(ttl_sec).unwrap_or(i32::MAX);
+    sm.session()
+        .execute(&prepared, RowWithTtl { row, ttl_sec })
+        .await?;
+
+    Ok(())
+}
+
+/// Get the row of the primary key (the values of the partition, then clustering, columns)
+pub async fn get<M: ScyllaBmc>(
+    sm: &ScyllaManager,
+    key: impl SerializeRow,
+) -> Result<Option<M::Row>> {
+    let prepared = sm.prepare(select_cql::<M>(&primary_key::<M>())).await?;
+    let row = sm
+        .session()
+        .execute(&prepared, key)
+        .await?
+        .rows
+        .unwrap_or_default()
+        .into_typed::<M::Row>()
+        .next()
+        .transpose()?;
+
+    Ok(row)
+}
+
+/// A page of the rows of a partition (in the clustering order)
+pub async fn list<M: ScyllaBmc>(
+    sm: &ScyllaManager,
+    partition_key: impl SerializeRow,
+    page_size: i32,
+    paging_state: Option<PagingState>,
+) -> Result<(Vec<M::Row>, Option<PagingState>)> {
+    let cql = select_cql::<M>(M::PARTITION_KEY);
+    list_page::<M>(sm, cql, partition_key, page_size, paging_state).await
+}
+
+/// A page of the rows of all the partitions (in token order)
+pub async fn list_all<M: ScyllaBmc>(
+    sm: &ScyllaManager,
+    page_size: i32,
+    paging_state: Option<PagingState>,
+) -> Result<(Vec<M::Row>, Option<PagingState>)> {
+    let cql = select_cql::<M>(&[]);
+    list_page::<M>(sm, cql, (), page_size, paging_state).await
+}
+
+/// Delete the row of the primary key
+pub async fn delete<M: ScyllaBmc>(sm: &ScyllaManager, key: impl SerializeRow) -> Result<()> {
+    let prepared = sm.prepare(delete_cql::<M>()).await?;
+    sm.session().execute(&prepared, key).await?;
+
+    Ok(())
+}
+
+async fn list_page<M: ScyllaBmc>(
+    sm: &ScyllaManager,
+    cql: String,
+    values: impl SerializeRow,
+    page_size: i32,
+    paging_state: Option<PagingState>,
+) -> Result<(Vec<M::Row>, Option<PagingState>)> {
+    // The statement is prepared once, only the page size differs
+    let mut prepared = sm.prepare(cql).await?;
+    prepared.set_page_size(page_size.max(1));
+    let paging_state = paging_state.map(|ps| ps.into_bytes()).transpose()?;
+
+    let result = sm
+        .session()
+        .execute_paged(&prepared, values, paging_state)
+        .await?;
+    let next_paging_state = result.paging_state.as_ref().map(PagingState::from_bytes);
+    let rows = result
+        .rows
+        .unwrap_or_default()
+        .into_typed::<M::Row>()
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    Ok((rows, next_paging_state))
+}
+
+/// The row values, then the TTL (the `USING TTL ?` bind marker, the last one)
+struct RowWithTtl<'a, R> {
+    row: &'a R,
+    ttl_sec: i32,
+}
+
+impl<R: SerializeRow> SerializeRow for RowWithTtl<'_, R> {
+    fn serialize(
+        &self,
+        ctx: &RowSerializationContext<'_>,
+        writer: &mut RowWriter,
+    ) -> std::result::Result<(), SerializationError> {
+        let (ttl_spec, row_specs) = ctx.columns().split_last().ok_or_else(|| {
+            SerializationError::new(io::Error::other("the TTL bind marker is missing"))
+        })?;
+
+        // The row is serialized by the names of its columns (without the TTL marker)
+        let row_metadata = PreparedMetadata {
+            flags: 0,
+            col_count: row_specs.len(),
+            pk_indexes: Vec::new(),
+            col_specs: row_specs.to_vec(),
+        };
+        self.row.serialize(
+            &RowSerializationContext::from_prepared(&row_metadata),
+            writer,
+        )?;
+        SerializeCql::serialize(&self.ttl_sec, &ttl_spec.typ, writer.make_cell_writer())?;
+
+        Ok(())
+    }
+
+    fn is_empty(&self) -> bool {
+        false
+    }
+}
+
+// region:    --- CQL Builders
+fn primary_key<M: ScyllaBmc>() -> Vec<&'static str> {
+    [M::PARTITION_KEY, M::CLUSTERING_KEY].concat()
+}
+
+fn where_clause(columns: &[&str]) -> String {
+    if columns.is_empty() {
+        return String::new();
+    }
+
+    let conds: Vec<String> = columns.iter().map(|c| format!("{c} = ?")).collect();
+    format!(" WHERE {}", conds.join(" AND "))
+}
+
+fn insert_cql<M: ScyllaBmc>(with_ttl: bool) -> String {
+    let markers = vec!["?"; M::COLUMNS.len()].join(", ");
+    let ttl = if with_ttl { " USING TTL ?" } else { "" };
+
+    format!(
+        "INSERT INTO {} ({}) VALUES ({markers}){ttl}",
+        M::TABLE,
+        M::COLUMNS.join(", ")
+    )
+}
+
+fn select_cql<M: ScyllaBmc>(key_columns: &[&str]) -> String {
+    format!(
+        "SELECT {} FROM {}{}",
+        M::COLUMNS.join(", "),
+        M::TABLE,
+        where_clause(key_columns)
+    )
+}
+
+fn delete_cql<M: ScyllaBmc>() -> String {
+    format!(
+        "DELETE FROM {}{}",
+        M::TABLE,
+        where_clause(&primary_key::<M>())
+    )
+}
+// endregion: --- CQL Builders
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scylla::frame::response::result::{ColumnSpec, ColumnType, TableSpec};
+    use scylla::serialize::row::SerializedValues;
+    use scylla_cql::frame::types::RawValue;
+
+    #[derive(scylla::SerializeRow, scylla::FromRow)]
+    struct EventRow {
+        source: String,
+        at: i64,
+        message: String,
+    }
+
+    struct EventBmc;
+
+    impl ScyllaBmc for EventBmc {
+        const TABLE: &'static str = "event";
+        const PARTITION_KEY: &'static [&'static str] = &["source"];
+        const CLUSTERING_KEY: &'static [&'static str] = &["at"];
+        const COLUMNS: &'static [&'static str] = &["source", "at", "message"];
+        type Row = EventRow;
+    }
+
+    #[test]
+    fn test_cql_builders_ok() {
+        assert_eq!(
+            insert_cql::<EventBmc>(false),
+            "INSERT INTO event (source, at, message) VALUES (?, ?, ?)"
+        );
+        assert_eq!(
+            insert_cql::<EventBmc>(true),
+            "INSERT INTO event (source, at, message) VALUES (?, ?, ?) USING TTL ?"
+        );
+        assert_eq!(
+            select_cql::<EventBmc>(&primary_key::<EventBmc>()),
+            "SELECT source, at, message FROM event WHERE source = ? AND at = ?"
+        );
+        assert_eq!(
+            select_cql::<EventBmc>(EventBmc::PARTITION_KEY),
+            "SELECT source, at, message FROM event WHERE source = ?"
+        );
+        assert_eq!(
+            select_cql::<EventBmc>(&[]),
+            "SELECT source, at, message FROM event"
+        );
+        assert_eq!(
+            delete_cql::<EventBmc>(),
+            "DELETE FROM event WHERE source = ? AND at = ?"
+        );
+    }
+
+    #[test]
+    fn test_row_with_ttl_serialize_ok() -> std::result::Result<(), SerializationError> {
+        // -- Setup & Fixtures
+        let fx_spec = |name: &str, typ: ColumnType| ColumnSpec {
+            table_spec: TableSpec::borrowed("ks", "event"),
+            name: name.to_string(),
+            typ,
+        };
+        // (The bind markers of `insert_cql::<EventBmc>(true)`)
+        let fx_specs = vec![
+            fx_spec("source", ColumnType::Text),
+            fx_spec("at", ColumnType::BigInt),
+            fx_spec("message", ColumnType::Text),
+            fx_spec("[ttl]", ColumnType::Int),
+        ];
+        let fx_metadata = PreparedMetadata {
+            flags: 0,
+            col_count: fx_specs.len(),
+            pk_indexes: Vec::new(),
+            col_specs: fx_specs,
+        };
+        let fx_row = EventRow {
+            source: "web".to_string(),
+            at: 1,
+            message: "started".to_string(),
+        };
+
+        // -- Exec
+        let values = SerializedValues::from_serializable(
+            &RowSerializationContext::from_prepared(&fx_metadata),
+            &RowWithTtl {
+                row: &fx_row,
+                ttl_sec: 60,
+            },
+        )?;
+
+        // -- Check
+        // The row values, then the TTL
+        assert_eq!(values.element_count(), 4);
+        assert_eq!(
+            values.iter().last(),
+            Some(RawValue::Value(&60i32.to_be_bytes()))
+        );
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
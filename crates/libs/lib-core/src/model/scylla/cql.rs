@@ -0,0 +1,54 @@
+//! The CQL queries, embedded in the binary (from `sql/csql`, one statement per file),
+//! and prepared once, when the `ScyllaManager` is created (see `Statements`).
+//! (The schema is in the migrations, see `migration`)
+
+use crate::model::scylla::error::Result;
+use scylla::prepared_statement::PreparedStatement;
+use scylla::Session;
+
+/// Embed a CQL file of `sql/csql`
+macro_rules! cql {
+    ($file:literal) => {
+        include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../../sql/csql/",
+            $file
+        ))
+    };
+}
+pub(super) use cql;
+
+// region:    --- Statements
+/// The prepared queries (one handle per statement)
+/// (The single table accesses are generic, see `base`)
+pub(super) struct Statements {
+    pub add_story_term: PreparedStatement,
+    pub select_term_stories: PreparedStatement,
+    pub select_stories_by_tag: PreparedStatement,
+    pub select_stories_by_author: PreparedStatement,
+    pub select_stories_by_day: PreparedStatement,
+}
+
+impl Statements {
+    /// Prepare all the statements (the migrations must be applied, and the keyspace used)
+    pub async fn prepare(session: &Session) -> Result<Self> {
+        Ok(Statements {
+            add_story_term: session
+                .prepare(cql!("hnstory/00-add-story-term.sql"))
+                .await?,
+            select_term_stories: session
+                .prepare(cql!("hnstory/01-select-term-stories.sql"))
+                .await?,
+            select_stories_by_tag: session
+                .prepare(cql!("hnstory/02-select-stories-by-tag.sql"))
+                .await?,
+            select_stories_by_author: session
+                .prepare(cql!("hnstory/03-select-stories-by-author.sql"))
+                .await?,
+            select_stories_by_day: session
+                .prepare(cql!("hnstory/04-select-stories-by-day.sql"))
+                .await?,
+        })
+    }
+}
+// endregion: --- Statements
@@ -31,6 +31,21 @@ pub enum Error {
     Base64(#[serde_as(as = "DisplayFromStr")] base64::DecodeError),
 
     ScyllaError(String),
+    /// The paging state is not one returned by the previous page
+    PagingStateInvalid(String),
+
+    // -- Migrations
+    KeyspaceInvalid(String),
+    ReplicationInvalid(String),
+    /// An applied migration was modified since
+    MigrationChecksumMismatch {
+        version: i32,
+    },
+    /// An applied migration is not known to this binary (applied by a newer one)
+    MigrationUnknown {
+        version: i32,
+    },
+    MigrationLockTimeout,
 }
 
 // region:    --- Error Boilerplate
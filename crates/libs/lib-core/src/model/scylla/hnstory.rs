@@ -1,18 +1,33 @@
-use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
-use scylla::{
-    prepared_statement::PreparedStatement, query::Query, Bytes, FromRow, IntoTypedRows,
-    SerializeRow, Session, SessionBuilder, ValueList,
-};
+use scylla::{FromRow, SerializeRow, ValueList};
 use serde::{Deserialize, Serialize};
-use std::{env, fs, path::Path, path::PathBuf};
+use time::OffsetDateTime;
+
+use crate::model::redis_cache::{CacheKey, CacheNamespace, RedisManager};
+use crate::model::scylla::base::{self, ScyllaBmc};
+use crate::model::scylla::error::Result;
+use crate::model::scylla::hnstory_query::add_hnstory_to_query_tables;
+use crate::model::scylla::hnstory_search::index_hnstory;
+use crate::model::scylla::ScyllaManager;
+use tracing::{debug, info, warn};
+
+pub use crate::model::scylla::base::PagingState;
 
-use crate::model::redis_cache::RedisManager;
-use crate::model::scylla::error::{Error, Result};
-use tracing::{debug, error, info};
+/// Number of stories read per query when reindexing
+const REINDEX_PAGE_SIZE: i32 = 500;
 
-const REDIS_CACHE_DURATION: usize = 300; // 5 minutes
+/// The cached stories, by id
+const HNSTORY_CACHE: CacheNamespace = CacheNamespace {
+    name: "hnstory",
+    ttl_sec: 3600,
+};
+/// The cached lists of stories (pages, queries and searches)
+pub(super) const HNSTORIES_CACHE: CacheNamespace = CacheNamespace {
+    name: "hnstories",
+    ttl_sec: 300,
+};
 
 #[derive(PartialEq, Clone, Debug, SerializeRow, Deserialize, FromRow, ValueList, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct HNStory {
     #[serde(alias = "objectID")]
     pub id: String,
@@ -23,224 +38,145 @@ pub struct HNStory {
     #[serde(alias = "_tags")]
     pub tags: Option<Vec<String>>,
     pub points: i32,
+    /// Unix time (seconds), set when added if missing
+    #[serde(alias = "created_at_i")]
+    pub created_at: Option<i64>,
 }
 
-const SQL_DIR: &str = "sql/csql";
-
-pub async fn create_session(uri: &str) -> Result<Session> {
-    SessionBuilder::new()
-        .known_node(uri)
-        .build()
-        .await
-        .map_err(From::from)
+/// The stories, by id
+pub struct HNStoryBmc;
+
+impl ScyllaBmc for HNStoryBmc {
+    const TABLE: &'static str = "hnstory";
+    const PARTITION_KEY: &'static [&'static str] = &["id"];
+    const COLUMNS: &'static [&'static str] = &[
+        "id",
+        "title",
+        "author",
+        "url",
+        "story_text",
+        "tags",
+        "points",
+        "created_at",
+    ];
+    type Row = HNStory;
 }
 
-pub async fn initialize(session: &Session) -> Result<()> {
-    create_keyspace(session).await?;
-    create_hnstory_table(session).await?;
-    Ok(())
-}
+/// Add the story, its terms to the search index, and the story to the query tables
+/// (by tag, author and day). The cached story and lists of stories are invalidated
+/// (without Redis, e.g., unreachable at startup, the cached lists only expire with their TTL).
+pub async fn add_hnstory(
+    sm: &ScyllaManager,
+    redis: Option<&RedisManager>,
+    mut hnstory: HNStory,
+) -> Result<()> {
+    // A re-added story keeps its date (its query table rows are overwritten, not duplicated)
+    if hnstory.created_at.is_none() {
+        let stored = base::get::<HNStoryBmc>(sm, (hnstory.id.as_str(),)).await?;
+        hnstory.created_at = Some(
+            stored
+                .and_then(|stored| stored.created_at)
+                .unwrap_or_else(|| OffsetDateTime::now_utc().unix_timestamp()),
+        );
+    }
 
-async fn create_keyspace(session: &Session) -> Result<()> {
-    let query = read_sql_file("dev_initial/00-recreate-db.sql")?;
-    session
-        .query(query, ())
-        .await
-        .map(|_| ())
-        .map_err(From::from)
-}
+    base::insert::<HNStoryBmc>(sm, &hnstory).await?;
+    index_hnstory(sm, &hnstory).await?;
+    add_hnstory_to_query_tables(sm, &hnstory).await?;
 
-async fn create_hnstory_table(session: &Session) -> Result<()> {
-    let query = read_sql_file("dev_initial/01-create-schema.sql")?;
-    session
-        .query(query, ())
-        .await
-        .map(|_| ())
-        .map_err(From::from)
+    // (The story is added, a cache failure only delays its visibility by the cache TTLs)
+    let Some(redis) = redis else {
+        return Ok(());
+    };
+    let story_key = CacheKey::new(HNSTORY_CACHE, hnstory.id.as_str());
+    if let Err(e) = redis.invalidate(&story_key).await {
+        warn!(
+            "--> HNStory: Failed to invalidate the cached story: {:?}",
+            e
+        );
+    }
+    if let Err(e) = redis.invalidate_namespace(HNSTORIES_CACHE).await {
+        warn!(
+            "--> HNStory: Failed to invalidate the cached stories: {:?}",
+            e
+        );
+    }
+
+    Ok(())
 }
 
-pub async fn add_hnstory(session: &Session, hnstory: HNStory) -> Result<()> {
-    let query = read_sql_file("hnstory/01-add-story.sql")?;
-    session
-        .query(query, hnstory)
-        .await
-        .map(|_| ())
-        .map_err(From::from)
+/// Add all the stories of the hnstory table to the search index and the query tables,
+/// returns the number of stories (e.g., the stories added before the index and the tables).
+/// The stories without `created_at` are dated now. (The cached lists expire after their TTL)
+pub async fn reindex_hnstories(sm: &ScyllaManager) -> Result<usize> {
+    let mut count = 0;
+    let mut paging_state = None;
+    loop {
+        let (stories, next_paging_state) =
+            base::list_all::<HNStoryBmc>(sm, REINDEX_PAGE_SIZE, paging_state).await?;
+        for mut hnstory in stories {
+            if hnstory.created_at.is_none() {
+                hnstory.created_at = Some(OffsetDateTime::now_utc().unix_timestamp());
+                base::insert::<HNStoryBmc>(sm, &hnstory).await?;
+            }
+            index_hnstory(sm, &hnstory).await?;
+            add_hnstory_to_query_tables(sm, &hnstory).await?;
+            count += 1;
+        }
+        info!("--> HNStory: Reindexed {} stories", count);
+
+        match next_paging_state {
+            Some(next_paging_state) => paging_state = Some(next_paging_state),
+            None => return Ok(count),
+        }
+    }
 }
 
 pub async fn select_hnstory(
-    session: &Session,
+    sm: &ScyllaManager,
     redis: &RedisManager,
     id: String,
 ) -> Result<Vec<HNStory>> {
-    let cache_key = format!("hnstory:{}", id);
-    if let Ok(Some(cached)) = redis.get::<Vec<HNStory>>(&cache_key).await {
-        return Ok(cached);
-    }
-
-    let query = read_sql_file("hnstory/00-select-story.sql")?;
-    let result = session
-        .query(query, (id,))
-        .await?
-        .rows
-        .unwrap_or_default()
-        .into_typed::<HNStory>()
-        .map(|v| v.map_err(From::from))
-        .collect::<Result<Vec<HNStory>>>()?;
-
-    if !result.is_empty() {
-        redis.set(&cache_key, &result, 3600).await.ok(); // 1시간 캐시
-    }
-
-    Ok(result)
-}
-
-pub async fn select_all_hnstories(session: &Session) -> Result<Vec<HNStory>> {
-    let query = read_sql_file("hnstory/02-select-all-stories.sql")?;
-    session
-        .query(query, ())
-        .await?
-        .rows
-        .unwrap_or_default()
-        .into_typed::<HNStory>()
-        .map(|v| v.map_err(From::from))
-        .collect()
+    let cache_key = CacheKey::new(HNSTORY_CACHE, id.as_str());
+    redis
+        .get_or_load(&cache_key, || async {
+            let story = base::get::<HNStoryBmc>(sm, (id.as_str(),)).await?;
+            Ok(story.into_iter().collect())
+        })
+        .await
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct PagingState(pub String);
-
-impl PagingState {
-    pub fn new(encoded: String) -> Self {
-        PagingState(encoded)
-    }
-
-    pub fn from_bytes(bytes: &Bytes) -> Self {
-        PagingState(BASE64.encode(bytes))
-    }
-
-    pub fn into_bytes(&self) -> Result<Bytes> {
-        BASE64
-            .decode(&self.0)
-            .map(Bytes::from)
-            .map_err(Error::Base64)
-    }
+/// The first stories (100 at most)
+pub async fn select_all_hnstories(sm: &ScyllaManager) -> Result<Vec<HNStory>> {
+    let (stories, _) = base::list_all::<HNStoryBmc>(sm, 100, None).await?;
+    Ok(stories)
 }
 
 pub async fn select_all_hnstories_with_pagination(
-    session: &Session,
+    sm: &ScyllaManager,
     redis: &RedisManager,
     page_size: i32,
     paging_state: Option<PagingState>,
 ) -> Result<(Vec<HNStory>, Option<PagingState>)> {
     debug!("--> HNStory: Selecting HNStories with pagination");
-    let query_str = read_sql_file("hnstory/03-select-stories-with-pagination.sql")?;
-
     debug!("--> HNStory: page_size: {}", page_size);
     debug!("--> HNStory: paging_state: {:?}", paging_state);
 
-    // Prepare the statement
-    let prepared: PreparedStatement = session
-        .prepare(Query::new(query_str).with_page_size(page_size))
-        .await?;
-
-    // Generate a unique cache key with paging state
-    let cache_key = match &paging_state {
-        Some(state) => format!("hnstories:page_size:{}:paging_state:{}", page_size, state.0),
-        None => format!("hnstories:page_size:{}:first_page", page_size),
-    };
-
-    // Check if the cached result exists
-    if let Ok(Some(cached)) = redis
-        .get::<(Vec<HNStory>, Option<PagingState>)>(&cache_key)
-        .await
-    {
-        debug!(
-            "--> HNStory: Data fetched directly from Redis. Cache Key: {}",
-            cache_key
-        );
-        return Ok(cached);
-    }
-
-    // If there is no data in Redis, fetch data from the backend
-    info!("--> HNStory: No data in Redis. Fetching data from the backend.");
-
-    // Execute the query with paging state
-    let result = session
-        .execute_paged(
-            &prepared,
-            &[],
-            paging_state.as_ref().and_then(|ps| ps.into_bytes().ok()),
-        )
-        .await?;
-
-    // Convert rows to HNStory instances with improved error handling and logging
-    let stories: Vec<HNStory> = result
-        .rows
-        .unwrap_or_default()
-        .into_typed::<HNStory>()
-        .map(|row_result| match row_result {
-            Ok(story) => {
-                debug!("--> HNStory: Successfully parsed HNStory");
-                Ok(story)
-            }
-            Err(e) => {
-                error!("--> HNStory: Error converting row to HNStory: {:?}", e);
-                Err(Error::from(e))
-            }
-        })
-        .collect::<Result<Vec<HNStory>>>()?;
-
-    let new_paging_state = result.paging_state.as_ref().map(PagingState::from_bytes);
-
-    let result = (stories, new_paging_state);
+    // A unique cache key with paging state
+    let cache_key = CacheKey::new(
+        HNSTORIES_CACHE,
+        match &paging_state {
+            Some(state) => format!("page_size:{}:paging_state:{}", page_size, state.0),
+            None => format!("page_size:{}:first_page", page_size),
+        },
+    );
 
-    // Cache the result
     redis
-        .set(&cache_key, &result, REDIS_CACHE_DURATION)
+        .get_or_load(&cache_key, || async {
+            let (stories, new_paging_state) =
+                base::list_all::<HNStoryBmc>(sm, page_size, paging_state).await?;
+            debug!("--> HNStory: Fetched {} HNStories", stories.len());
+            Ok((stories, new_paging_state))
+        })
         .await
-        .ok();
-    debug!(
-        "--> HNStory: Backend data fetched. Cache Key: {}",
-        cache_key
-    );
-
-    Ok(result)
-}
-
-fn read_sql_file(file_name: &str) -> Result<String> {
-    debug!("--> HNStory: Reading SQL file: {}", file_name);
-    let project_root = find_project_root()?;
-    let sql_file_path = project_root.join(SQL_DIR).join(file_name);
-    fs::read_to_string(sql_file_path).map_err(Error::from)
-}
-
-fn find_project_root() -> Result<PathBuf> {
-    // First, check environment variable
-    if let Ok(project_root) = env::var("PROJECT_ROOT") {
-        let path = PathBuf::from(project_root);
-        if path.exists() {
-            return Ok(path);
-        }
-    }
-
-    // Check common container application root directories
-    let possible_roots = vec!["/usr/src/app", "/app", "/home/app", "/opt/app"];
-
-    for root in possible_roots {
-        let path = PathBuf::from(root);
-        if is_project_root(&path) {
-            return Ok(path);
-        }
-    }
-
-    // If all else fails, use the current directory
-    env::current_dir().map_err(Error::from)
-}
-
-fn is_project_root(path: &Path) -> bool {
-    path.join("Cargo.toml").exists()
-        || path.join(".git").exists()
-        || path.join("sql").exists()
-        || path.join("crates").exists()
 }
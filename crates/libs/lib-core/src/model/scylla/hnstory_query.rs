@@ -0,0 +1,514 @@
+//! Browsing the HN stories by tag, by author, by minimum points and newest first.
+//! The hnstory table is keyed by id only, so `add_hnstory` also writes each story to query tables,
+//! with one partition per tag, author or UTC day, ordered by `created_at` (newest first).
+//! (The stories stored before the query tables are added by `reindex_hnstories`)
+
+use crate::model::redis_cache::{CacheKey, RedisManager};
+use crate::model::scylla::base::{self, ScyllaBmc};
+use crate::model::scylla::cql::Statements;
+use crate::model::scylla::error::{Error, Result};
+use crate::model::scylla::hnstory::{HNStory, HNStoryBmc, PagingState, HNSTORIES_CACHE};
+use crate::model::scylla::ScyllaManager;
+use scylla::prepared_statement::PreparedStatement;
+use scylla::{Bytes, FromRow, IntoTypedRows, SerializeRow};
+use serde::{Deserialize, Serialize};
+use time::{Date, Duration, OffsetDateTime};
+
+/// The default window of the newest stories (without `since`), in days
+const NEWEST_DAYS_DEFAULT: i64 = 30;
+/// The default window of the top stories (without `since`), in days
+const TOP_DAYS_DEFAULT: i64 = 7;
+/// The oldest `since` of the newest stories, and of the top stories, in days
+/// (an older `since` is moved to this limit)
+const NEWEST_DAYS_MAX: i64 = 365;
+const TOP_DAYS_MAX: i64 = 31;
+/// The top stories are ranked among this number of stories at most
+const TOP_SCAN_MAX: usize = 1000;
+/// Number of queries of a page of the newest stories at most
+/// (the page is short if the stories are sparse, its paging state continues it)
+const PAGE_QUERIES_MAX: usize = 32;
+
+/// The story filters (all optional, combined with AND)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HNStoryFilter {
+    pub tag: Option<String>,
+    pub author: Option<String>,
+    pub min_points: Option<i32>,
+    /// Unix time (seconds) of the oldest story
+    /// (365 days ago at most for the newest stories, 31 days for the top ones)
+    pub since: Option<i64>,
+}
+
+impl HNStoryFilter {
+    fn matches(&self, story: &HNStory) -> bool {
+        let tag_ok = self.tag.as_ref().is_none_or(|tag| {
+            story
+                .tags
+                .iter()
+                .flatten()
+                .any(|story_tag| story_tag.to_lowercase() == *tag)
+        });
+        let author_ok = self
+            .author
+            .as_ref()
+            .is_none_or(|author| story.author == *author);
+
+        tag_ok && author_ok
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum HNStorySort {
+    /// Newest first (paged)
+    #[default]
+    Newest,
+    /// Most points first, among the stories since `since`
+    /// (a week by default, a month at most, not paged)
+    Top,
+}
+
+/// A story in the partition of one of its tags (lowercase)
+#[derive(SerializeRow, FromRow)]
+struct HNStoryByTag {
+    tag: String,
+    id: String,
+    title: String,
+    author: String,
+    url: Option<String>,
+    story_text: Option<String>,
+    tags: Option<Vec<String>>,
+    points: i32,
+    created_at: i64,
+}
+
+/// The stories, by tag (newest first)
+struct HNStoryByTagBmc;
+
+impl ScyllaBmc for HNStoryByTagBmc {
+    const TABLE: &'static str = "hnstory_by_tag";
+    const PARTITION_KEY: &'static [&'static str] = &["tag"];
+    const CLUSTERING_KEY: &'static [&'static str] = &["created_at", "id"];
+    const COLUMNS: &'static [&'static str] = &[
+        "tag",
+        "id",
+        "title",
+        "author",
+        "url",
+        "story_text",
+        "tags",
+        "points",
+        "created_at",
+    ];
+    type Row = HNStoryByTag;
+}
+
+/// The stories, by author (newest first)
+struct HNStoryByAuthorBmc;
+
+impl ScyllaBmc for HNStoryByAuthorBmc {
+    const TABLE: &'static str = "hnstory_by_author";
+    const PARTITION_KEY: &'static [&'static str] = &["author"];
+    const CLUSTERING_KEY: &'static [&'static str] = &["created_at", "id"];
+    const COLUMNS: &'static [&'static str] = HNStoryBmc::COLUMNS;
+    type Row = HNStory;
+}
+
+/// A story in the partition of its UTC day (e.g., `2024-06-30`)
+#[derive(SerializeRow, FromRow)]
+struct HNStoryByDay {
+    day: String,
+    id: String,
+    title: String,
+    author: String,
+    url: Option<String>,
+    story_text: Option<String>,
+    tags: Option<Vec<String>>,
+    points: i32,
+    created_at: i64,
+}
+
+/// The stories, by day (newest first)
+struct HNStoryByDayBmc;
+
+impl ScyllaBmc for HNStoryByDayBmc {
+    const TABLE: &'static str = "hnstory_by_day";
+    const PARTITION_KEY: &'static [&'static str] = &["day"];
+    const CLUSTERING_KEY: &'static [&'static str] = &["created_at", "id"];
+    const COLUMNS: &'static [&'static str] = &[
+        "day",
+        "id",
+        "title",
+        "author",
+        "url",
+        "story_text",
+        "tags",
+        "points",
+        "created_at",
+    ];
+    type Row = HNStoryByDay;
+}
+
+/// The query table partition read for a filter
+enum Partition {
+    Tag(String),
+    Author(String),
+    Days { from: Date, to: Date },
+}
+
+impl Partition {
+    fn statement<'a>(&self, statements: &'a Statements) -> &'a PreparedStatement {
+        match self {
+            Partition::Tag(_) => &statements.select_stories_by_tag,
+            Partition::Author(_) => &statements.select_stories_by_author,
+            Partition::Days { .. } => &statements.select_stories_by_day,
+        }
+    }
+}
+
+/// Add the story to the query tables
+/// (`created_at` is set by `add_hnstory` and `reindex_hnstories`)
+pub async fn add_hnstory_to_query_tables(sm: &ScyllaManager, hnstory: &HNStory) -> Result<()> {
+    let created_at = hnstory.created_at.unwrap_or_default();
+
+    let mut tags: Vec<String> = hnstory
+        .tags
+        .iter()
+        .flatten()
+        .map(|tag| tag.to_lowercase())
+        .collect();
+    tags.sort();
+    tags.dedup();
+    for tag in tags {
+        let by_tag = HNStoryByTag {
+            tag,
+            id: hnstory.id.clone(),
+            title: hnstory.title.clone(),
+            author: hnstory.author.clone(),
+            url: hnstory.url.clone(),
+            story_text: hnstory.story_text.clone(),
+            tags: hnstory.tags.clone(),
+            points: hnstory.points,
+            created_at,
+        };
+        base::insert::<HNStoryByTagBmc>(sm, &by_tag).await?;
+    }
+
+    base::insert::<HNStoryByAuthorBmc>(sm, hnstory).await?;
+
+    let by_day = HNStoryByDay {
+        day: day_of(created_at).to_string(),
+        id: hnstory.id.clone(),
+        title: hnstory.title.clone(),
+        author: hnstory.author.clone(),
+        url: hnstory.url.clone(),
+        story_text: hnstory.story_text.clone(),
+        tags: hnstory.tags.clone(),
+        points: hnstory.points,
+        created_at,
+    };
+    base::insert::<HNStoryByDayBmc>(sm, &by_day).await?;
+
+    Ok(())
+}
+
+/// Select the stories matching the filter.
+/// Newest: a page of `page_size` stories (pass the returned paging state for the next page).
+/// A page may be short (even empty) when the matching stories are sparse,
+/// the last page has no paging state.
+/// Top: the `page_size` stories with the most points (no next page).
+pub async fn select_hnstories_by(
+    sm: &ScyllaManager,
+    redis: &RedisManager,
+    filter: HNStoryFilter,
+    sort: HNStorySort,
+    page_size: i32,
+    paging_state: Option<PagingState>,
+) -> Result<(Vec<HNStory>, Option<PagingState>)> {
+    let filter = HNStoryFilter {
+        tag: filter.tag.map(|tag| tag.to_lowercase()),
+        ..filter
+    };
+
+    let cache_key = CacheKey::new(
+        HNSTORIES_CACHE,
+        format!(
+            "query:{}:sort:{:?}:page_size:{}:paging_state:{}",
+            serde_json::to_string(&filter).unwrap_or_default(),
+            sort,
+            page_size,
+            paging_state
+                .as_ref()
+                .map_or("first_page", |ps| ps.0.as_str())
+        ),
+    );
+    redis
+        .get_or_load(&cache_key, || {
+            select_uncached(sm, filter, sort, page_size, paging_state)
+        })
+        .await
+}
+
+async fn select_uncached(
+    sm: &ScyllaManager,
+    filter: HNStoryFilter,
+    sort: HNStorySort,
+    page_size: i32,
+    paging_state: Option<PagingState>,
+) -> Result<(Vec<HNStory>, Option<PagingState>)> {
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    let (default_days, max_days) = match sort {
+        HNStorySort::Newest => (NEWEST_DAYS_DEFAULT, NEWEST_DAYS_MAX),
+        HNStorySort::Top => (TOP_DAYS_DEFAULT, TOP_DAYS_MAX),
+    };
+    // (The day partitions of the window are read, one query per day)
+    let since = filter
+        .since
+        .unwrap_or(now - Duration::days(default_days).whole_seconds())
+        .max(now - Duration::days(max_days).whole_seconds());
+    let partition = match (&filter.tag, &filter.author) {
+        (Some(tag), _) => Partition::Tag(tag.clone()),
+        (None, Some(author)) => Partition::Author(author.clone()),
+        (None, None) => Partition::Days {
+            from: day_of(now),
+            to: day_of(since),
+        },
+    };
+
+    let page_size = page_size.max(1) as usize;
+    match sort {
+        HNStorySort::Newest => {
+            select_newest(sm, &filter, &partition, since, page_size, paging_state).await
+        }
+        HNStorySort::Top => Ok((
+            select_top(sm, &filter, &partition, since, page_size).await?,
+            None,
+        )),
+    }
+}
+
+/// A page of the newest stories (`PAGE_QUERIES_MAX` queries at most).
+/// (For the day partitions, the paging state is prefixed by the julian day of the page end)
+async fn select_newest(
+    sm: &ScyllaManager,
+    filter: &HNStoryFilter,
+    partition: &Partition,
+    since: i64,
+    page_size: usize,
+    paging_state: Option<PagingState>,
+) -> Result<(Vec<HNStory>, Option<PagingState>)> {
+    let session = sm.session();
+    let mut prepared = partition.statement(sm.statements()).clone();
+    let min_points = filter.min_points.unwrap_or(i32::MIN);
+
+    match partition {
+        Partition::Tag(key) | Partition::Author(key) => {
+            let mut state = paging_state.map(|ps| ps.into_bytes()).transpose()?;
+
+            // (The points and the other filter are checked on the read rows, read until full)
+            let mut stories = Vec::new();
+            for _ in 0..PAGE_QUERIES_MAX {
+                prepared.set_page_size((page_size - stories.len()) as i32);
+                let result = session
+                    .execute_paged(&prepared, (key, since, min_points), state)
+                    .await?;
+                state = result.paging_state;
+                stories.extend(into_stories(result.rows, filter)?);
+                if state.is_none() || stories.len() >= page_size {
+                    break;
+                }
+            }
+
+            Ok((stories, state.as_ref().map(PagingState::from_bytes)))
+        }
+        Partition::Days { from, to } => {
+            let (mut day, mut state) = match paging_state {
+                Some(paging_state) => parse_day_paging_state(&paging_state, *from, *to)?,
+                None => (*from, None),
+            };
+
+            let mut stories = Vec::new();
+            for _ in 0..PAGE_QUERIES_MAX {
+                prepared.set_page_size((page_size - stories.len()) as i32);
+                let result = session
+                    .execute_paged(&prepared, (day.to_string(), since, min_points), state)
+                    .await?;
+                state = result.paging_state;
+                stories.extend(into_stories(result.rows, filter)?);
+                // The day is fully read, continue with the previous one
+                if state.is_none() {
+                    day = day.previous_day().unwrap_or(Date::MIN);
+                    if day < *to {
+                        return Ok((stories, None));
+                    }
+                }
+                if stories.len() >= page_size {
+                    break;
+                }
+            }
+
+            Ok((stories, Some(day_paging_state(day, state.as_ref()))))
+        }
+    }
+}
+
+/// The stories with the most points (then the newest)
+async fn select_top(
+    sm: &ScyllaManager,
+    filter: &HNStoryFilter,
+    partition: &Partition,
+    since: i64,
+    limit: usize,
+) -> Result<Vec<HNStory>> {
+    let session = sm.session();
+    let mut prepared = partition.statement(sm.statements()).clone();
+    prepared.set_page_size(TOP_SCAN_MAX as i32);
+    let min_points = filter.min_points.unwrap_or(i32::MIN);
+
+    let mut stories = Vec::new();
+    match partition {
+        Partition::Tag(key) | Partition::Author(key) => {
+            let result = session
+                .execute_paged(&prepared, (key, since, min_points), None)
+                .await?;
+            stories.extend(into_stories(result.rows, filter)?);
+        }
+        Partition::Days { from, to } => {
+            let mut day = *from;
+            while day >= *to && stories.len() < TOP_SCAN_MAX {
+                let result = session
+                    .execute_paged(&prepared, (day.to_string(), since, min_points), None)
+                    .await?;
+                stories.extend(into_stories(result.rows, filter)?);
+                day = day.previous_day().unwrap_or(Date::MIN);
+            }
+        }
+    }
+
+    stories.sort_by(|a, b| {
+        b.points
+            .cmp(&a.points)
+            .then_with(|| b.created_at.cmp(&a.created_at))
+    });
+    stories.truncate(limit);
+
+    Ok(stories)
+}
+
+// region:    --- Support
+fn into_stories(
+    rows: Option<Vec<scylla::frame::response::result::Row>>,
+    filter: &HNStoryFilter,
+) -> Result<Vec<HNStory>> {
+    let mut stories = Vec::new();
+    for story in rows.unwrap_or_default().into_typed::<HNStory>() {
+        let story = story?;
+        // The partition is of the tag (or author), the other filter is checked here
+        if filter.matches(&story) {
+            stories.push(story);
+        }
+    }
+
+    Ok(stories)
+}
+
+/// The UTC day of a unix time (seconds)
+fn day_of(unix_time: i64) -> Date {
+    OffsetDateTime::from_unix_timestamp(unix_time)
+        .unwrap_or(OffsetDateTime::UNIX_EPOCH)
+        .date()
+}
+
+fn day_paging_state(day: Date, state: Option<&Bytes>) -> PagingState {
+    let state = state
+        .map(PagingState::from_bytes)
+        .unwrap_or(PagingState::new(String::new()));
+    PagingState::new(format!("{}:{}", day.to_julian_day(), state.0))
+}
+
+/// The day and the driver paging state
+/// (the day is from `from` back to `to`, only the days of the window are read)
+fn parse_day_paging_state(
+    paging_state: &PagingState,
+    from: Date,
+    to: Date,
+) -> Result<(Date, Option<Bytes>)> {
+    let invalid = || Error::PagingStateInvalid(paging_state.0.clone());
+
+    let (day, state) = paging_state.0.split_once(':').ok_or_else(invalid)?;
+    let day = day
+        .parse::<i32>()
+        .ok()
+        .and_then(|day| Date::from_julian_day(day).ok())
+        .filter(|day| (to..=from).contains(day))
+        .ok_or_else(invalid)?;
+    let state = match state {
+        "" => None,
+        state => Some(PagingState::new(state.to_string()).into_bytes()?),
+    };
+
+    Ok((day, state))
+}
+// endregion: --- Support
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fx_story(tags: &[&str], author: &str) -> HNStory {
+        HNStory {
+            id: "1".to_string(),
+            title: "title".to_string(),
+            author: author.to_string(),
+            url: None,
+            story_text: None,
+            tags: Some(tags.iter().map(|tag| tag.to_string()).collect()),
+            points: 10,
+            created_at: Some(0),
+        }
+    }
+
+    #[test]
+    fn test_filter_matches_ok() {
+        let filter = HNStoryFilter {
+            tag: Some("rust".to_string()),
+            author: Some("pg".to_string()),
+            ..Default::default()
+        };
+
+        assert!(filter.matches(&fx_story(&["story", "Rust"], "pg")));
+        assert!(!filter.matches(&fx_story(&["story"], "pg")));
+        assert!(!filter.matches(&fx_story(&["rust"], "dang")));
+    }
+
+    #[test]
+    fn test_day_paging_state_ok() -> Result<()> {
+        let fx_day = day_of(1_719_705_600); // 2024-06-30
+        let fx_state = Bytes::from_static(b"state");
+        let (fx_from, fx_to) = (fx_day, day_of(1_719_100_800)); // 2024-06-23
+        let parse =
+            |paging_state: &PagingState| parse_day_paging_state(paging_state, fx_from, fx_to);
+
+        let (day, state) = parse(&day_paging_state(fx_day, Some(&fx_state)))?;
+        let (day_start, state_start) = parse(&day_paging_state(fx_day, None))?;
+        let res_invalid = parse(&PagingState::new("invalid".to_string()));
+        // (e.g., a forged day, year 9999 or 1970, would read the partitions up to the window)
+        let res_after = parse(&PagingState::new("5373484:".to_string()));
+        let res_before = parse(&day_paging_state(fx_to.previous_day().unwrap(), None));
+
+        assert_eq!(fx_day.to_string(), "2024-06-30");
+        assert_eq!((day, state), (fx_day, Some(fx_state)));
+        assert_eq!((day_start, state_start), (fx_day, None));
+        for res in [res_invalid, res_after, res_before] {
+            assert!(
+                matches!(res, Err(Error::PagingStateInvalid(_))),
+                "PagingStateInvalid not matching"
+            );
+        }
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
@@ -0,0 +1,311 @@
+//! Full-text search of the HN stories (title, story_text and author).
+//! ScyllaDB has no full-text index, so the stories are indexed in an inverted index table
+//! (`hnstory_term`: the stories of each term) when added, see `index_hnstory`.
+//! (A story re-added with other texts keeps its previous terms until reindexed)
+//!
+//! The stories added before the index, e.g., before the search was deployed,
+//! are indexed by `reindex_hnstories` (run by the `hnstory-reindex` tool).
+//! A term matching more than `TERM_STORIES_MAX` stories only scores the first ones (by story id).
+
+use crate::model::redis_cache::{CacheKey, RedisManager};
+use crate::model::scylla::error::Result;
+use crate::model::scylla::hnstory::{select_hnstory, HNStory, HNSTORIES_CACHE};
+use crate::model::scylla::ScyllaManager;
+use scylla::IntoTypedRows;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use tracing::warn;
+
+/// Weight of a term occurrence, by field
+const TITLE_WEIGHT: i32 = 3;
+const AUTHOR_WEIGHT: i32 = 2;
+const STORY_TEXT_WEIGHT: i32 = 1;
+
+/// Number of stories of a term read per query, and at most
+const TERM_STORIES_PAGE_SIZE: i32 = 1000;
+const TERM_STORIES_MAX: usize = 20_000;
+
+/// Number of words of the story_text highlight around the first match
+const SNIPPET_WORDS_BEFORE: usize = 10;
+const SNIPPET_WORDS_AFTER: usize = 20;
+
+/// The words too common to be searched
+const STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "by", "for", "from", "in", "is", "it", "of", "on",
+    "or", "that", "the", "this", "to", "was", "with",
+];
+
+/// A story matching a search, with its relevance and the matches highlighted (`<mark>..</mark>`)
+/// (The highlights are HTML: the story text is escaped)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct HNStorySearchHit {
+    #[serde(flatten)]
+    pub story: HNStory,
+    /// The higher, the more relevant (the stories matching more terms first)
+    pub rank: f32,
+    pub title_highlight: String,
+    /// An excerpt around the first match
+    pub story_text_highlight: Option<String>,
+}
+
+/// Add the terms of the story to the search index
+pub async fn index_hnstory(sm: &ScyllaManager, hnstory: &HNStory) -> Result<()> {
+    let mut weights: HashMap<String, i32> = HashMap::new();
+    let fields = [
+        (Some(hnstory.title.as_str()), TITLE_WEIGHT),
+        (Some(hnstory.author.as_str()), AUTHOR_WEIGHT),
+        (hnstory.story_text.as_deref(), STORY_TEXT_WEIGHT),
+    ];
+    for (text, weight) in fields {
+        for term in tokenize(text.unwrap_or_default()) {
+            *weights.entry(term).or_default() += weight;
+        }
+    }
+
+    for (term, weight) in weights {
+        sm.session()
+            .execute(
+                &sm.statements().add_story_term,
+                (term, hnstory.id.as_str(), weight),
+            )
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Search the stories, the most relevant first
+pub async fn search_hnstories(
+    sm: &ScyllaManager,
+    redis: &RedisManager,
+    text: &str,
+    limit: usize,
+) -> Result<Vec<HNStorySearchHit>> {
+    let terms: BTreeSet<String> = tokenize(text).collect();
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let cache_key = CacheKey::new(
+        HNSTORIES_CACHE,
+        format!(
+            "search:limit:{}:terms:{}",
+            limit,
+            terms.iter().cloned().collect::<Vec<_>>().join(",")
+        ),
+    );
+    redis
+        .get_or_load(&cache_key, || search_uncached(sm, redis, &terms, limit))
+        .await
+}
+
+async fn search_uncached(
+    sm: &ScyllaManager,
+    redis: &RedisManager,
+    terms: &BTreeSet<String>,
+    limit: usize,
+) -> Result<Vec<HNStorySearchHit>> {
+    // Score the stories: (number of matched terms, sum of the term weights)
+    let mut scores: HashMap<String, (usize, i32)> = HashMap::new();
+    for term in terms.iter() {
+        for (story_id, weight) in select_term_stories(sm, term).await? {
+            let score = scores.entry(story_id).or_default();
+            score.0 += 1;
+            score.1 += weight;
+        }
+    }
+    let mut scores: Vec<(String, (usize, i32))> = scores.into_iter().collect();
+    scores.sort_by(|(id_a, score_a), (id_b, score_b)| {
+        score_b.cmp(score_a).then_with(|| id_a.cmp(id_b))
+    });
+
+    let mut hits = Vec::new();
+    for (story_id, (matched, weight)) in scores.into_iter().take(limit) {
+        let Some(story) = select_hnstory(sm, redis, story_id).await?.pop() else {
+            continue;
+        };
+        hits.push(HNStorySearchHit {
+            rank: (matched as f32) * (weight as f32),
+            title_highlight: highlight(&story.title, terms),
+            story_text_highlight: story
+                .story_text
+                .as_deref()
+                .map(|text| highlight(&snippet(text, terms), terms)),
+            story,
+        });
+    }
+
+    Ok(hits)
+}
+
+/// The stories of the term, with their weight (`TERM_STORIES_MAX` at most)
+async fn select_term_stories(sm: &ScyllaManager, term: &str) -> Result<Vec<(String, i32)>> {
+    let mut prepared = sm.statements().select_term_stories.clone();
+    prepared.set_page_size(TERM_STORIES_PAGE_SIZE);
+
+    let mut stories = Vec::new();
+    let mut paging_state = None;
+    loop {
+        let result = sm
+            .session()
+            .execute_paged(&prepared, (term,), paging_state)
+            .await?;
+        paging_state = result.paging_state.clone();
+        for row in result
+            .rows
+            .unwrap_or_default()
+            .into_typed::<(String, i32)>()
+        {
+            stories.push(row?);
+        }
+
+        if paging_state.is_none() {
+            return Ok(stories);
+        }
+        if stories.len() >= TERM_STORIES_MAX {
+            warn!(
+                "--> HNStorySearch: The term {} matches more than {} stories, \
+                 only the first are scored",
+                term, TERM_STORIES_MAX
+            );
+            stories.truncate(TERM_STORIES_MAX);
+            return Ok(stories);
+        }
+    }
+}
+
+// region:    --- Text Utils
+/// The searchable terms of a text (lowercase words, without the stop words)
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() > 1)
+        .map(|word| word.to_lowercase())
+        .filter(|word| !STOP_WORDS.contains(&word.as_str()))
+}
+
+/// Wrap the words matching the terms with `<mark>..</mark>` (the text is HTML-escaped)
+fn highlight(text: &str, terms: &BTreeSet<String>) -> String {
+    let mut highlighted = String::with_capacity(text.len());
+    let mut word_start = None;
+    let push_word = |highlighted: &mut String, word: &str| {
+        if terms.contains(&word.to_lowercase()) {
+            highlighted.push_str("<mark>");
+            push_escaped(highlighted, word);
+            highlighted.push_str("</mark>");
+        } else {
+            push_escaped(highlighted, word);
+        }
+    };
+
+    for (i, c) in text.char_indices() {
+        match (c.is_alphanumeric(), word_start) {
+            (true, None) => word_start = Some(i),
+            (false, Some(start)) => {
+                push_word(&mut highlighted, &text[start..i]);
+                push_escaped(&mut highlighted, &text[i..i + c.len_utf8()]);
+                word_start = None;
+            }
+            (false, None) => push_escaped(&mut highlighted, &text[i..i + c.len_utf8()]),
+            (true, Some(_)) => {}
+        }
+    }
+    if let Some(start) = word_start {
+        push_word(&mut highlighted, &text[start..]);
+    }
+
+    highlighted
+}
+
+/// Push the text, HTML-escaped
+fn push_escaped(out: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+}
+
+/// The words of the text around its first match (the text start if no match)
+fn snippet(text: &str, terms: &BTreeSet<String>) -> String {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let first_match = words
+        .iter()
+        .position(|word| tokenize(word).any(|term| terms.contains(&term)))
+        .unwrap_or(0);
+    let start = first_match.saturating_sub(SNIPPET_WORDS_BEFORE);
+    let end = (first_match + SNIPPET_WORDS_AFTER).min(words.len());
+
+    let mut snippet = words[start..end].join(" ");
+    if start > 0 {
+        snippet.insert_str(0, "… ");
+    }
+    if end < words.len() {
+        snippet.push_str(" …");
+    }
+
+    snippet
+}
+// endregion: --- Text Utils
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terms(text: &str) -> BTreeSet<String> {
+        tokenize(text).collect()
+    }
+
+    #[test]
+    fn test_tokenize_ok() {
+        let tokens: Vec<String> = tokenize("Show HN: The Rust-based DB, in 2024!").collect();
+
+        assert_eq!(tokens, &["show", "hn", "rust", "based", "db", "2024"]);
+    }
+
+    #[test]
+    fn test_highlight_ok() {
+        let highlighted = highlight("Rust: rust-lang, RUSTY (rust)", &terms("rust"));
+
+        assert_eq!(
+            highlighted,
+            "<mark>Rust</mark>: <mark>rust</mark>-lang, RUSTY (<mark>rust</mark>)"
+        );
+    }
+
+    #[test]
+    fn test_highlight_escaped_ok() {
+        let highlighted = highlight(
+            "<script>alert('rust')</script> & \"rust\"",
+            &terms("rust script"),
+        );
+
+        assert_eq!(
+            highlighted,
+            "&lt;<mark>script</mark>&gt;alert(&#39;<mark>rust</mark>&#39;)&lt;/<mark>script</mark>&gt; \
+             &amp; &quot;<mark>rust</mark>&quot;"
+        );
+    }
+
+    #[test]
+    fn test_snippet_ok() {
+        let text = (0..50)
+            .map(|i| format!("w{i}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let fx_snippet = snippet(&text, &terms("w25"));
+        let no_match_snippet = snippet("a short text", &terms("rust"));
+
+        assert!(fx_snippet.starts_with("… w15 "), "{fx_snippet}");
+        assert!(fx_snippet.ends_with(" w44 …"), "{fx_snippet}");
+        assert_eq!(no_match_snippet, "a short text");
+    }
+}
+// endregion: --- Tests
@@ -0,0 +1,417 @@
+//! Versioned schema migrations for the Scylla database.
+//!
+//! Migrations are the `sql/csql/migrations/<version>_<name>.sql` files (one CQL statement each).
+//! They are embedded in the binary at compile time (see `MIGRATIONS`), applied in version order,
+//! and recorded (with their checksum) in the `schema_migration` table of the keyspace.
+//! The keyspace name and replication come from the `CoreConfig`.
+//! (The `schema_version` table of the previous schema versioning is dropped by a migration)
+
+use crate::config::core_config;
+use crate::model::scylla::cql::cql;
+use crate::model::scylla::error::{Error, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use scylla::frame::response::result::CqlValue;
+use scylla::{IntoTypedRows, Session};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::time::Duration;
+use time::OffsetDateTime;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// A migration (applied once, in the version order)
+pub struct Migration {
+    pub version: i32,
+    pub name: &'static str,
+    pub cql: &'static str,
+    /// The (table, column) added by the migration (`ALTER TABLE .. ADD` has no `IF NOT EXISTS`).
+    /// If the column already exists (e.g., created before the migrations), it is only recorded.
+    pub adds_column: Option<(&'static str, &'static str)>,
+}
+
+/// The migrations (append only: an applied migration must not be modified)
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "create-hnstory",
+        cql: cql!("migrations/0001_create-hnstory.sql"),
+        adds_column: None,
+    },
+    Migration {
+        version: 2,
+        name: "create-hnstory-term",
+        cql: cql!("migrations/0002_create-hnstory-term.sql"),
+        adds_column: None,
+    },
+    Migration {
+        version: 3,
+        name: "hnstory-created-at",
+        cql: cql!("migrations/0003_hnstory-created-at.sql"),
+        adds_column: Some(("hnstory", "created_at")),
+    },
+    Migration {
+        version: 4,
+        name: "create-hnstory-by-tag",
+        cql: cql!("migrations/0004_create-hnstory-by-tag.sql"),
+        adds_column: None,
+    },
+    Migration {
+        version: 5,
+        name: "create-hnstory-by-author",
+        cql: cql!("migrations/0005_create-hnstory-by-author.sql"),
+        adds_column: None,
+    },
+    Migration {
+        version: 6,
+        name: "create-hnstory-by-day",
+        cql: cql!("migrations/0006_create-hnstory-by-day.sql"),
+        adds_column: None,
+    },
+    Migration {
+        version: 7,
+        name: "drop-schema-version",
+        cql: cql!("migrations/0007_drop-schema-version.sql"),
+        adds_column: None,
+    },
+];
+
+/// The migration lock is released after this time if its runner died
+const LOCK_TTL_SEC: i32 = 300;
+/// Wait for the migration lock at most this number of times (one second apart)
+const LOCK_ATTEMPTS: u32 = 120;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationStatus {
+    pub version: i32,
+    pub name: String,
+    pub applied: bool,
+}
+
+impl Migration {
+    fn checksum(&self) -> String {
+        BASE64.encode(Sha256::digest(self.cql.as_bytes()))
+    }
+}
+
+/// Create the keyspace (if not exists), use it on the session, and apply the pending migrations.
+/// Fails if an already applied migration was modified or is unknown to this binary.
+/// (One runner at a time: the other processes wait for the migration lock)
+pub async fn migrate_up(session: &Session) -> Result<()> {
+    let keyspace = &core_config().SCYLLA_KEYSPACE;
+    info!(
+        "{:<12} - scylla migrate_up - keyspace: {keyspace}",
+        "MIGRATION"
+    );
+
+    let create_keyspace = keyspace_cql(keyspace, &core_config().SCYLLA_REPLICATION)?;
+    session.query(create_keyspace, ()).await?;
+    session.await_schema_agreement().await?;
+    session.use_keyspace(keyspace, false).await?;
+
+    session
+        .query(cql!("schema_migration/00-create-table.sql"), ())
+        .await?;
+    session
+        .query(cql!("schema_migration/01-create-lock-table.sql"), ())
+        .await?;
+    session.await_schema_agreement().await?;
+
+    let owner = Uuid::new_v4().to_string();
+    lock(session, &owner).await?;
+    let res = apply_pending(session).await;
+    // (The migration error is the one returned, the lock expires anyway after LOCK_TTL_SEC)
+    if let Err(e) = unlock(session, &owner).await {
+        warn!("{:<12} - scylla unlock failed: {e:?}", "MIGRATION");
+    }
+
+    res
+}
+
+/// Return the status of each known migration (the keyspace must be used by the session).
+/// Fails if an applied migration does not match the embedded one.
+pub async fn status(session: &Session) -> Result<Vec<MigrationStatus>> {
+    let applied = applied_checksums(session).await?;
+    check_applied(&applied)?;
+
+    Ok(MIGRATIONS
+        .iter()
+        .map(|migration| MigrationStatus {
+            version: migration.version,
+            name: migration.name.to_string(),
+            applied: applied.contains_key(&migration.version),
+        })
+        .collect())
+}
+
+async fn apply_pending(session: &Session) -> Result<()> {
+    // (Read after the lock is taken, another runner may have applied some)
+    let applied = applied_checksums(session).await?;
+    check_applied(&applied)?;
+    let keyspace = &core_config().SCYLLA_KEYSPACE;
+
+    for migration in MIGRATIONS
+        .iter()
+        .filter(|m| !applied.contains_key(&m.version))
+    {
+        info!(
+            "{:<12} - scylla apply - {:04}_{}",
+            "MIGRATION", migration.version, migration.name
+        );
+        let column_exists = match migration.adds_column {
+            Some((table, column)) => column_exists(session, keyspace, table, column).await?,
+            None => false,
+        };
+        if column_exists {
+            info!(
+                "{:<12} - scylla apply - column already exists, recorded only",
+                "MIGRATION"
+            );
+        } else {
+            session.query(migration.cql, ()).await?;
+            session.await_schema_agreement().await?;
+        }
+        session
+            .query(
+                cql!("schema_migration/03-add-migration.sql"),
+                (
+                    migration.version,
+                    migration.name,
+                    migration.checksum(),
+                    OffsetDateTime::now_utc().unix_timestamp(),
+                ),
+            )
+            .await?;
+    }
+
+    Ok(())
+}
+
+async fn applied_checksums(session: &Session) -> Result<HashMap<i32, String>> {
+    let mut applied = HashMap::new();
+    let rows = session
+        .query(cql!("schema_migration/02-select-migrations.sql"), ())
+        .await?
+        .rows
+        .unwrap_or_default()
+        .into_typed::<(i32, String)>();
+    for row in rows {
+        let (version, checksum) = row?;
+        applied.insert(version, checksum);
+    }
+
+    Ok(applied)
+}
+
+async fn column_exists(
+    session: &Session,
+    keyspace: &str,
+    table: &str,
+    column: &str,
+) -> Result<bool> {
+    let result = session
+        .query(
+            cql!("schema_migration/06-select-column.sql"),
+            (keyspace, table, column),
+        )
+        .await?;
+
+    Ok(result.rows.is_some_and(|rows| !rows.is_empty()))
+}
+
+/// Check that the applied migrations are the embedded ones
+fn check_applied(applied: &HashMap<i32, String>) -> Result<()> {
+    for (version, checksum) in applied {
+        match MIGRATIONS.iter().find(|m| m.version == *version) {
+            None => return Err(Error::MigrationUnknown { version: *version }),
+            Some(migration) if migration.checksum() != *checksum => {
+                return Err(Error::MigrationChecksumMismatch { version: *version });
+            }
+            Some(_) => {}
+        }
+    }
+
+    Ok(())
+}
+
+// region:    --- Lock
+async fn lock(session: &Session, owner: &str) -> Result<()> {
+    for _ in 0..LOCK_ATTEMPTS {
+        let result = session
+            .query(cql!("schema_migration/04-lock.sql"), (owner, LOCK_TTL_SEC))
+            .await?;
+        // The first column of a lightweight transaction result is `[applied]`
+        let applied = result
+            .rows
+            .and_then(|rows| rows.into_iter().next())
+            .and_then(|row| row.columns.into_iter().next().flatten())
+            .and_then(|value| CqlValue::as_boolean(&value))
+            .unwrap_or(false);
+        if applied {
+            return Ok(());
+        }
+
+        warn!("{:<12} - scylla lock taken, waiting", "MIGRATION");
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+
+    Err(Error::MigrationLockTimeout)
+}
+
+async fn unlock(session: &Session, owner: &str) -> Result<()> {
+    session
+        .query(cql!("schema_migration/05-unlock.sql"), (owner,))
+        .await?;
+
+    Ok(())
+}
+// endregion: --- Lock
+
+// region:    --- Keyspace
+/// The CQL creating the keyspace with the configured replication
+fn keyspace_cql(keyspace: &str, replication: &str) -> Result<String> {
+    let valid_keyspace = !keyspace.is_empty()
+        && keyspace.len() <= 48
+        && keyspace.starts_with(|c: char| c.is_ascii_alphabetic())
+        && keyspace
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid_keyspace {
+        return Err(Error::KeyspaceInvalid(keyspace.to_string()));
+    }
+
+    Ok(cql!("keyspace/00-create-keyspace.sql")
+        .replace("{keyspace}", keyspace)
+        .replace("{replication}", &replication_cql(replication)?))
+}
+
+/// The replication map of a `SERVICE_SCYLLA_REPLICATION` value:
+/// `<class>:<replication_factor>`, or `NetworkTopologyStrategy:<dc>=<factor>,...`
+fn replication_cql(replication: &str) -> Result<String> {
+    let invalid = || Error::ReplicationInvalid(replication.to_string());
+    let is_name = |name: &str| {
+        !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    };
+
+    let (class, factors) = replication.split_once(':').ok_or_else(invalid)?;
+    if !matches!(class, "SimpleStrategy" | "NetworkTopologyStrategy") {
+        return Err(invalid());
+    }
+
+    let mut options = vec![format!("'class': '{class}'")];
+    if let Ok(factor) = factors.parse::<u32>() {
+        options.push(format!("'replication_factor': {factor}"));
+    } else if class == "NetworkTopologyStrategy" {
+        for dc_factor in factors.split(',') {
+            let (dc, factor) = dc_factor.trim().split_once('=').ok_or_else(invalid)?;
+            let factor = factor.parse::<u32>().map_err(|_| invalid())?;
+            if !is_name(dc) {
+                return Err(invalid());
+            }
+            options.push(format!("'{dc}': {factor}"));
+        }
+    } else {
+        return Err(invalid());
+    }
+
+    Ok(format!("{{{}}}", options.join(", ")))
+}
+// endregion: --- Keyspace
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_migrations_ordered_and_embedded_ok() -> Result<()> {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/../../../sql/csql/migrations");
+        let mut files: Vec<String> = fs::read_dir(dir)?
+            .map(|entry| entry.map(|e| e.file_name().to_string_lossy().to_string()))
+            .collect::<std::io::Result<_>>()?;
+        files.sort();
+
+        let embedded: Vec<String> = MIGRATIONS
+            .iter()
+            .map(|m| format!("{:04}_{}.sql", m.version, m.name))
+            .collect();
+
+        // Every migration file is embedded, in the version order
+        assert_eq!(embedded, files);
+        assert!(
+            MIGRATIONS.windows(2).all(|w| w[0].version < w[1].version),
+            "migrations should be ordered by version"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_migrations_adds_column_ok() {
+        // The column adds have no `IF NOT EXISTS`, they are skipped if the column exists
+        for migration in MIGRATIONS {
+            let adds_column =
+                migration.cql.contains("ALTER TABLE") && migration.cql.contains(" ADD ");
+            assert_eq!(
+                adds_column,
+                migration.adds_column.is_some(),
+                "adds_column of migration {}",
+                migration.version
+            );
+        }
+    }
+
+    #[test]
+    fn test_check_applied_err() {
+        let fx_migration = &MIGRATIONS[0];
+        let ok = HashMap::from([(fx_migration.version, fx_migration.checksum())]);
+        let modified = HashMap::from([(fx_migration.version, "modified".to_string())]);
+        let unknown = HashMap::from([(9999, "unknown".to_string())]);
+
+        assert!(check_applied(&ok).is_ok());
+        assert!(matches!(
+            check_applied(&modified),
+            Err(Error::MigrationChecksumMismatch { version: 1 })
+        ));
+        assert!(matches!(
+            check_applied(&unknown),
+            Err(Error::MigrationUnknown { version: 9999 })
+        ));
+    }
+
+    #[test]
+    fn test_keyspace_cql_ok() -> Result<()> {
+        let cql = keyspace_cql("fast_logger", "NetworkTopologyStrategy:dc1=3, dc2=2")?;
+
+        assert!(cql.contains("CREATE KEYSPACE IF NOT EXISTS fast_logger"));
+        assert!(cql.contains("{'class': 'NetworkTopologyStrategy', 'dc1': 3, 'dc2': 2}"));
+        assert_eq!(
+            replication_cql("SimpleStrategy:1")?,
+            "{'class': 'SimpleStrategy', 'replication_factor': 1}"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_keyspace_cql_err_invalid() {
+        for (keyspace, replication) in [
+            ("fast-logger", "SimpleStrategy:1"),
+            ("fast_logger; DROP", "SimpleStrategy:1"),
+            ("fast_logger", "SimpleStrategy"),
+            ("fast_logger", "SimpleStrategy:dc1=3"),
+            ("fast_logger", "OtherStrategy:1"),
+            ("fast_logger", "NetworkTopologyStrategy:dc'1=3"),
+        ] {
+            assert!(
+                keyspace_cql(keyspace, replication).is_err(),
+                "should be invalid: {keyspace} {replication}"
+            );
+        }
+    }
+}
+// endregion: --- Tests
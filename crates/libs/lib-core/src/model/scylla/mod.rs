@@ -1,12 +1,20 @@
-mod base;
+pub mod base;
+mod cql;
 mod error;
 pub mod hnstory;
+pub mod hnstory_query;
+pub mod hnstory_search;
+pub mod migration;
 
 pub use self::error::{Error, Result};
 
+use self::cql::Statements;
+
 use crate::config::core_config;
+use scylla::prepared_statement::PreparedStatement;
 use scylla::{Session, SessionBuilder};
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
 use tracing::debug;
 
 /// Create a new database connection.
@@ -32,25 +40,51 @@ pub async fn db_conn() -> Result<Session> {
 
 pub struct ScyllaManager {
     session: Arc<Session>,
+    statements: Arc<Statements>,
+    // The statements of the generic functions (see `base`), prepared on first use, by CQL
+    prepared: RwLock<HashMap<String, PreparedStatement>>,
 }
 
 impl ScyllaManager {
     // Constructor
+    // (connects, applies the pending migrations and prepares the statements)
     pub async fn new() -> Result<Arc<Self>> {
         let session = Arc::new(db_conn().await?);
-        Ok(Arc::new(ScyllaManager { session }))
+        migration::migrate_up(&session).await?;
+        let statements = Arc::new(Statements::prepare(&session).await?);
+        Ok(Arc::new(ScyllaManager {
+            session,
+            statements,
+            prepared: RwLock::default(),
+        }))
     }
 
     // Return the Session reference
     pub fn session(&self) -> &Session {
         &self.session
     }
-}
 
-// Initialize the scylla database
-pub async fn initialize(session: &Session) -> std::result::Result<(), Error> {
-    hnstory::initialize(session)
-        .await
-        .map_err(|e| Error::ScyllaError(e.to_string()))?;
-    Ok(())
+    // Return the prepared statements
+    pub(in crate::model::scylla) fn statements(&self) -> &Statements {
+        &self.statements
+    }
+
+    // Return the prepared statement of the CQL (prepared once)
+    pub(in crate::model::scylla) async fn prepare(&self, cql: String) -> Result<PreparedStatement> {
+        if let Some(prepared) = self
+            .prepared
+            .read()
+            .ok()
+            .and_then(|prepared| prepared.get(&cql).cloned())
+        {
+            return Ok(prepared);
+        }
+
+        let prepared = self.session.prepare(cql.as_str()).await?;
+        if let Ok(mut cache) = self.prepared.write() {
+            cache.insert(cql, prepared.clone());
+        }
+
+        Ok(prepared)
+    }
 }
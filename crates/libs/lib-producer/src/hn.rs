@@ -18,9 +18,12 @@ pub struct HNSearchResult {
     #[serde(alias = "_tags")]
     pub tags: Option<Vec<String>>,
     pub points: u32,
+    /// Unix time (seconds) of the story creation
+    pub created_at_i: Option<i64>,
 }
 
 /// Fetches the search results from the Hacker News API.
+/// (The lowercase search term is added to the tags of each story, e.g., "rust")
 pub async fn fetch_hn_stories(
     search_term: String,
     search_result_limit: u32,
@@ -32,11 +35,19 @@ pub async fn fetch_hn_stories(
     );
     let client = reqwest::Client::new();
     let request = client.get(url_str).build().unwrap();
-    let json_response = client
+    let mut json_response = client
         .execute(request)
         .await?
         .json::<HackerNewsResponse>()
         .await?;
 
+    let search_tag = search_term.to_lowercase();
+    for story in json_response.hits.iter_mut() {
+        let tags = story.tags.get_or_insert_with(Vec::new);
+        if !tags.contains(&search_tag) {
+            tags.push(search_tag.clone());
+        }
+    }
+
     Ok(json_response)
 }
@@ -15,6 +15,10 @@ pub enum Error {
     RpcFailJsonParams {
         rpc_method: String,
     },
+    RpcPermissionDenied {
+        rpc_method: String,
+        permission: &'static str,
+    },
 
     // -- Modules
     #[from]
@@ -0,0 +1,370 @@
+//! JSON-RPC 2.0 envelope (request, response and error objects).
+//! See <https://www.jsonrpc.org/specification>
+
+use crate::Error;
+use lib_auth::pwd;
+use lib_core::model;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{json, Value};
+
+pub const JSONRPC_VERSION: &str = "2.0";
+
+// region:    --- Error Codes
+// -- Spec codes
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+
+// -- Application codes (implementation-defined server error range)
+pub const PERMISSION_DENIED: i64 = -32003;
+pub const ENTITY_NOT_FOUND: i64 = -32004;
+pub const VERSION_CONFLICT: i64 = -32009;
+pub const USERNAME_NOT_AVAILABLE: i64 = -32010;
+pub const USERNAME_INVALID: i64 = -32011;
+pub const PWD_NOT_MATCHING: i64 = -32012;
+pub const PWD_TOO_WEAK: i64 = -32013;
+// endregion: --- Error Codes
+
+// region:    --- Request
+/// JSON-RPC 2.0 Request
+/// (A request without `id` is a notification, no response is sent back)
+#[derive(Deserialize)]
+pub struct RpcRequest {
+    pub jsonrpc: String,
+    // `Some(Value::Null)` for `"id": null`, `None` when absent
+    #[serde(default, deserialize_with = "deserialize_present")]
+    pub id: Option<Value>,
+    pub method: String,
+    pub params: Option<Value>,
+}
+
+impl RpcRequest {
+    /// Parse one request object of a payload
+    /// (On failure, return the error response to send back)
+    pub fn from_value(value: Value) -> core::result::Result<Self, RpcResponse> {
+        // Get the id first, so the error response can refer to it
+        let id = match value.get("id") {
+            Some(id @ (Value::Null | Value::String(_) | Value::Number(_))) => id.clone(),
+            _ => Value::Null,
+        };
+
+        let rpc_req: RpcRequest = serde_json::from_value(value)
+            .map_err(|ex| RpcResponse::error(id.clone(), RpcError::invalid_request(ex)))?;
+
+        if rpc_req.jsonrpc != JSONRPC_VERSION {
+            return Err(RpcResponse::error(
+                id,
+                RpcError::invalid_request("jsonrpc must be \"2.0\""),
+            ));
+        }
+        if matches!(
+            rpc_req.id,
+            Some(Value::Bool(_) | Value::Array(_) | Value::Object(_))
+        ) {
+            return Err(RpcResponse::error(
+                id,
+                RpcError::invalid_request("id must be a string, a number or null"),
+            ));
+        }
+
+        Ok(rpc_req)
+    }
+
+    pub fn is_notification(&self) -> bool {
+        self.id.is_none()
+    }
+}
+
+fn deserialize_present<'de, D>(deserializer: D) -> core::result::Result<Option<Value>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Value::deserialize(deserializer).map(Some)
+}
+// endregion: --- Request
+
+// region:    --- Response
+/// JSON-RPC 2.0 Response (`result` or `error`)
+#[derive(Debug, Serialize)]
+pub struct RpcResponse {
+    jsonrpc: &'static str,
+    #[serde(flatten)]
+    body: RpcResponseBody,
+    id: Value,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum RpcResponseBody {
+    Result(Value),
+    Error(RpcError),
+}
+
+impl RpcResponse {
+    pub fn result(id: Value, result: Value) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION,
+            body: RpcResponseBody::Result(result),
+            id,
+        }
+    }
+
+    pub fn error(id: Value, error: RpcError) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION,
+            body: RpcResponseBody::Error(error),
+            id,
+        }
+    }
+}
+// endregion: --- Response
+
+// region:    --- Error Object
+/// JSON-RPC 2.0 Error Object
+#[derive(Debug, Serialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl RpcError {
+    fn new(code: i64, message: &'static str, data: Option<Value>) -> Self {
+        Self {
+            code,
+            message,
+            data,
+        }
+    }
+
+    pub fn parse_error() -> Self {
+        Self::new(PARSE_ERROR, "Parse error", None)
+    }
+
+    pub fn invalid_request(detail: impl ToString) -> Self {
+        Self::new(
+            INVALID_REQUEST,
+            "Invalid Request",
+            Some(json!({ "detail": detail.to_string() })),
+        )
+    }
+}
+
+/// From the lib-rpc error to the client error object
+/// (The internal errors are not detailed to the client)
+impl From<&Error> for RpcError {
+    fn from(err: &Error) -> Self {
+        match err {
+            Error::RpcMethodUnknown(method) => Self::new(
+                METHOD_NOT_FOUND,
+                "Method not found",
+                Some(json!({ "method": method })),
+            ),
+            Error::RpcMissingParams { rpc_method } | Error::RpcFailJsonParams { rpc_method } => {
+                Self::new(
+                    INVALID_PARAMS,
+                    "Invalid params",
+                    Some(json!({ "rpc_method": rpc_method })),
+                )
+            }
+            Error::RpcPermissionDenied { permission, .. } => Self::new(
+                PERMISSION_DENIED,
+                "Permission denied",
+                Some(json!({ "permission": permission })),
+            ),
+
+            // -- Model
+            Error::Model(model_error) => Self::from_model_error(model_error),
+
+            // -- Fallback
+            _ => Self::new(INTERNAL_ERROR, "Internal error", None),
+        }
+    }
+}
+
+impl RpcError {
+    fn from_model_error(model_error: &model::psql::Error) -> Self {
+        use model::psql::Error as ModelError;
+
+        match model_error {
+            ModelError::ListLimitOverMax { .. }
+            | ModelError::ListLimitNotPositive { .. }
+            | ModelError::ListCursorInvalid { .. }
+            | ModelError::ListCursorOrderUnsupported
+            | ModelError::BatchSizeOverMax { .. }
+            | ModelError::BatchIdDuplicate { .. }
+            | ModelError::TaskPriorityOutOfRange { .. }
+            | ModelError::TaskDoneStatusConflict { .. }
+            | ModelError::LabelNotInTaskProject { .. }
+            | ModelError::LabelNameInvalid { .. }
+            | ModelError::LabelAlreadyExists { .. } => Self::new(
+                INVALID_PARAMS,
+                "Invalid params",
+                Some(json!({ "detail": model_error.to_string() })),
+            ),
+            ModelError::EntityNotFound { entity, id } => Self::new(
+                ENTITY_NOT_FOUND,
+                "Entity not found",
+                Some(json!({ "entity": entity, "id": id })),
+            ),
+            ModelError::VersionConflict {
+                entity, id, actual, ..
+            } => Self::new(
+                VERSION_CONFLICT,
+                "Version conflict",
+                Some(json!({ "entity": entity, "id": id, "version": actual })),
+            ),
+            // The error of the failing item, with its index in the batch
+            ModelError::BatchItemFailed { index, cause } => {
+                let mut rpc_error = Self::from_model_error(cause);
+                let mut data = match rpc_error.data.take() {
+                    Some(Value::Object(data)) => data,
+                    _ => Default::default(),
+                };
+                data.insert("index".to_string(), json!(index));
+                rpc_error.data = Some(Value::Object(data));
+                rpc_error
+            }
+            ModelError::UserAlreadyExists { username } => Self::new(
+                USERNAME_NOT_AVAILABLE,
+                "Username not available",
+                Some(json!({ "username": username })),
+            ),
+            ModelError::UsernameInvalid { username } => Self::new(
+                USERNAME_INVALID,
+                "Username invalid",
+                Some(json!({ "username": username })),
+            ),
+            ModelError::UserPwdNotMatching { .. } => {
+                Self::new(PWD_NOT_MATCHING, "Password not matching", None)
+            }
+            ModelError::Pwd(
+                pwd_error @ (pwd::Error::PwdTooShort { .. }
+                | pwd::Error::PwdTooLong { .. }
+                | pwd::Error::PwdMissingLetter
+                | pwd::Error::PwdMissingDigit),
+            ) => Self::new(
+                PWD_TOO_WEAK,
+                "Password too weak",
+                Some(json!({ "rule": pwd_error.to_string() })),
+            ),
+
+            // -- Fallback
+            _ => Self::new(INTERNAL_ERROR, "Internal error", None),
+        }
+    }
+}
+// endregion: --- Error Object
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn test_request_from_value_ok() -> Result<()> {
+        let rpc_req = RpcRequest::from_value(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "task.list",
+            "params": {}
+        }))
+        .map_err(|res| anyhow::anyhow!("{res:?}"))?;
+        let notification = RpcRequest::from_value(json!({
+            "jsonrpc": "2.0",
+            "method": "task.list"
+        }))
+        .map_err(|res| anyhow::anyhow!("{res:?}"))?;
+        let null_id = RpcRequest::from_value(json!({
+            "jsonrpc": "2.0",
+            "id": null,
+            "method": "task.list"
+        }))
+        .map_err(|res| anyhow::anyhow!("{res:?}"))?;
+
+        assert_eq!(rpc_req.id, Some(json!(1)));
+        assert!(notification.is_notification());
+        assert!(!null_id.is_notification());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_request_from_value_err_invalid() -> Result<()> {
+        let fx_invalids = [
+            json!({ "id": 1, "method": "task.list" }),
+            json!({ "jsonrpc": "1.0", "id": 1, "method": "task.list" }),
+            json!({ "jsonrpc": "2.0", "id": {}, "method": "task.list" }),
+            json!({ "jsonrpc": "2.0", "id": 1, "method": 1 }),
+            json!(1),
+        ];
+
+        for fx_invalid in fx_invalids {
+            let Err(res) = RpcRequest::from_value(fx_invalid.clone()) else {
+                anyhow::bail!("should be invalid: {fx_invalid}");
+            };
+            let res = serde_json::to_value(res)?;
+            assert_eq!(res["error"]["code"], INVALID_REQUEST);
+            assert_eq!(res["jsonrpc"], JSONRPC_VERSION);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_response_shape() -> Result<()> {
+        let res_ok = serde_json::to_value(RpcResponse::result(json!(1), json!({"id": 1000})))?;
+        let res_err = serde_json::to_value(RpcResponse::error(
+            json!("a"),
+            RpcError::from(&Error::RpcMethodUnknown("task.nope".to_string())),
+        ))?;
+
+        assert_eq!(
+            res_ok,
+            json!({"jsonrpc": "2.0", "id": 1, "result": {"id": 1000}})
+        );
+        assert_eq!(
+            res_err,
+            json!({
+                "jsonrpc": "2.0",
+                "id": "a",
+                "error": {
+                    "code": METHOD_NOT_FOUND,
+                    "message": "Method not found",
+                    "data": {"method": "task.nope"}
+                }
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_error_batch_item_failed() -> Result<()> {
+        let err = Error::Model(model::psql::Error::batch_item_failed(
+            2,
+            model::psql::Error::EntityNotFound {
+                entity: "task",
+                id: 100,
+            },
+        ));
+
+        let rpc_error = serde_json::to_value(RpcError::from(&err))?;
+
+        assert_eq!(
+            rpc_error,
+            json!({
+                "code": ENTITY_NOT_FOUND,
+                "message": "Entity not found",
+                "data": {"entity": "task", "id": 100, "index": 2}
+            })
+        );
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
@@ -0,0 +1,71 @@
+use crate::params::ParamsList;
+use crate::registry::RpcRegistry;
+use crate::Result;
+use crate::{ParamsForCreate, ParamsForUpdate, ParamsIded};
+use lib_core::ctx::Ctx;
+use lib_core::model::psql::label::{Label, LabelBmc, LabelFilter, LabelForCreate, LabelForUpdate};
+use lib_core::model::psql::role::perm::{TASK_READ, TASK_WRITE};
+use lib_core::model::psql::ModelManager;
+
+/// Register the label RPC methods
+/// (The labels are part of the task management, so they need the task permissions)
+pub fn rpc_methods(registry: RpcRegistry) -> RpcRegistry {
+    registry
+        .add("label.create", Some(TASK_WRITE), create_label)
+        .add("label.list", Some(TASK_READ), list_labels)
+        .add("label.update", Some(TASK_WRITE), update_label)
+        .add("label.delete", Some(TASK_WRITE), delete_label)
+}
+
+/// Create a label in a project
+pub async fn create_label(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsForCreate<LabelForCreate>,
+) -> Result<Label> {
+    let ParamsForCreate { data } = params;
+
+    let id = LabelBmc::create(&ctx, &mm, data).await?;
+    let label = LabelBmc::get(&ctx, &mm, id).await?;
+
+    // Return the created label
+    Ok(label)
+}
+
+/// List the labels of the projects of the ctx user
+pub async fn list_labels(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsList<LabelFilter>,
+) -> Result<Vec<Label>> {
+    let labels = LabelBmc::list(&ctx, &mm, params.filters, params.list_options).await?;
+
+    // Return the list of labels
+    Ok(labels)
+}
+
+/// Update a label with the given data
+pub async fn update_label(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsForUpdate<LabelForUpdate>,
+) -> Result<Label> {
+    let ParamsForUpdate { id, data } = params;
+
+    LabelBmc::update(&ctx, &mm, id, data).await?;
+
+    let label = LabelBmc::get(&ctx, &mm, id).await?;
+
+    // Return the updated label
+    Ok(label)
+}
+
+/// Delete a label (and remove it from its tasks) with the given id
+pub async fn delete_label(ctx: Ctx, mm: ModelManager, params: ParamsIded) -> Result<Label> {
+    let ParamsIded { id } = params;
+    let label = LabelBmc::get(&ctx, &mm, id).await?;
+    LabelBmc::delete(&ctx, &mm, id).await?;
+
+    // Return the deleted label
+    Ok(label)
+}
@@ -1,58 +1,62 @@
 mod error;
+mod jsonrpc;
+mod label_rpc;
 mod params;
+mod project_rpc;
+mod registry;
+mod role_rpc;
+mod search_rpc;
+mod session_rpc;
+mod task_activity_rpc;
+mod task_comment_rpc;
 mod task_rpc;
+mod user_rpc;
 
 pub use self::error::{Error, Result};
+pub use self::jsonrpc::{RpcError, RpcRequest, RpcResponse, INTERNAL_ERROR};
+pub use self::registry::{RpcRegistry, RPC_DISCOVER};
 use params::*;
 
 use lib_core::ctx::Ctx;
 use lib_core::model::psql::ModelManager;
-use serde::Deserialize;
-use serde_json::{from_value, to_value, Value};
-use task_rpc::{create_task, delete_task, list_tasks, update_task};
+use serde_json::Value;
+use std::sync::LazyLock;
 
-/// JSON-RPC 2.0 Request
-#[derive(Deserialize)]
-pub struct RpcRequest {
-    pub id: Option<Value>,
-    pub method: String,
-    pub params: Option<Value>,
-}
-
-/// Define the macro to execute the RPC function
-macro_rules! exec_rpc_fn {
-    // With Params
-    ($rpc_fn:expr, $ctx:expr, $mm:expr, $rpc_params:expr) => {{
-        let rpc_fn_name = stringify!($rpc_fn);
-        let params = $rpc_params.ok_or(Error::RpcMissingParams {
-            rpc_method: rpc_fn_name.to_string(),
-        })?;
-        let params = from_value(params).map_err(|_| Error::RpcFailJsonParams {
-            rpc_method: rpc_fn_name.to_string(),
-        })?;
-        $rpc_fn($ctx, $mm, params).await.map(to_value)??
-    }};
-
-    // Without Params
-    ($rpc_fn:expr, $ctx:expr, $mm:expr) => {
-        $rpc_fn($ctx, $mm).await.map(to_value)??
-    };
-}
-
-pub async fn exec_rpc(ctx: Ctx, mm: ModelManager, rpc_req: RpcRequest) -> Result<Value> {
-    let rpc_method = rpc_req.method;
-    let rpc_params = rpc_req.params;
-
-    // Actually, these RPC methods are not appropriate for the RPC API because they are CRUD operations.
+/// The RPC methods of the authenticated users
+/// (Each method declares its required permission, if any)
+static RPC_REGISTRY: LazyLock<RpcRegistry> = LazyLock::new(|| {
+    // Actually, the project and task RPC methods are not appropriate for the RPC API because they are CRUD operations.
     // RESTful API is more suitable for CRUD operations.
-    let result_json = match rpc_method.as_str() {
-        "task.create" => exec_rpc_fn!(create_task, ctx, mm, rpc_params),
-        "task.list" => exec_rpc_fn!(list_tasks, ctx, mm, rpc_params),
-        "task.update" => exec_rpc_fn!(update_task, ctx, mm, rpc_params),
-        "task.delete" => exec_rpc_fn!(delete_task, ctx, mm, rpc_params),
-        _ => return Err(Error::RpcMethodUnknown(rpc_method)),
-    };
+    let rpc_methods: [fn(RpcRegistry) -> RpcRegistry; 9] = [
+        project_rpc::rpc_methods,
+        task_rpc::rpc_methods,
+        label_rpc::rpc_methods,
+        task_comment_rpc::rpc_methods,
+        task_activity_rpc::rpc_methods,
+        search_rpc::rpc_methods,
+        role_rpc::rpc_methods,
+        session_rpc::rpc_methods,
+        user_rpc::rpc_methods,
+    ];
+
+    rpc_methods
+        .into_iter()
+        .fold(RpcRegistry::new("JSON-RPC API"), |registry, rpc_methods| {
+            rpc_methods(registry)
+        })
+});
+
+/// The RPC methods that do not require an authenticated user
+static PUBLIC_RPC_REGISTRY: LazyLock<RpcRegistry> =
+    LazyLock::new(|| user_rpc::public_rpc_methods(RpcRegistry::new("Public JSON-RPC API")));
 
+pub async fn exec_rpc(ctx: Ctx, mm: ModelManager, rpc_req: RpcRequest) -> Result<Value> {
     // The benefit of using JSON-RPC is that the response is always in the same format and the client can easily parse it.
-    Ok(result_json)
+    RPC_REGISTRY.call(ctx, mm, rpc_req).await
+}
+
+/// Execute the RPC methods that do not require an authenticated user
+/// (They are executed with the root ctx)
+pub async fn exec_public_rpc(mm: ModelManager, rpc_req: RpcRequest) -> Result<Value> {
+    PUBLIC_RPC_REGISTRY.call(Ctx::root_ctx(), mm, rpc_req).await
 }
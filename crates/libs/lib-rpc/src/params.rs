@@ -1,24 +1,44 @@
+use lib_core::model::psql::PageOptions;
 use modql::filter::ListOptions;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
 use serde::de::DeserializeOwned;
 use serde::Deserialize;
+use serde_json::Value;
 use serde_with::{serde_as, OneOrMany};
 
-#[derive(Deserialize)]
+#[derive(Deserialize, JsonSchema)]
 pub struct ParamsForCreate<D> {
     pub data: D,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, JsonSchema)]
 pub struct ParamsForUpdate<D> {
     pub id: i64,
     pub data: D,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, JsonSchema)]
 pub struct ParamsIded {
     pub id: i64,
 }
 
+#[derive(Deserialize, JsonSchema)]
+pub struct ParamsForCreateMany<D> {
+    pub data: Vec<D>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ParamsForUpdateMany<D> {
+    pub data: Vec<ParamsForUpdate<D>>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ParamsIds {
+    pub ids: Vec<i64>,
+}
+
 #[serde_as]
 #[derive(Deserialize)]
 pub struct ParamsList<F>
@@ -29,3 +49,73 @@ where
     pub filters: Option<Vec<F>>,
     pub list_options: Option<ListOptions>,
 }
+
+/// ParamsList with the paging options (cursor and total count)
+#[serde_as]
+#[derive(Deserialize)]
+pub struct ParamsListPage<F>
+where
+    F: DeserializeOwned,
+{
+    #[serde_as(deserialize_as = "Option<OneOrMany<_>>")]
+    pub filters: Option<Vec<F>>,
+    pub list_options: Option<ListOptions>,
+    pub page_options: Option<PageOptions>,
+}
+
+// region:    --- ParamsList JsonSchema
+/// The modql filter and list options types have no JsonSchema,
+/// so the ParamsList schema is described by these mirror types.
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+struct ParamsListSchema {
+    /// One or many filter objects (ORed), e.g., `{"title": {"$startsWith": "test"}}`
+    filters: Option<Value>,
+    list_options: Option<ListOptionsSchema>,
+}
+
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+struct ParamsListPageSchema {
+    /// One or many filter objects (ORed), e.g., `{"title": {"$startsWith": "test"}}`
+    filters: Option<Value>,
+    list_options: Option<ListOptionsSchema>,
+    page_options: Option<PageOptions>,
+}
+
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+#[schemars(rename = "ListOptions")]
+struct ListOptionsSchema {
+    limit: Option<i64>,
+    offset: Option<i64>,
+    /// One or many column names, `!` prefix for descending order, e.g., `"!id"`
+    order_bys: Option<Value>,
+}
+
+impl<F> JsonSchema for ParamsList<F>
+where
+    F: DeserializeOwned,
+{
+    fn schema_name() -> String {
+        "ParamsList".to_string()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        ParamsListSchema::json_schema(gen)
+    }
+}
+
+impl<F> JsonSchema for ParamsListPage<F>
+where
+    F: DeserializeOwned,
+{
+    fn schema_name() -> String {
+        "ParamsListPage".to_string()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        ParamsListPageSchema::json_schema(gen)
+    }
+}
+// endregion: --- ParamsList JsonSchema
@@ -0,0 +1,72 @@
+use crate::params::ParamsList;
+use crate::registry::RpcRegistry;
+use crate::Result;
+use crate::{ParamsForCreate, ParamsForUpdate, ParamsIded};
+use lib_core::ctx::Ctx;
+use lib_core::model::psql::project::{
+    Project, ProjectBmc, ProjectFilter, ProjectForCreate, ProjectForUpdate,
+};
+use lib_core::model::psql::role::perm::{PROJECT_READ, PROJECT_WRITE};
+use lib_core::model::psql::ModelManager;
+
+/// Register the project RPC methods
+pub fn rpc_methods(registry: RpcRegistry) -> RpcRegistry {
+    registry
+        .add("project.create", Some(PROJECT_WRITE), create_project)
+        .add("project.list", Some(PROJECT_READ), list_projects)
+        .add("project.update", Some(PROJECT_WRITE), update_project)
+        .add("project.delete", Some(PROJECT_WRITE), delete_project)
+}
+
+/// Create a project owned by the ctx user
+pub async fn create_project(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsForCreate<ProjectForCreate>,
+) -> Result<Project> {
+    let ParamsForCreate { data } = params;
+
+    let id = ProjectBmc::create(&ctx, &mm, data).await?;
+    let project = ProjectBmc::get(&ctx, &mm, id).await?;
+
+    // Return the created project
+    Ok(project)
+}
+
+/// List the projects of the ctx user
+pub async fn list_projects(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsList<ProjectFilter>,
+) -> Result<Vec<Project>> {
+    let projects = ProjectBmc::list(&ctx, &mm, params.filters, params.list_options).await?;
+
+    // Return the list of projects
+    Ok(projects)
+}
+
+/// Update a project with the given data
+pub async fn update_project(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsForUpdate<ProjectForUpdate>,
+) -> Result<Project> {
+    let ParamsForUpdate { id, data } = params;
+
+    ProjectBmc::update(&ctx, &mm, id, data).await?;
+
+    let project = ProjectBmc::get(&ctx, &mm, id).await?;
+
+    // Return the updated project
+    Ok(project)
+}
+
+/// Delete a project (and its tasks) with the given id
+pub async fn delete_project(ctx: Ctx, mm: ModelManager, params: ParamsIded) -> Result<Project> {
+    let ParamsIded { id } = params;
+    let project = ProjectBmc::get(&ctx, &mm, id).await?;
+    ProjectBmc::delete(&ctx, &mm, id).await?;
+
+    // Return the deleted project
+    Ok(project)
+}
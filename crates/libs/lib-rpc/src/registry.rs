@@ -0,0 +1,508 @@
+//! RPC method registry.
+//! Each `*_rpc` module registers its methods (name, required permission and handler fn).
+//! The params and result types of the handlers give the OpenRPC document served by `rpc.discover`.
+//! See <https://spec.open-rpc.org>
+
+use crate::{Error, Result, RpcRequest};
+use lib_core::ctx::Ctx;
+use lib_core::model::psql::ModelManager;
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::schema::{ObjectValidation, Schema, SchemaObject};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{from_value, json, to_value, Map, Value};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+
+/// The OpenRPC service discovery method
+pub const RPC_DISCOVER: &str = "rpc.discover";
+const OPENRPC_VERSION: &str = "1.2.6";
+
+type RpcFuture = Pin<Box<dyn Future<Output = Result<Value>> + Send>>;
+type RpcHandler = Box<dyn Fn(Ctx, ModelManager, Option<Value>) -> RpcFuture + Send + Sync>;
+type SchemaFn = fn(&mut SchemaGenerator) -> Schema;
+
+struct RpcMethod {
+    name: &'static str,
+    permission: Option<&'static str>,
+    params_schema: Option<SchemaFn>,
+    result_schema: SchemaFn,
+    handler: RpcHandler,
+    // Run in its own transaction (see `RpcRegistry::with_txn`)
+    txn: bool,
+}
+
+pub struct RpcRegistry {
+    title: &'static str,
+    methods: Vec<RpcMethod>,
+    // method name -> index in methods (methods keep the registration order)
+    index: HashMap<&'static str, usize>,
+}
+
+impl RpcRegistry {
+    pub fn new(title: &'static str) -> Self {
+        Self {
+            title,
+            methods: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+
+    /// Register a method taking a params object
+    /// (`permission` is the permission required to call it, if any)
+    pub fn add<P, R, F, Fut>(
+        self,
+        name: &'static str,
+        permission: Option<&'static str>,
+        rpc_fn: F,
+    ) -> Self
+    where
+        P: DeserializeOwned + JsonSchema + Send + 'static,
+        R: Serialize + JsonSchema,
+        F: Fn(Ctx, ModelManager, P) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<R>> + Send + 'static,
+    {
+        let handler: RpcHandler = Box::new(move |ctx, mm, params| {
+            let params = params
+                .ok_or_else(|| Error::RpcMissingParams {
+                    rpc_method: name.to_string(),
+                })
+                .and_then(|params| {
+                    from_value::<P>(params).map_err(|_| Error::RpcFailJsonParams {
+                        rpc_method: name.to_string(),
+                    })
+                });
+
+            match params {
+                Ok(params) => {
+                    let fut = rpc_fn(ctx, mm, params);
+                    Box::pin(async move { Ok(to_value(fut.await?)?) })
+                }
+                Err(err) => Box::pin(async move { Err(err) }),
+            }
+        });
+
+        self.push(RpcMethod {
+            name,
+            permission,
+            params_schema: Some(P::json_schema),
+            result_schema: subschema_for::<R>,
+            handler,
+            txn: false,
+        })
+    }
+
+    /// Register a method without params
+    pub fn add_no_params<R, F, Fut>(
+        self,
+        name: &'static str,
+        permission: Option<&'static str>,
+        rpc_fn: F,
+    ) -> Self
+    where
+        R: Serialize + JsonSchema,
+        F: Fn(Ctx, ModelManager) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<R>> + Send + 'static,
+    {
+        let handler: RpcHandler = Box::new(move |ctx, mm, _params| {
+            let fut = rpc_fn(ctx, mm);
+            Box::pin(async move { Ok(to_value(fut.await?)?) })
+        });
+
+        self.push(RpcMethod {
+            name,
+            permission,
+            params_schema: None,
+            result_schema: subschema_for::<R>,
+            handler,
+            txn: false,
+        })
+    }
+
+    /// Run the method registered last in its own transaction,
+    /// so its bmc calls are consistent (all or none)
+    /// (Only for the methods with several dependent steps, the others use the pool connections)
+    pub fn with_txn(mut self) -> Self {
+        let rpc_method = self
+            .methods
+            .last_mut()
+            .expect("with_txn called before any method was registered");
+        rpc_method.txn = true;
+        self
+    }
+
+    fn push(mut self, rpc_method: RpcMethod) -> Self {
+        assert!(
+            !self.index.contains_key(rpc_method.name),
+            "rpc method registered twice: {}",
+            rpc_method.name
+        );
+
+        self.index.insert(rpc_method.name, self.methods.len());
+        self.methods.push(rpc_method);
+        self
+    }
+
+    /// Execute the request method (after checking its permission),
+    /// in its own transaction if registered `with_txn`
+    pub async fn call(&self, ctx: Ctx, mm: ModelManager, rpc_req: RpcRequest) -> Result<Value> {
+        let RpcRequest { method, params, .. } = rpc_req;
+
+        if method == RPC_DISCOVER {
+            return Ok(self.discover());
+        }
+
+        let Some(rpc_method) = self
+            .index
+            .get(method.as_str())
+            .map(|idx| &self.methods[*idx])
+        else {
+            return Err(Error::RpcMethodUnknown(method));
+        };
+
+        if let Some(permission) = rpc_method.permission {
+            if !ctx.has_permission(permission) {
+                return Err(Error::RpcPermissionDenied {
+                    rpc_method: method,
+                    permission,
+                });
+            }
+        }
+
+        if !rpc_method.txn {
+            return (rpc_method.handler)(ctx, mm, params).await;
+        }
+
+        let mm = mm.new_with_txn();
+        mm.begin_txn().await?;
+        match (rpc_method.handler)(ctx, mm.clone(), params).await {
+            Ok(result) => {
+                mm.commit_txn().await?;
+                Ok(result)
+            }
+            Err(err) => {
+                // (The method error is the one to report, even if the rollback fails)
+                mm.rollback_txn().await.ok();
+                Err(err)
+            }
+        }
+    }
+
+    /// Generate the OpenRPC document of the registered methods
+    pub fn discover(&self) -> Value {
+        let mut gen = SchemaSettings::draft07()
+            .with(|settings| settings.definitions_path = "#/components/schemas/".to_string())
+            .into_generator();
+
+        let methods: Vec<Value> = self
+            .methods
+            .iter()
+            .map(|rpc_method| {
+                let params = rpc_method
+                    .params_schema
+                    .map(|params_schema| content_descriptors(params_schema(&mut gen)))
+                    .unwrap_or_default();
+
+                let mut method = Map::new();
+                method.insert("name".to_string(), json!(rpc_method.name));
+                method.insert("paramStructure".to_string(), json!("by-name"));
+                method.insert("params".to_string(), json!(params));
+                method.insert(
+                    "result".to_string(),
+                    json!({
+                        "name": "result",
+                        "schema": (rpc_method.result_schema)(&mut gen),
+                    }),
+                );
+                if let Some(permission) = rpc_method.permission {
+                    method.insert("x-permission".to_string(), json!(permission));
+                }
+
+                Value::Object(method)
+            })
+            .collect();
+
+        json!({
+            "openrpc": OPENRPC_VERSION,
+            "info": {
+                "title": self.title,
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+            "methods": methods,
+            "components": {
+                "schemas": gen.take_definitions(),
+            },
+        })
+    }
+}
+
+fn subschema_for<T: JsonSchema>(gen: &mut SchemaGenerator) -> Schema {
+    gen.subschema_for::<T>()
+}
+
+/// From the params object schema to the OpenRPC content descriptors (one per property)
+fn content_descriptors(params_schema: Schema) -> Vec<Value> {
+    let Schema::Object(SchemaObject {
+        object: Some(object),
+        ..
+    }) = params_schema
+    else {
+        return Vec::new();
+    };
+    let ObjectValidation {
+        properties,
+        required,
+        ..
+    } = *object;
+
+    properties
+        .into_iter()
+        .map(|(name, schema)| {
+            json!({
+                "required": required.contains(&name),
+                "name": name,
+                "schema": schema,
+            })
+        })
+        .collect()
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ParamsForCreate;
+    use crate::RPC_REGISTRY;
+    use anyhow::Result;
+    use lib_core::_dev_utils;
+    use lib_core::model::psql::project::{ProjectBmc, ProjectForCreate};
+    use lib_core::model::psql::role::perm;
+    use lib_core::model::psql::task::TaskBmc;
+    use serial_test::serial;
+
+    fn fx_rpc_req(method: &str, params: Option<Value>) -> Result<RpcRequest> {
+        let rpc_req = RpcRequest::from_value(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        }))
+        .map_err(|res| anyhow::anyhow!("{res:?}"))?;
+
+        Ok(rpc_req)
+    }
+
+    #[test]
+    fn test_discover_ok() -> Result<()> {
+        // -- Exec
+        let doc = RPC_REGISTRY.discover();
+
+        // -- Check
+        assert_eq!(doc["openrpc"], OPENRPC_VERSION);
+        let methods = doc["methods"]
+            .as_array()
+            .ok_or(anyhow::anyhow!("no methods"))?;
+        let task_create = methods
+            .iter()
+            .find(|m| m["name"] == "task.create")
+            .ok_or(anyhow::anyhow!("no task.create method"))?;
+        assert_eq!(task_create["x-permission"], perm::TASK_WRITE);
+        assert_eq!(task_create["params"][0]["name"], "data");
+        assert_eq!(task_create["params"][0]["required"], true);
+        assert_eq!(
+            task_create["params"][0]["schema"]["$ref"],
+            "#/components/schemas/TaskForCreate"
+        );
+        assert!(doc["components"]["schemas"]["Task"].is_object());
+
+        // The methods without params or permission
+        let user_get = methods
+            .iter()
+            .find(|m| m["name"] == "user.get")
+            .ok_or(anyhow::anyhow!("no user.get method"))?;
+        assert_eq!(user_get["params"], json!([]));
+        assert!(user_get.get("x-permission").is_none());
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_call_err() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::new(1000)?.with_permissions([perm::TASK_READ.to_string()]);
+
+        // -- Exec
+        let res_unknown = RPC_REGISTRY
+            .call(ctx.clone(), mm.clone(), fx_rpc_req("task.nope", None)?)
+            .await;
+        let res_denied = RPC_REGISTRY
+            .call(
+                ctx.clone(),
+                mm.clone(),
+                fx_rpc_req("task.delete", Some(json!({"id": 1})))?,
+            )
+            .await;
+        let res_missing = RPC_REGISTRY
+            .call(ctx.clone(), mm.clone(), fx_rpc_req("task.list", None)?)
+            .await;
+        let res_bad = RPC_REGISTRY
+            .call(
+                ctx,
+                mm,
+                fx_rpc_req("task.list", Some(json!({"filters": 1})))?,
+            )
+            .await;
+
+        // -- Check
+        assert!(matches!(res_unknown, Err(Error::RpcMethodUnknown(_))));
+        assert!(matches!(
+            res_denied,
+            Err(Error::RpcPermissionDenied {
+                permission: "task:write",
+                ..
+            })
+        ));
+        assert!(matches!(res_missing, Err(Error::RpcMissingParams { .. })));
+        assert!(matches!(res_bad, Err(Error::RpcFailJsonParams { .. })));
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_call_with_txn_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        _dev_utils::init_test().await;
+        // (A pool of this test runtime: the connections of the shared one,
+        //  opened in the runtime of another test, cannot be used here)
+        let mm = ModelManager::new().await?;
+        let ctx = Ctx::new(1000)?;
+        async fn create_then_fail(
+            ctx: Ctx,
+            mm: ModelManager,
+            params: ParamsForCreate<ProjectForCreate>,
+        ) -> crate::Result<Value> {
+            ProjectBmc::create(&ctx, &mm, params.data).await?;
+            Err(Error::RpcMethodUnknown("create_then_fail".to_string()))
+        }
+        let registry = RpcRegistry::new("test_call_with_txn_ok")
+            .add("project.create_fail", None, create_then_fail)
+            .add("project.create_fail_txn", None, create_then_fail)
+            .with_txn();
+        let fx_name = "test_call_with_txn_ok project";
+        let fx_params = || Some(json!({"data": {"name": fx_name}}));
+
+        // -- Exec
+        let res = registry
+            .call(
+                ctx.clone(),
+                mm.clone(),
+                fx_rpc_req("project.create_fail", fx_params())?,
+            )
+            .await;
+        let res_txn = registry
+            .call(
+                ctx.clone(),
+                mm.clone(),
+                fx_rpc_req("project.create_fail_txn", fx_params())?,
+            )
+            .await;
+
+        // -- Check
+        assert!(res.is_err() && res_txn.is_err());
+        let filters = serde_json::from_value(json!([{"name": fx_name}]))?;
+        let projects = ProjectBmc::list(&ctx, &mm, Some(filters), None).await?;
+        // Only the project created without transaction is kept (the other is rolled back)
+        assert_eq!(projects.len(), 1);
+
+        // -- Clean
+        ProjectBmc::delete(&ctx, &mm, projects[0].id).await?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_txn_methods_ok() -> Result<()> {
+        // -- Exec & Check
+        // The methods with several dependent steps run in their own transaction
+        for name in [
+            "task.update",
+            "task.delete",
+            "task.restore",
+            "task.add_label",
+            "task.remove_label",
+            "session.revoke_all",
+            "user.delete",
+        ] {
+            let idx = RPC_REGISTRY
+                .index
+                .get(name)
+                .ok_or(anyhow::anyhow!("no {name} method"))?;
+            assert!(RPC_REGISTRY.methods[*idx].txn, "{name} should be with_txn");
+        }
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_call_task_update_txn_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        _dev_utils::init_test().await;
+        // (A pool of this test runtime, see `test_call_with_txn_ok`)
+        let mm = ModelManager::new().await?;
+        let ctx = Ctx::new(1000)?.with_permissions([perm::TASK_WRITE.to_string()]);
+        let fx_title_new = "test_call_task_update_txn_ok - task 01 - new";
+        let project_id =
+            _dev_utils::seed_project(&ctx, &mm, "test_call_task_update_txn_ok project").await?;
+        let tasks = _dev_utils::seed_tasks(
+            &ctx,
+            &mm,
+            project_id,
+            &["test_call_task_update_txn_ok - task 01"],
+        )
+        .await?;
+        let task_id = tasks[0].id;
+
+        // -- Exec
+        let res = RPC_REGISTRY
+            .call(
+                ctx.clone(),
+                mm.clone(),
+                fx_rpc_req(
+                    "task.update",
+                    Some(json!({"id": task_id, "data": {"title": fx_title_new}})),
+                )?,
+            )
+            .await?;
+        let res_err = RPC_REGISTRY
+            .call(
+                ctx.clone(),
+                mm.clone(),
+                fx_rpc_req(
+                    "task.update",
+                    Some(json!({"id": task_id, "data": {"title": "nope", "version": 0}})),
+                )?,
+            )
+            .await;
+
+        // -- Check
+        // The update and get of the transaction are committed together
+        assert_eq!(res["title"], fx_title_new);
+        assert!(res_err.is_err());
+        let task = TaskBmc::get(&ctx, &mm, task_id).await?;
+        assert_eq!(task.title, fx_title_new);
+        // The transaction connection is released (the pool is still usable)
+        assert!(!mm.in_txn().await);
+
+        // -- Clean
+        ProjectBmc::delete(&ctx, &mm, project_id).await?;
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
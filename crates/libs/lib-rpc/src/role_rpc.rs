@@ -0,0 +1,57 @@
+use crate::registry::RpcRegistry;
+use crate::Result;
+use lib_core::ctx::Ctx;
+use lib_core::model::psql::role::perm::ROLE_MANAGE;
+use lib_core::model::psql::role::{Role, RoleBmc};
+use lib_core::model::psql::ModelManager;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+/// Register the role RPC methods
+pub fn rpc_methods(registry: RpcRegistry) -> RpcRegistry {
+    registry
+        .add_no_params("role.list", Some(ROLE_MANAGE), list_roles)
+        .add("role.assign", Some(ROLE_MANAGE), assign_role)
+        .add("role.unassign", Some(ROLE_MANAGE), unassign_role)
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ParamsForRoleAssign {
+    pub user_id: i64,
+    pub role: String,
+}
+
+/// List all the roles
+pub async fn list_roles(ctx: Ctx, mm: ModelManager) -> Result<Vec<Role>> {
+    let roles = RoleBmc::list(&ctx, &mm).await?;
+
+    Ok(roles)
+}
+
+/// Give a role to a user and return the user roles
+pub async fn assign_role(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsForRoleAssign,
+) -> Result<Vec<Role>> {
+    let ParamsForRoleAssign { user_id, role } = params;
+
+    RoleBmc::assign(&ctx, &mm, user_id, &role).await?;
+    let roles = RoleBmc::list_for_user(&ctx, &mm, user_id).await?;
+
+    Ok(roles)
+}
+
+/// Remove a role from a user and return the user roles
+pub async fn unassign_role(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsForRoleAssign,
+) -> Result<Vec<Role>> {
+    let ParamsForRoleAssign { user_id, role } = params;
+
+    RoleBmc::unassign(&ctx, &mm, user_id, &role).await?;
+    let roles = RoleBmc::list_for_user(&ctx, &mm, user_id).await?;
+
+    Ok(roles)
+}
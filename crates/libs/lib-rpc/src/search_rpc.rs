@@ -0,0 +1,37 @@
+use crate::registry::RpcRegistry;
+use crate::Result;
+use lib_core::ctx::Ctx;
+use lib_core::model::psql::role::perm::TASK_READ;
+use lib_core::model::psql::task::{TaskBmc, TaskSearchHit};
+use lib_core::model::psql::ModelManager;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+/// Register the search RPC methods
+/// (The HN stories are searched with `GET /api/v2/hnstories/search`)
+pub fn rpc_methods(registry: RpcRegistry) -> RpcRegistry {
+    registry.add("search", Some(TASK_READ), search)
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ParamsSearch {
+    /// The query in the web search syntax, e.g., `deploy -staging "release notes"`
+    pub q: String,
+    /// 20 by default, 100 at most
+    pub limit: Option<i64>,
+}
+
+/// Full-text search of the tasks of the ctx user (title and description),
+/// the most relevant first, with the matches highlighted (`<mark>..</mark>`)
+pub async fn search(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsSearch,
+) -> Result<Vec<TaskSearchHit>> {
+    let ParamsSearch { q, limit } = params;
+
+    let hits = TaskBmc::search(&ctx, &mm, &q, limit).await?;
+
+    // Return the search hits
+    Ok(hits)
+}
@@ -0,0 +1,54 @@
+use crate::params::ParamsList;
+use crate::registry::RpcRegistry;
+use crate::ParamsIded;
+use crate::Result;
+use lib_core::ctx::Ctx;
+use lib_core::model::psql::user::UserBmc;
+use lib_core::model::psql::user_session::{UserSession, UserSessionBmc, UserSessionFilter};
+use lib_core::model::psql::ModelManager;
+use serde_json::{json, Value};
+
+/// Register the session RPC methods (own sessions, no permission required)
+pub fn rpc_methods(registry: RpcRegistry) -> RpcRegistry {
+    registry
+        .add("session.list", None, list_sessions)
+        .add("session.revoke", None, revoke_session)
+        .add_no_params("session.revoke_all", None, revoke_all_sessions)
+        .with_txn()
+}
+
+/// List the sessions of the ctx user
+pub async fn list_sessions(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsList<UserSessionFilter>,
+) -> Result<Vec<UserSession>> {
+    let sessions = UserSessionBmc::list(&ctx, &mm, params.filters, params.list_options).await?;
+
+    // Return the list of sessions
+    Ok(sessions)
+}
+
+/// Revoke a session of the ctx user (its refresh token cannot be used anymore)
+pub async fn revoke_session(ctx: Ctx, mm: ModelManager, params: ParamsIded) -> Result<UserSession> {
+    let ParamsIded { id } = params;
+
+    UserSessionBmc::revoke(&ctx, &mm, id, "revoked_by_user").await?;
+    let session = UserSessionBmc::get(&ctx, &mm, id).await?;
+
+    // Return the revoked session
+    Ok(session)
+}
+
+/// Revoke all the sessions of the ctx user
+/// (The token salt is rotated, so the issued web tokens are invalidated as well)
+/// (Registered `with_txn`: the revoke and the salt rotation are one unit)
+pub async fn revoke_all_sessions(ctx: Ctx, mm: ModelManager) -> Result<Value> {
+    let user_id = ctx.user_id();
+
+    let revoked =
+        UserSessionBmc::revoke_all_for_user(&ctx, &mm, user_id, "revoked_all_by_user").await?;
+    UserBmc::rotate_token_salt(&ctx, &mm, user_id).await?;
+
+    Ok(json!({ "revoked": revoked }))
+}
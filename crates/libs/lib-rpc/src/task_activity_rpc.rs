@@ -0,0 +1,26 @@
+use crate::params::ParamsList;
+use crate::registry::RpcRegistry;
+use crate::Result;
+use lib_core::ctx::Ctx;
+use lib_core::model::psql::role::perm::TASK_READ;
+use lib_core::model::psql::task_activity::{TaskActivity, TaskActivityBmc, TaskActivityFilter};
+use lib_core::model::psql::ModelManager;
+
+/// Register the task activity RPC methods
+/// (The activities are recorded by the task writes, so they are read only)
+pub fn rpc_methods(registry: RpcRegistry) -> RpcRegistry {
+    registry.add("task_activity.list", Some(TASK_READ), list_task_activities)
+}
+
+/// List the activities of the tasks of the ctx user (the oldest first by default),
+/// e.g., `{"filters": {"task_id": 1000}}` for the history of a task
+pub async fn list_task_activities(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsList<TaskActivityFilter>,
+) -> Result<Vec<TaskActivity>> {
+    let activities = TaskActivityBmc::list(&ctx, &mm, params.filters, params.list_options).await?;
+
+    // Return the list of activities
+    Ok(activities)
+}
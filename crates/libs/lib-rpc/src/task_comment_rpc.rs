@@ -0,0 +1,77 @@
+use crate::params::ParamsList;
+use crate::registry::RpcRegistry;
+use crate::Result;
+use crate::{ParamsForCreate, ParamsForUpdate, ParamsIded};
+use lib_core::ctx::Ctx;
+use lib_core::model::psql::role::perm::{TASK_READ, TASK_WRITE};
+use lib_core::model::psql::task_comment::{
+    TaskComment, TaskCommentBmc, TaskCommentFilter, TaskCommentForCreate, TaskCommentForUpdate,
+};
+use lib_core::model::psql::ModelManager;
+
+/// Register the task comment RPC methods
+/// (The comments are part of the task management, so they need the task permissions)
+pub fn rpc_methods(registry: RpcRegistry) -> RpcRegistry {
+    registry
+        .add("task_comment.create", Some(TASK_WRITE), create_task_comment)
+        .add("task_comment.list", Some(TASK_READ), list_task_comments)
+        .add("task_comment.update", Some(TASK_WRITE), update_task_comment)
+        .add("task_comment.delete", Some(TASK_WRITE), delete_task_comment)
+}
+
+/// Create a comment on a task (authored by the ctx user)
+pub async fn create_task_comment(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsForCreate<TaskCommentForCreate>,
+) -> Result<TaskComment> {
+    let ParamsForCreate { data } = params;
+
+    let id = TaskCommentBmc::create(&ctx, &mm, data).await?;
+    let comment = TaskCommentBmc::get(&ctx, &mm, id).await?;
+
+    // Return the created comment
+    Ok(comment)
+}
+
+/// List the comments of the tasks of the ctx user
+pub async fn list_task_comments(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsList<TaskCommentFilter>,
+) -> Result<Vec<TaskComment>> {
+    let comments = TaskCommentBmc::list(&ctx, &mm, params.filters, params.list_options).await?;
+
+    // Return the list of comments
+    Ok(comments)
+}
+
+/// Update a comment with the given data
+pub async fn update_task_comment(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsForUpdate<TaskCommentForUpdate>,
+) -> Result<TaskComment> {
+    let ParamsForUpdate { id, data } = params;
+
+    TaskCommentBmc::update(&ctx, &mm, id, data).await?;
+
+    let comment = TaskCommentBmc::get(&ctx, &mm, id).await?;
+
+    // Return the updated comment
+    Ok(comment)
+}
+
+/// Delete a comment with the given id
+pub async fn delete_task_comment(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsIded,
+) -> Result<TaskComment> {
+    let ParamsIded { id } = params;
+    let comment = TaskCommentBmc::get(&ctx, &mm, id).await?;
+    TaskCommentBmc::delete(&ctx, &mm, id).await?;
+
+    // Return the deleted comment
+    Ok(comment)
+}
@@ -1,9 +1,47 @@
-use crate::params::ParamsList;
+use crate::params::{ParamsList, ParamsListPage};
+use crate::registry::RpcRegistry;
 use crate::Result;
-use crate::{ParamsForCreate, ParamsForUpdate, ParamsIded};
+use crate::{
+    ParamsForCreate, ParamsForCreateMany, ParamsForUpdate, ParamsForUpdateMany, ParamsIded,
+    ParamsIds,
+};
 use lib_core::ctx::Ctx;
+use lib_core::model::psql::label::Label;
+use lib_core::model::psql::role::perm::{TASK_READ, TASK_WRITE};
 use lib_core::model::psql::task::{Task, TaskBmc, TaskFilter, TaskForCreate, TaskForUpdate};
-use lib_core::model::psql::ModelManager;
+use lib_core::model::psql::{ModelManager, Page};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+/// Register the task RPC methods
+pub fn rpc_methods(registry: RpcRegistry) -> RpcRegistry {
+    registry
+        .add("task.create", Some(TASK_WRITE), create_task)
+        .add("task.list", Some(TASK_READ), list_tasks)
+        .add("task.list_page", Some(TASK_READ), list_tasks_page)
+        .add("task.update", Some(TASK_WRITE), update_task)
+        .with_txn()
+        .add("task.delete", Some(TASK_WRITE), delete_task)
+        .with_txn()
+        .add("task.create_many", Some(TASK_WRITE), create_tasks)
+        .add("task.update_many", Some(TASK_WRITE), update_tasks)
+        .add("task.delete_many", Some(TASK_WRITE), delete_tasks)
+        .add("task.restore", Some(TASK_WRITE), restore_task)
+        .with_txn()
+        .add("task.list_deleted", Some(TASK_READ), list_deleted_tasks)
+        .add("task.add_label", Some(TASK_WRITE), add_task_label)
+        .with_txn()
+        .add("task.remove_label", Some(TASK_WRITE), remove_task_label)
+        .with_txn()
+        .add("task.list_labels", Some(TASK_READ), list_task_labels)
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ParamsTaskLabel {
+    /// The task id
+    pub id: i64,
+    pub label_id: i64,
+}
 
 /// Create a task with the given data
 pub async fn create_task(
@@ -20,19 +58,44 @@ pub async fn create_task(
     Ok(task)
 }
 
-/// List all tasks
+/// List the tasks
 pub async fn list_tasks(
     ctx: Ctx,
     mm: ModelManager,
     params: ParamsList<TaskFilter>,
 ) -> Result<Vec<Task>> {
-    let tasks = TaskBmc::list(&ctx, &mm, params.filters, params.list_options).await?;
+    let ParamsList {
+        filters,
+        list_options,
+    } = params;
 
-    // Return the list of tasks
+    let tasks = TaskBmc::list(&ctx, &mm, filters, list_options).await?;
+
+    // Return the tasks
     Ok(tasks)
 }
 
+/// List a page of tasks, ordered by `id` or `!id`
+/// (pass `next_cursor` as `page_options.cursor` for the next page)
+pub async fn list_tasks_page(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsListPage<TaskFilter>,
+) -> Result<Page<Task>> {
+    let ParamsListPage {
+        filters,
+        list_options,
+        page_options,
+    } = params;
+
+    let page = TaskBmc::list_page(&ctx, &mm, filters, list_options, page_options).await?;
+
+    // Return the page of tasks
+    Ok(page)
+}
+
 /// Update a task with the given data
+/// (Registered `with_txn`: the update and get are one unit)
 pub async fn update_task(
     ctx: Ctx,
     mm: ModelManager,
@@ -48,7 +111,8 @@ pub async fn update_task(
     Ok(task)
 }
 
-/// Delete a task with the given id
+/// Delete a task with the given id (moved to the trash, see `task.restore`)
+/// (Registered `with_txn`: the get and delete are one unit)
 pub async fn delete_task(ctx: Ctx, mm: ModelManager, params: ParamsIded) -> Result<Task> {
     let ParamsIded { id } = params;
     let task = TaskBmc::get(&ctx, &mm, id).await?;
@@ -57,3 +121,118 @@ pub async fn delete_task(ctx: Ctx, mm: ModelManager, params: ParamsIded) -> Resu
     // Return the deleted task
     Ok(task)
 }
+
+/// Create the tasks with the given data, in one transaction (all or none)
+pub async fn create_tasks(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsForCreateMany<TaskForCreate>,
+) -> Result<Vec<Task>> {
+    let ParamsForCreateMany { data } = params;
+
+    let tasks = TaskBmc::create_many(&ctx, &mm, data).await?;
+
+    // Return the created tasks (in the data order)
+    Ok(tasks)
+}
+
+/// Update the tasks with the given data, in one transaction (all or none)
+pub async fn update_tasks(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsForUpdateMany<TaskForUpdate>,
+) -> Result<Vec<Task>> {
+    let ParamsForUpdateMany { data } = params;
+    let tasks_u = data
+        .into_iter()
+        .map(|ParamsForUpdate { id, data }| (id, data))
+        .collect();
+
+    let tasks = TaskBmc::update_many(&ctx, &mm, tasks_u).await?;
+
+    // Return the updated tasks (in the data order)
+    Ok(tasks)
+}
+
+/// Delete the tasks with the given ids, in one transaction (all or none)
+pub async fn delete_tasks(ctx: Ctx, mm: ModelManager, params: ParamsIds) -> Result<Vec<Task>> {
+    let ParamsIds { ids } = params;
+
+    let tasks = TaskBmc::delete_many(&ctx, &mm, ids).await?;
+
+    // Return the deleted tasks (in the ids order)
+    Ok(tasks)
+}
+
+/// Restore a task from the trash
+/// (Registered `with_txn`: the restore and get are one unit)
+pub async fn restore_task(ctx: Ctx, mm: ModelManager, params: ParamsIded) -> Result<Task> {
+    let ParamsIded { id } = params;
+    TaskBmc::restore(&ctx, &mm, id).await?;
+    let task = TaskBmc::get(&ctx, &mm, id).await?;
+
+    // Return the restored task
+    Ok(task)
+}
+
+/// List a page of the tasks in the trash, ordered by `id` or `!id`
+/// (pass `next_cursor` as `page_options.cursor` for the next page)
+pub async fn list_deleted_tasks(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsListPage<TaskFilter>,
+) -> Result<Page<Task>> {
+    let ParamsListPage {
+        filters,
+        list_options,
+        page_options,
+    } = params;
+
+    let page = TaskBmc::list_deleted(&ctx, &mm, filters, list_options, page_options).await?;
+
+    // Return the page of trashed tasks
+    Ok(page)
+}
+
+/// Add a label (of the task project) to a task
+/// (Registered `with_txn`: the add and the labels list are one unit)
+pub async fn add_task_label(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsTaskLabel,
+) -> Result<Vec<Label>> {
+    let ParamsTaskLabel { id, label_id } = params;
+    TaskBmc::add_label(&ctx, &mm, id, label_id).await?;
+    let labels = TaskBmc::list_labels(&ctx, &mm, id).await?;
+
+    // Return the labels of the task
+    Ok(labels)
+}
+
+/// Remove a label from a task
+/// (Registered `with_txn`: the remove and the labels list are one unit)
+pub async fn remove_task_label(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsTaskLabel,
+) -> Result<Vec<Label>> {
+    let ParamsTaskLabel { id, label_id } = params;
+    TaskBmc::remove_label(&ctx, &mm, id, label_id).await?;
+    let labels = TaskBmc::list_labels(&ctx, &mm, id).await?;
+
+    // Return the labels of the task
+    Ok(labels)
+}
+
+/// List the labels of a task
+pub async fn list_task_labels(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsIded,
+) -> Result<Vec<Label>> {
+    let ParamsIded { id } = params;
+    let labels = TaskBmc::list_labels(&ctx, &mm, id).await?;
+
+    // Return the labels of the task
+    Ok(labels)
+}
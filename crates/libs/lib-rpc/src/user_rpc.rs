@@ -0,0 +1,94 @@
+use crate::registry::RpcRegistry;
+use crate::Result;
+use lib_core::ctx::Ctx;
+use lib_core::model::psql::user::{User, UserBmc, UserForCreate};
+use lib_core::model::psql::ModelManager;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// Register the public user RPC methods (no auth)
+pub fn public_rpc_methods(registry: RpcRegistry) -> RpcRegistry {
+    registry
+        .add("user.signup", None, signup)
+        .add("user.check_username", None, check_username)
+}
+
+/// Register the user RPC methods (own account, no permission required)
+pub fn rpc_methods(registry: RpcRegistry) -> RpcRegistry {
+    registry
+        .add_no_params("user.get", None, get_account)
+        .add("user.change_pwd", None, change_pwd)
+        .add("user.delete", None, delete_account)
+        .with_txn()
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ParamsUsername {
+    pub username: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ParamsForChangePwd {
+    pub pwd_old: String,
+    pub pwd_new: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ParamsForDeleteAccount {
+    pub password: String,
+}
+
+// region:    --- Public (no auth, called with the root ctx)
+/// Sign up a new user
+pub async fn signup(ctx: Ctx, mm: ModelManager, params: UserForCreate) -> Result<User> {
+    let id = UserBmc::create(&ctx, &mm, params).await?;
+    let user = UserBmc::get(&ctx, &mm, id).await?;
+
+    // Return the created user
+    Ok(user)
+}
+
+/// Check if a username can be used to sign up
+pub async fn check_username(ctx: Ctx, mm: ModelManager, params: ParamsUsername) -> Result<Value> {
+    let ParamsUsername { username } = params;
+
+    let available = UserBmc::is_username_available(&ctx, &mm, &username).await?;
+
+    Ok(json!({ "username": username, "available": available }))
+}
+// endregion: --- Public (no auth, called with the root ctx)
+
+/// Get the account of the ctx user
+pub async fn get_account(ctx: Ctx, mm: ModelManager) -> Result<User> {
+    let user = UserBmc::get(&ctx, &mm, ctx.user_id()).await?;
+
+    Ok(user)
+}
+
+/// Change the password of the ctx user (the old password is required).
+/// All its sessions are revoked and its tokens invalidated: the client logs in again.
+pub async fn change_pwd(ctx: Ctx, mm: ModelManager, params: ParamsForChangePwd) -> Result<Value> {
+    let ParamsForChangePwd { pwd_old, pwd_new } = params;
+
+    UserBmc::change_pwd(&ctx, &mm, ctx.user_id(), &pwd_old, &pwd_new).await?;
+
+    Ok(json!({ "success": true }))
+}
+
+/// Delete the account of the ctx user (the password is required)
+/// (Registered `with_txn`: the password check, get and delete are one unit)
+pub async fn delete_account(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsForDeleteAccount,
+) -> Result<User> {
+    let user_id = ctx.user_id();
+
+    UserBmc::check_pwd(&ctx, &mm, user_id, &params.password).await?;
+    let user = UserBmc::get(&ctx, &mm, user_id).await?;
+    UserBmc::delete(&ctx, &mm, user_id).await?;
+
+    // Return the deleted user
+    Ok(user)
+}
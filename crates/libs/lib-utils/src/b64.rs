@@ -28,10 +28,7 @@ pub enum Error {
 }
 
 impl core::fmt::Display for Error {
-    fn fmt(
-        &self, 
-        fmt: &mut core::fmt::Formatter
-    ) -> core::result::Result<(), core::fmt::Error> {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::result::Result<(), core::fmt::Error> {
         write!(fmt, "{self:?}")
     }
 }
@@ -11,6 +11,16 @@ pub fn get_env_parse<T: FromStr>(name: &'static str) -> Result<T> {
     val.parse::<T>().map_err(|_| Error::WrongFormat(name))
 }
 
+/// Parse the env value, which must be greater than zero
+pub fn get_env_parse_positive<T: FromStr + PartialOrd + Default>(name: &'static str) -> Result<T> {
+    let val = get_env_parse::<T>(name)?;
+    if val > T::default() {
+        Ok(val)
+    } else {
+        Err(Error::NotPositive(name))
+    }
+}
+
 pub fn get_env_b64u_as_u8s(name: &'static str) -> Result<Vec<u8>> {
     b64u_decode(&get_env(name)?).map_err(|_| Error::WrongFormat(name))
 }
@@ -22,13 +32,11 @@ pub type Result<T> = core::result::Result<T, Error>;
 pub enum Error {
     MissingEnv(&'static str),
     WrongFormat(&'static str),
+    NotPositive(&'static str),
 }
 
 impl core::fmt::Display for Error {
-    fn fmt(
-        &self, 
-        fmt: &mut core::fmt::Formatter
-    ) -> core::result::Result<(), core::fmt::Error> {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::result::Result<(), core::fmt::Error> {
         write!(fmt, "{self:?}")
     }
 }
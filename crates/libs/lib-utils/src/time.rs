@@ -1,6 +1,7 @@
-use time::{Duration, OffsetDateTime};
+use time::Duration;
 
 pub use time::format_description::well_known::Rfc3339;
+pub use time::OffsetDateTime;
 
 /// Return the current time in UTC
 pub fn now_utc() -> OffsetDateTime {
@@ -15,11 +16,14 @@ pub fn format_time(time: OffsetDateTime) -> String {
     time.format(&Rfc3339).expect("Wrong time format")
 }
 
+/// Return the current time in UTC plus the number of duration seconds
+pub fn now_utc_plus_sec(sec: i64) -> OffsetDateTime {
+    now_utc() + Duration::seconds(sec)
+}
+
 /// Return the current time in UTC plus the number of duration seconds as a string
 pub fn now_utc_plus_sec_str(sec: i64) -> String {
-    let now = now_utc();
-    let future = now + Duration::seconds(sec);
-    format_time(future)
+    format_time(now_utc_plus_sec(sec))
 }
 
 /// Parse a string as a UTC time
@@ -35,10 +39,7 @@ pub enum Error {
 }
 
 impl core::fmt::Display for Error {
-    fn fmt(
-        &self, 
-        fmt: &mut core::fmt::Formatter
-    ) -> core::result::Result<(), core::fmt::Error> {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::result::Result<(), core::fmt::Error> {
         write!(fmt, "{self:?}")
     }
 }
@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use serde_json::json;
 
 #[tokio::main]
@@ -11,6 +11,19 @@ async fn main() -> Result<()> {
     tracing::info!("Starting test 'test'");
     let ht = httpc_test::new_client("http://localhost:3000")?;
     ht.do_get("/index.html").await?.print().await?;
+
+    let req_check_username = ht.do_post(
+        "/api/v2/rpc/public",
+        json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "user.check_username",
+            "params": {
+                "username": "demo1"
+            }
+        }),
+    );
+    req_check_username.await?.print().await?;
     let req_login = ht.do_post(
         "/api/v2/login",
         json!({
@@ -22,42 +35,89 @@ async fn main() -> Result<()> {
 
     // ht.do_get("/hello").await?.print().await?;
 
-    let mut task_ids: Vec<i64> = Vec::new();
-    for i in 0..=4 {
-        let req_create_task = ht.do_post(
-            "/api/v2/rpc",
-            json!({
-                "id": 1,
-                "method": "task.create",
-                "params": {
-                    "data": {
-                        "title": format!("test_create_ok title {i}")
-                    }
+    let req_create_project = ht.do_post(
+        "/api/v2/rpc",
+        json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "project.create",
+            "params": {
+                "data": {
+                    "name": "test_create_ok project"
                 }
-            }),
-        );
-        let result = req_create_task.await?;
-        task_ids.push(result.json_value::<i64>("/result/id")?);
-    }
+            }
+        }),
+    );
+    let result = req_create_project.await?;
+    let project_id = result.json_value::<i64>("/result/id")?;
+
+    // Create the tasks in one call (one transaction)
+    let tasks_c: Vec<_> = (0..=4)
+        .map(|i| {
+            json!({
+                "project_id": project_id,
+                "title": format!("test_create_ok title {i}")
+            })
+        })
+        .collect();
+    let req_create_tasks = ht.do_post(
+        "/api/v2/rpc",
+        json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "task.create_many",
+            "params": {
+                "data": tasks_c
+            }
+        }),
+    );
+    let result = req_create_tasks.await?;
+    let task_ids = result
+        .json_value::<Vec<serde_json::Value>>("/result")?
+        .iter()
+        .map(|task| {
+            task["id"]
+                .as_i64()
+                .ok_or_else(|| anyhow!("task.create_many - no id in the task: {task}"))
+        })
+        .collect::<Result<Vec<i64>>>()?;
 
     let req_update_task = ht.do_post(
         "/api/v2/rpc",
         json!({
+            "jsonrpc": "2.0",
             "id": 1,
             "method": "task.update",
             "params": {
                 "id": task_ids[0],
                 "data": {
-                    "title": "test_create_ok title updated"
+                    "title": "test_create_ok title updated",
+                    "status": "in_progress",
+                    "priority": 3
                 }
             }
         }),
     );
     req_update_task.await?.print().await?;
 
+    // The update is in the activity log of the task
+    let req_list_task_activities = ht.do_post(
+        "/api/v2/rpc",
+        json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "task_activity.list",
+            "params": {
+                "filters": {"task_id": task_ids[0]}
+            }
+        }),
+    );
+    req_list_task_activities.await?.print().await?;
+
     let req_delete_task = ht.do_post(
         "/api/v2/rpc",
         json!({
+            "jsonrpc": "2.0",
             "id": 1,
             "method": "task.delete",
             "params": {
@@ -67,11 +127,36 @@ async fn main() -> Result<()> {
     );
     req_delete_task.await?.print().await?;
 
+    // The deleted task is in the trash until restored (or purged)
+    let req_list_deleted_tasks = ht.do_post(
+        "/api/v2/rpc",
+        json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "task.list_deleted",
+            "params": {}
+        }),
+    );
+    req_list_deleted_tasks.await?.print().await?;
+    let req_restore_task = ht.do_post(
+        "/api/v2/rpc",
+        json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "task.restore",
+            "params": {
+                "id": task_ids[1]
+            }
+        }),
+    );
+    req_restore_task.await?.print().await?;
+
     let req_list_tasks = ht.do_post(
         "/api/v2/rpc",
         json!({
+            "jsonrpc": "2.0",
             "id": 1,
-            "method": "task.list",
+            "method": "task.list_page",
             "params": {
                 "filters": [{
                     "title": {
@@ -81,20 +166,113 @@ async fn main() -> Result<()> {
                     "id": {"$in": [task_ids[2], task_ids[3]]}
                 }],
                 "list_options": {
-                    "order_bys": "!id"
+                    "order_bys": "!id",
+                    "limit": 2
+                },
+                "page_options": {
+                    "with_total": true
                 }
             }
         }),
     );
     req_list_tasks.await?.print().await?;
 
-    let req_logoff = ht.do_post(
+    // Batch (the notification, without id, gets no response)
+    let req_batch = ht.do_post(
+        "/api/v2/rpc",
+        json!([
+            {"jsonrpc": "2.0", "id": 1, "method": "project.list", "params": {}},
+            {"jsonrpc": "2.0", "id": 2, "method": "task.nope"},
+            {"jsonrpc": "2.0", "method": "task.list", "params": {}}
+        ]),
+    );
+    req_batch.await?.print().await?;
+
+    // Tasks REST API (the filters are URL-encoded JSON)
+    let task_url = format!("/api/v2/tasks/{}", task_ids[2]);
+    ht.do_get(&task_url).await?.print().await?;
+    ht.do_patch(&task_url, json!({"title": "test_create_ok title patched"}))
+        .await?
+        .print()
+        .await?;
+    let req_rest_list_tasks = ht.do_get(
+        "/api/v2/tasks?filters=%7B%22title%22%3A%7B%22%24startsWith%22%3A%22test_create_ok%22%7D%7D&order_bys=!id&limit=2",
+    );
+    req_rest_list_tasks.await?.print().await?;
+    let task_url = format!("/api/v2/tasks/{}", task_ids[3]);
+    ht.do_delete(&task_url).await?.print().await?;
+
+    // OpenRPC document of the methods
+    let req_discover = ht.do_post(
+        "/api/v2/rpc",
+        json!({"jsonrpc": "2.0", "id": 1, "method": "rpc.discover"}),
+    );
+    req_discover.await?.print().await?;
+
+    // Rotate the refresh token (and renew the web token)
+    let req_refresh = ht.do_post("/api/v2/refresh", json!({}));
+    req_refresh.await?.print().await?;
+
+    let req_list_sessions = ht.do_post(
+        "/api/v2/rpc",
+        json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "session.list",
+            "params": {
+                "filters": {
+                    "revoked_at": {"$null": true}
+                }
+            }
+        }),
+    );
+    req_list_sessions.await?.print().await?;
+
+    let _req_logoff = ht.do_post(
         "/api/v2/logoff",
         json!({
             "logoff": true
         }),
     );
-    // req_logoff.await?.print().await?;
+    // _req_logoff.await?.print().await?;
+
+    // Bearer token (non browser clients)
+    let ht_bearer = httpc_test::new_client("http://localhost:3000")?;
+    let res_login_token = ht_bearer
+        .do_post(
+            "/api/v2/login/token",
+            json!({
+                "username": "demo1",
+                "password": "demo"
+            }),
+        )
+        .await?;
+    let token = res_login_token.json_value::<String>("/result/token")?;
+    let res_list_projects = ht_bearer
+        .reqwest_client()
+        .post("http://localhost:3000/api/v2/rpc")
+        .bearer_auth(token)
+        .json(&json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "project.list",
+            "params": {}
+        }))
+        .send()
+        .await?;
+    tracing::info!(
+        "project.list with bearer token: {}",
+        res_list_projects.status()
+    );
+
+    let refresh_token = res_login_token.json_value::<String>("/result/refresh_token")?;
+    let req_refresh_token = ht_bearer.do_post(
+        "/api/v2/refresh/token",
+        json!({
+            "refresh_token": refresh_token
+        }),
+    );
+    req_refresh_token.await?.print().await?;
 
     tracing::info!("Finished test 'test'");
 
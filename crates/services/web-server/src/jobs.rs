@@ -0,0 +1,28 @@
+//! Background jobs of the service.
+
+use lib_core::ctx::Ctx;
+use lib_core::model::psql::task::TaskBmc;
+use lib_core::model::psql::ModelManager;
+use std::time::Duration;
+use tracing::{error, info};
+
+/// Interval between two purges of the task trash
+const TASK_TRASH_PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Spawn the job purging the tasks deleted for longer than the trash retention
+/// (`SERVICE_TASK_TRASH_RETENTION_SEC`)
+pub fn spawn_task_trash_purge(mm: ModelManager) {
+    tokio::spawn(async move {
+        let ctx = Ctx::root_ctx();
+        let mut interval = tokio::time::interval(TASK_TRASH_PURGE_INTERVAL);
+
+        loop {
+            interval.tick().await;
+            match TaskBmc::purge_trash(&ctx, &mm).await {
+                Ok(ids) if ids.is_empty() => {}
+                Ok(ids) => info!("{:<12} - task trash purge - {} purged", "JOB", ids.len()),
+                Err(err) => error!("{:<12} - task trash purge - {err:?}", "JOB"),
+            }
+        }
+    });
+}
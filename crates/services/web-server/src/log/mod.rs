@@ -13,7 +13,7 @@ pub async fn log_request(
     uuid: Uuid,
     req_method: Method,
     uri: Uri,
-    rpc_info: Option<&RpcInfo>,
+    rpc_infos: &[RpcInfo],
     ctx: Option<Ctx>,
     web_error: Option<&web::Error>,
     client_error: Option<ClientError>,
@@ -23,11 +23,30 @@ pub async fn log_request(
         .unwrap()
         .as_millis();
 
-    let error_type = web_error.map(|se| se.as_ref().to_string());
-    // CheckPoint
-    let error_data = serde_json::to_value(web_error)
-        .ok()
-        .and_then(|mut v| v.get_mut("data").map(|v| v.take()));
+    // The request error, else the first failed rpc call
+    // (the rpc errors are sent back as JSON-RPC error objects with a 200 OK)
+    let web_error = web_error.or_else(|| rpc_infos.iter().find_map(|rpc| rpc.error.as_ref()));
+    let (error_type, error_data) = error_type_and_data(web_error);
+
+    // One rpc call is logged inline, the calls of a batch as a list
+    let (rpc_id, rpc_method) = match rpc_infos {
+        [rpc] => (rpc_id_string(rpc), Some(rpc.method.to_string())),
+        _ => (None, None),
+    };
+    let rpc_calls = (rpc_infos.len() > 1).then(|| {
+        rpc_infos
+            .iter()
+            .map(|rpc| {
+                let (error_type, error_data) = error_type_and_data(rpc.error.as_ref());
+                RpcCallLog {
+                    rpc_id: rpc_id_string(rpc),
+                    rpc_method: rpc.method.to_string(),
+                    error_type,
+                    error_data,
+                }
+            })
+            .collect()
+    });
 
     // Create the RequestLogLine
     let log_line = RequestLogLine {
@@ -37,8 +56,9 @@ pub async fn log_request(
         http_path: uri.to_string(),
         http_method: req_method.to_string(),
 
-        rpc_id: rpc_info.and_then(|rpc| rpc.id.as_ref().map(|v| v.to_string())),
-        rpc_method: rpc_info.map(|rpc| rpc.method.to_string()),
+        rpc_id,
+        rpc_method,
+        rpc_calls,
 
         user_id: ctx.map(|c| c.user_id()),
 
@@ -55,6 +75,20 @@ pub async fn log_request(
     Ok(())
 }
 
+fn error_type_and_data(web_error: Option<&web::Error>) -> (Option<String>, Option<Value>) {
+    let error_type = web_error.map(|se| se.as_ref().to_string());
+    // CheckPoint
+    let error_data = serde_json::to_value(web_error)
+        .ok()
+        .and_then(|mut v| v.get_mut("data").map(|v| v.take()));
+
+    (error_type, error_data)
+}
+
+fn rpc_id_string(rpc_info: &RpcInfo) -> Option<String> {
+    rpc_info.id.as_ref().map(|v| v.to_string())
+}
+
 // Struct to represent a log line for a request.
 #[skip_serializing_none]
 #[derive(Serialize)]
@@ -72,9 +106,20 @@ struct RequestLogLine {
     // -- rpc info
     rpc_id: Option<String>,
     rpc_method: Option<String>,
+    rpc_calls: Option<Vec<RpcCallLog>>,
 
     // -- Errors attributes.
     client_error_type: Option<String>,
     error_type: Option<String>,
     error_data: Option<Value>,
 }
+
+// One rpc call of a batch.
+#[skip_serializing_none]
+#[derive(Serialize)]
+struct RpcCallLog {
+    rpc_id: Option<String>,
+    rpc_method: String,
+    error_type: Option<String>,
+    error_data: Option<Value>,
+}
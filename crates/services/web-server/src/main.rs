@@ -1,6 +1,7 @@
 /// Import the necessary modules
 mod config;
 mod error;
+mod jobs;
 mod log;
 mod web;
 // #[cfg(test)] // Commented during the early development
@@ -11,11 +12,13 @@ pub use self::error::{Error, Result};
 
 use crate::web::mw_auth::{mw_ctx_resolver, mw_require_auth};
 use crate::web::mw_res_map::main_response_mapper;
-use crate::web::{routes_hnstory, routes_login, routes_rpc, routes_static};
+use crate::web::{
+    routes_docs, routes_hnstory, routes_login, routes_rpc, routes_static, routes_tasks,
+};
 /// Import the necessary modules
-use lib_core::model::psql::ModelManager;
+use lib_core::model::psql::{migration, ModelManager};
 use lib_core::model::redis_cache::RedisManager;
-use lib_core::model::scylla::{initialize as initialize_scylla, ScyllaManager};
+use lib_core::model::scylla::ScyllaManager;
 
 use axum::http::{HeaderValue, Method};
 use axum::routing::get;
@@ -59,14 +62,16 @@ async fn main() -> Result<()> {
     // Initialize the Model Manager and wait for it to be ready
     let mm = ModelManager::new().await?;
 
+    // Apply the pending migrations (fails if an applied migration was modified or is unknown)
+    migration::migrate_up(&mm).await?;
+
+    // Purge the task trash periodically (deleted tasks are restorable until then)
+    jobs::spawn_task_trash_purge(mm.clone());
+
     // Initialize the Scylla Manager
+    // (applies the pending schema versions and prepares the statements)
     let sm: Arc<ScyllaManager> = ScyllaManager::new().await?;
 
-    // Initialize Scylla tables
-    initialize_scylla(sm.session())
-        .await
-        .map_err(Error::Scylla)?;
-
     // Initialize the Redis Manager
     let rm = RedisManager::initialize().await?;
 
@@ -82,10 +87,11 @@ async fn main() -> Result<()> {
             Method::GET,
             Method::POST,
             Method::PUT,
+            Method::PATCH,
             Method::DELETE,
             Method::OPTIONS,
         ])
-        .allow_headers([http::header::CONTENT_TYPE])
+        .allow_headers([http::header::CONTENT_TYPE, http::header::AUTHORIZATION])
         .allow_credentials(true);
 
     let trace_layer = TraceLayer::new_for_http().make_span_with(|request: &Request<_>| {
@@ -97,8 +103,11 @@ async fn main() -> Result<()> {
         }
     });
 
-    let routes_rpc =
-        routes_rpc::routes(mm.clone()).route_layer(middleware::from_fn(mw_require_auth));
+    // The public rpc routes (e.g., user.signup) are merged after the auth route_layer
+    let routes_rpc = routes_rpc::routes(mm.clone())
+        .merge(routes_tasks::routes(mm.clone()))
+        .route_layer(middleware::from_fn(mw_require_auth))
+        .merge(routes_rpc::public_routes(mm.clone()));
 
     let routes_hnstory = routes_hnstory::routes(sm.clone(), rm.clone());
 
@@ -124,6 +133,8 @@ async fn main() -> Result<()> {
         .nest("/api/v2/hnstories", routes_hnstory)
         .layer(cors.clone())
         .route("/health", get(handler_health))
+        // Add the OpenAPI document and the Swagger UI
+        .merge(routes_docs::routes())
         // Add a fallback service to serve static files
         .fallback_service(routes_static::serve_dir());
 
@@ -177,6 +188,12 @@ async fn shutdown_signal() {
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "health",
+    responses((status = 200, description = "The service is up", body = String, example = "OK"))
+)]
 async fn handler_health() -> &'static str {
     "OK"
 }
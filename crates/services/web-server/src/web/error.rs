@@ -2,6 +2,7 @@
 //! serde_as 를 사용하여 DisplayFromStr 을 사용합니다.
 
 use crate::web;
+use axum::extract::rejection::{JsonRejection, PathRejection, QueryRejection};
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use derive_more::From;
@@ -12,6 +13,7 @@ use serde_with::{serde_as, DisplayFromStr};
 use std::sync::Arc;
 use strum_macros::AsRefStr;
 use tracing::debug;
+use utoipa::ToSchema;
 
 pub type Result<T> = core::result::Result<T, Error>;
 
@@ -32,6 +34,35 @@ pub enum Error {
         user_id: i64,
     },
 
+    // -- Refresh
+    RefreshTokenNotInCookie,
+    RefreshTokenWrongFormat,
+    RefreshSessionNotFound,
+    RefreshFailValidate,
+    RefreshSessionRevoked {
+        session_id: i64,
+    },
+    RefreshSessionExpired {
+        session_id: i64,
+    },
+    RefreshTokenReused {
+        session_id: i64,
+    },
+
+    // -- Rest
+    PermissionDenied {
+        permission: &'static str,
+    },
+    QueryParamsInvalid {
+        detail: String,
+    },
+    PathParamsInvalid {
+        detail: String,
+    },
+    JsonBodyInvalid {
+        detail: String,
+    },
+
     // -- CtxExtError
     #[from]
     CtxExt(web::mw_auth::CtxExtError),
@@ -51,6 +82,33 @@ pub enum Error {
     SerdeJson(#[serde_as(as = "DisplayFromStr")] Arc<serde_json::Error>),
 }
 
+// region:    --- Extractor Rejections
+// (The extractors are wrapped with `WithRejection<_, Error>`, see the route handlers)
+impl From<QueryRejection> for Error {
+    fn from(rejection: QueryRejection) -> Self {
+        Error::QueryParamsInvalid {
+            detail: rejection.body_text(),
+        }
+    }
+}
+
+impl From<PathRejection> for Error {
+    fn from(rejection: PathRejection) -> Self {
+        Error::PathParamsInvalid {
+            detail: rejection.body_text(),
+        }
+    }
+}
+
+impl From<JsonRejection> for Error {
+    fn from(rejection: JsonRejection) -> Self {
+        Error::JsonBodyInvalid {
+            detail: rejection.body_text(),
+        }
+    }
+}
+// endregion: --- Extractor Rejections
+
 // region:    --- Axum IntoResponse
 impl IntoResponse for Error {
     fn into_response(self) -> Response {
@@ -89,14 +147,96 @@ impl Error {
             | LoginFailPwdNotMatching { .. } => (StatusCode::FORBIDDEN, ClientError::LOGIN_FAIL),
 
             // -- Auth
-            CtxExt(_) => (StatusCode::FORBIDDEN, ClientError::NO_AUTH),
+            CtxExt(_)
+            | RefreshTokenNotInCookie
+            | RefreshTokenWrongFormat
+            | RefreshSessionNotFound
+            | RefreshFailValidate
+            | RefreshSessionRevoked { .. }
+            | RefreshSessionExpired { .. }
+            | RefreshTokenReused { .. } => (StatusCode::FORBIDDEN, ClientError::NO_AUTH),
+
+            // -- Rest
+            PermissionDenied { permission } => (
+                StatusCode::FORBIDDEN,
+                ClientError::PERMISSION_DENIED { permission },
+            ),
+            QueryParamsInvalid { detail }
+            | PathParamsInvalid { detail }
+            | JsonBodyInvalid { detail } => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_PARAMS {
+                    detail: detail.clone(),
+                },
+            ),
 
             // -- Model
+            Model(
+                model_error @ (model::psql::Error::ListLimitOverMax { .. }
+                | model::psql::Error::ListLimitNotPositive { .. }
+                | model::psql::Error::ListCursorInvalid { .. }
+                | model::psql::Error::ListCursorOrderUnsupported
+                | model::psql::Error::TaskPriorityOutOfRange { .. }
+                | model::psql::Error::TaskDoneStatusConflict { .. }
+                | model::psql::Error::LabelNotInTaskProject { .. }),
+            ) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_PARAMS {
+                    detail: model_error.to_string(),
+                },
+            ),
+            Model(model::psql::Error::VersionConflict {
+                entity, id, actual, ..
+            }) => (
+                StatusCode::CONFLICT,
+                ClientError::VERSION_CONFLICT {
+                    entity,
+                    id: *id,
+                    version: *actual,
+                },
+            ),
             Model(model::psql::Error::EntityNotFound { entity, id }) => (
                 StatusCode::BAD_REQUEST,
                 ClientError::ENTITY_NOT_FOUND { entity, id: *id },
             ),
 
+            // -- Permission
+            Rpc(lib_rpc::Error::RpcPermissionDenied { permission, .. }) => (
+                StatusCode::FORBIDDEN,
+                ClientError::PERMISSION_DENIED { permission },
+            ),
+
+            // -- Account
+            Model(model::psql::Error::UserAlreadyExists { .. })
+            | Rpc(lib_rpc::Error::Model(model::psql::Error::UserAlreadyExists { .. })) => {
+                (StatusCode::BAD_REQUEST, ClientError::USERNAME_NOT_AVAILABLE)
+            }
+            Model(model::psql::Error::UsernameInvalid { .. })
+            | Rpc(lib_rpc::Error::Model(model::psql::Error::UsernameInvalid { .. })) => {
+                (StatusCode::BAD_REQUEST, ClientError::USERNAME_INVALID)
+            }
+            Model(model::psql::Error::UserPwdNotMatching { .. })
+            | Rpc(lib_rpc::Error::Model(model::psql::Error::UserPwdNotMatching { .. })) => {
+                (StatusCode::FORBIDDEN, ClientError::PWD_NOT_MATCHING)
+            }
+            Model(model::psql::Error::Pwd(
+                pwd_error @ (pwd::Error::PwdTooShort { .. }
+                | pwd::Error::PwdTooLong { .. }
+                | pwd::Error::PwdMissingLetter
+                | pwd::Error::PwdMissingDigit),
+            ))
+            | Rpc(lib_rpc::Error::Model(model::psql::Error::Pwd(
+                pwd_error @ (pwd::Error::PwdTooShort { .. }
+                | pwd::Error::PwdTooLong { .. }
+                | pwd::Error::PwdMissingLetter
+                | pwd::Error::PwdMissingDigit),
+            ))) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::PWD_TOO_WEAK {
+                    rule: pwd_error.to_string(),
+                },
+            ),
+
             // -- Fallback.
             _ => (
                 StatusCode::INTERNAL_SERVER_ERROR,
@@ -106,15 +246,37 @@ impl Error {
     }
 }
 
-#[derive(Debug, Serialize, AsRefStr)]
+#[derive(Debug, Serialize, AsRefStr, ToSchema)]
 #[serde(tag = "message", content = "detail")]
 // Personal Preference(Distinguish between the error types in the client error)
 #[allow(non_camel_case_types)]
 pub enum ClientError {
     LOGIN_FAIL,
     NO_AUTH,
+    PERMISSION_DENIED {
+        permission: &'static str,
+    },
+    INVALID_PARAMS {
+        detail: String,
+    },
     // About static
-    ENTITY_NOT_FOUND { entity: &'static str, id: i64 },
+    ENTITY_NOT_FOUND {
+        entity: &'static str,
+        id: i64,
+    },
+    // The entity was updated by someone else (`version` is its current version, reload it)
+    VERSION_CONFLICT {
+        entity: &'static str,
+        id: i64,
+        version: i64,
+    },
+    // About account
+    USERNAME_NOT_AVAILABLE,
+    USERNAME_INVALID,
+    PWD_NOT_MATCHING,
+    PWD_TOO_WEAK {
+        rule: String,
+    },
 
     SERVICE_ERROR,
 }
@@ -1,20 +1,28 @@
 mod error;
 pub mod mw_auth;
 pub mod mw_res_map;
+pub mod routes_docs;
 pub mod routes_hnstory;
 pub mod routes_login;
 pub mod routes_rpc;
 pub mod routes_static;
+pub mod routes_tasks;
 
 pub use self::error::ClientError;
 pub use self::error::{Error, Result};
-use lib_auth::token::generate_web_token;
+use lib_auth::token::{generate_web_token, RefreshToken};
 use tower_cookies::{Cookie, Cookies};
 use uuid::Uuid;
 
 /// The name of the authentication token cookie
 pub const AUTH_TOKEN: &str = "auth-token";
 
+/// The name of the refresh token cookie
+pub const REFRESH_TOKEN: &str = "refresh-token";
+
+/// The refresh token cookie is only sent to the login routes (refresh, logoff)
+const REFRESH_TOKEN_PATH: &str = "/api/v2";
+
 /// Set the token cookie
 fn set_token_cookie(cookies: &Cookies, user: &str, salt: Uuid) -> Result<()> {
     // Generate the web token
@@ -41,3 +49,22 @@ fn remove_token_cookie(cookies: &Cookies) -> Result<()> {
 
     Ok(())
 }
+
+/// Set the refresh token cookie
+fn set_refresh_token_cookie(cookies: &Cookies, token: &RefreshToken) {
+    let mut cookie = Cookie::new(REFRESH_TOKEN, token.to_string());
+    // Javascript can't access the cookie
+    cookie.set_http_only(true);
+    cookie.set_path(REFRESH_TOKEN_PATH);
+
+    cookies.add(cookie);
+}
+
+/// Remove the refresh token cookie
+fn remove_refresh_token_cookie(cookies: &Cookies) {
+    let mut cookie = Cookie::from(REFRESH_TOKEN);
+    // The cookie path should be the same as the one set
+    cookie.set_path(REFRESH_TOKEN_PATH);
+
+    cookies.remove(cookie);
+}
@@ -1,14 +1,15 @@
-use crate::web::{set_token_cookie, AUTH_TOKEN};
+use crate::web::AUTH_TOKEN;
 use crate::web::{Error, Result};
 use async_trait::async_trait;
 use axum::body::Body;
 use axum::extract::{FromRequestParts, State};
 use axum::http::request::Parts;
-use axum::http::Request;
+use axum::http::{header, HeaderMap, Request};
 use axum::middleware::Next;
 use axum::response::Response;
 use lib_auth::token::{validate_web_token, Token};
 use lib_core::ctx::Ctx;
+use lib_core::model::psql::role::RoleBmc;
 use lib_core::model::psql::user::{UserBmc, UserForAuth};
 use lib_core::model::psql::ModelManager;
 use serde::Serialize;
@@ -35,9 +36,14 @@ pub async fn mw_ctx_resolver(
 ) -> Result<Response> {
     debug!("{:<12} - mw_ctx_resolve", "MIDDLEWARE");
 
-    let ctx_ext_result = _ctx_resolve(mm, &cookies).await;
+    let token = token_from_request(req.headers(), &cookies);
+    let ctx_ext_result = match &token {
+        Ok((token, _)) => _ctx_resolve(mm, token).await,
+        Err(ex) => Err(ex.clone()),
+    };
 
-    if ctx_ext_result.is_err() && !matches!(ctx_ext_result, Err(CtxExtError::TokenNotInCookie)) {
+    // Only an invalid cookie token is removed (a bearer token is managed by the client)
+    if ctx_ext_result.is_err() && matches!(token, Ok((_, TokenSource::Cookie))) {
         cookies.remove(Cookie::from(AUTH_TOKEN))
     }
 
@@ -47,13 +53,36 @@ pub async fn mw_ctx_resolver(
     Ok(next.run(req).await)
 }
 
-async fn _ctx_resolve(mm: State<ModelManager>, cookies: &Cookies) -> CtxExtResult {
-    // Get Token String
-    let token = cookies
+enum TokenSource {
+    Header,
+    Cookie,
+}
+
+/// Get the token string from the `Authorization: Bearer <token>` header,
+/// or from the auth-token cookie if there is no Authorization header
+fn token_from_request(
+    headers: &HeaderMap,
+    cookies: &Cookies,
+) -> core::result::Result<(String, TokenSource), CtxExtError> {
+    if let Some(auth_header) = headers.get(header::AUTHORIZATION) {
+        let token = auth_header
+            .to_str()
+            .ok()
+            .and_then(|value| value.split_once(' '))
+            // The auth scheme is case-insensitive (RFC 7235)
+            .filter(|(scheme, token)| scheme.eq_ignore_ascii_case("Bearer") && !token.is_empty())
+            .map(|(_, token)| token.trim().to_string())
+            .ok_or(CtxExtError::AuthHeaderWrongFormat)?;
+        return Ok((token, TokenSource::Header));
+    }
+
+    cookies
         .get(AUTH_TOKEN)
-        .map(|c| c.value().to_string())
-        .ok_or(CtxExtError::TokenNotInCookie)?;
+        .map(|c| (c.value().to_string(), TokenSource::Cookie))
+        .ok_or(CtxExtError::TokenNotInRequest)
+}
 
+async fn _ctx_resolve(mm: State<ModelManager>, token: &str) -> CtxExtResult {
     // Parse Token
     let token: Token = token.parse().map_err(|_| CtxExtError::TokenWrongFormat)?;
 
@@ -66,13 +95,16 @@ async fn _ctx_resolve(mm: State<ModelManager>, cookies: &Cookies) -> CtxExtResul
     // Validate Token
     validate_web_token(&token, user.token_salt).map_err(|_| CtxExtError::FailValidate)?;
 
-    // Update Token
-    set_token_cookie(cookies, &user.username, user.token_salt)
-        .map_err(|_| CtxExtError::CannotSetTokenCookie)?;
+    // (The token is not re-issued here, it is renewed with the refresh token at /api/v2/refresh)
+
+    // Load the permissions of the user roles
+    let permissions = RoleBmc::list_permissions_for_user(&Ctx::root_ctx(), &mm, user.id)
+        .await
+        .map_err(|e| CtxExtError::ModelAccessError(e.to_string()))?;
 
     // Create CtxExtResult
     Ctx::new(user.id)
-        .map(CtxW)
+        .map(|ctx| CtxW(ctx.with_permissions(permissions)))
         .map_err(|ex| CtxExtError::CtxCreateFail(ex.to_string()))
 }
 
@@ -109,13 +141,13 @@ type CtxExtResult = core::result::Result<CtxW, CtxExtError>;
 
 #[derive(Clone, Serialize, Debug)]
 pub enum CtxExtError {
-    TokenNotInCookie,
+    TokenNotInRequest,
+    AuthHeaderWrongFormat,
     TokenWrongFormat,
 
     UserNotFound,
     ModelAccessError(String),
     FailValidate,
-    CannotSetTokenCookie,
 
     CtxNotInRequestExt,
     CtxCreateFail(String),
@@ -22,7 +22,7 @@ pub async fn main_response_mapper(
     debug!("{:<12} - main_response_mapper", "RES_MAPPER");
     let uuid = Uuid::new_v4();
 
-    let rpc_info = res.extensions().get::<Arc<RpcInfo>>();
+    let rpc_infos = res.extensions().get::<Arc<Vec<RpcInfo>>>();
 
     // -- Get the eventual response error.
     let web_error = res.extensions().get::<Arc<web::Error>>();
@@ -60,7 +60,9 @@ pub async fn main_response_mapper(
         uuid,
         req_method,
         uri,
-        rpc_info.map(Arc::as_ref),
+        rpc_infos
+            .map(|rpc_infos| rpc_infos.as_slice())
+            .unwrap_or_default(),
         ctx,
         web_error.map(Arc::as_ref),
         client_error,
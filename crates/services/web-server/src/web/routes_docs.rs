@@ -0,0 +1,145 @@
+//! OpenAPI 3 document of the HTTP routes, generated from the route handlers and payload types.
+//! Served as JSON at `/api-docs/openapi.json`, and browsable with the Swagger UI at `/api-docs`.
+//! (The JSON-RPC methods are described by the OpenRPC document of `rpc.discover`)
+
+use crate::web::{
+    routes_hnstory, routes_login, routes_rpc, routes_tasks, ClientError, AUTH_TOKEN, REFRESH_TOKEN,
+};
+use axum::Router;
+use serde_json::Value;
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi, ToSchema};
+use utoipa_swagger_ui::SwaggerUi;
+
+/// Security scheme names (referenced by the `security(...)` of the protected routes)
+pub const BEARER_AUTH: &str = "bearer_auth";
+pub const COOKIE_AUTH: &str = "cookie_auth";
+pub const REFRESH_TOKEN_COOKIE: &str = "refresh_token_cookie";
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "web-server API"),
+    paths(
+        crate::handler_health,
+        routes_login::api_login_handler,
+        routes_login::api_login_token_handler,
+        routes_login::api_refresh_handler,
+        routes_login::api_refresh_token_handler,
+        routes_login::api_logoff_handler,
+        routes_rpc::rpc_handler,
+        routes_rpc::public_rpc_handler,
+        routes_tasks::list_tasks_handler,
+        routes_tasks::create_task_handler,
+        routes_tasks::get_task_handler,
+        routes_tasks::update_task_handler,
+        routes_tasks::delete_task_handler,
+        routes_hnstory::list_hnstories,
+        routes_hnstory::get_hnstory,
+        routes_hnstory::search_hnstories,
+    ),
+    components(schemas(ClientError, ClientErrorBody)),
+    modifiers(&SecurityAddon),
+    tags(
+        (name = "auth", description = "Login, token refresh and logoff"),
+        (name = "rpc", description = "JSON-RPC 2.0 endpoints (see `rpc.discover`)"),
+        (name = "tasks", description = "Tasks REST API"),
+        (name = "hnstories", description = "Hacker News stories"),
+        (name = "health", description = "Service health"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Routes serving the OpenAPI document and the Swagger UI
+pub fn routes() -> Router {
+    Router::new()
+        .merge(SwaggerUi::new("/api-docs").url("/api-docs/openapi.json", ApiDoc::openapi()))
+}
+
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            BEARER_AUTH,
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+        components.add_security_scheme(
+            COOKIE_AUTH,
+            SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::new(AUTH_TOKEN))),
+        );
+        components.add_security_scheme(
+            REFRESH_TOKEN_COOKIE,
+            SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::new(REFRESH_TOKEN))),
+        );
+    }
+}
+
+// region:    --- Doc Types
+/// The error body of a failed request (built by `main_response_mapper` from the ClientError)
+#[allow(dead_code)]
+#[derive(ToSchema)]
+pub struct ClientErrorBody {
+    error: ClientErrorBodyError,
+}
+
+#[allow(dead_code)]
+#[derive(ToSchema)]
+struct ClientErrorBodyError {
+    /// The ClientError variant, e.g., `NO_AUTH`
+    message: String,
+    data: ClientErrorBodyData,
+}
+
+#[allow(dead_code)]
+#[derive(ToSchema)]
+struct ClientErrorBodyData {
+    req_uuid: String,
+    /// The ClientError detail (if any)
+    detail: Option<Value>,
+}
+// endregion: --- Doc Types
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{Context, Result};
+
+    #[test]
+    fn test_openapi_paths_ok() -> Result<()> {
+        // -- Exec
+        let openapi = ApiDoc::openapi();
+
+        // -- Check
+        let paths = &openapi.paths.paths;
+        for path in [
+            "/health",
+            "/api/v2/login",
+            "/api/v2/login/token",
+            "/api/v2/refresh",
+            "/api/v2/refresh/token",
+            "/api/v2/logoff",
+            "/api/v2/rpc",
+            "/api/v2/rpc/public",
+            "/api/v2/tasks",
+            "/api/v2/tasks/{id}",
+            "/api/v2/hnstories",
+            "/api/v2/hnstories/{id}",
+            "/api/v2/hnstories/search",
+        ] {
+            assert!(paths.contains_key(path), "should have path {path}");
+        }
+        let tasks_id = paths
+            .get("/api/v2/tasks/{id}")
+            .context("no tasks id path")?;
+        assert!(tasks_id.get.is_some() && tasks_id.patch.is_some() && tasks_id.delete.is_some());
+
+        let components = openapi.components.context("no components")?;
+        assert!(components.schemas.contains_key("ClientError"));
+        assert!(components.security_schemes.contains_key(BEARER_AUTH));
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
@@ -7,29 +7,42 @@ use axum::{
 };
 use lib_core::model::redis_cache::RedisManager;
 use lib_core::model::scylla::hnstory::{
-    select_all_hnstories_with_pagination, select_hnstory, PagingState,
+    select_all_hnstories_with_pagination, select_hnstory, HNStory, PagingState,
 };
-use lib_core::model::scylla::ScyllaManager;
+use lib_core::model::scylla::hnstory_query::{select_hnstories_by, HNStoryFilter, HNStorySort};
+use lib_core::model::scylla::hnstory_search::{self, HNStorySearchHit};
+use lib_core::model::scylla::{self, ScyllaManager};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tracing::{debug, error};
+use utoipa::{IntoParams, ToSchema};
 
 pub fn routes(sm: Arc<ScyllaManager>, rm: Arc<RedisManager>) -> Router {
     Router::new()
         .route("/", get(list_hnstories))
+        .route("/search", get(search_hnstories))
         .route("/:id", get(get_hnstory))
         .route("/", options(handle_options))
         .with_state((sm, rm))
 }
 
 // HNStory select handler
-async fn get_hnstory(
+#[utoipa::path(
+    get,
+    path = "/api/v2/hnstories/{id}",
+    tag = "hnstories",
+    params(("id" = String, Path, description = "Story id")),
+    responses(
+        (status = 200, description = "The story", body = HNStory),
+        (status = 404, description = "Story not found", body = String),
+        (status = 500, description = "Failed to retrieve story", body = String),
+    )
+)]
+pub(crate) async fn get_hnstory(
     State((sm, rm)): State<(Arc<ScyllaManager>, Arc<RedisManager>)>,
     Path(id): Path<String>,
 ) -> impl IntoResponse {
-    let session = sm.session();
-
-    match select_hnstory(session, rm.as_ref(), id).await {
+    match select_hnstory(sm.as_ref(), rm.as_ref(), id).await {
         Ok(stories) => {
             if stories.is_empty() {
                 (StatusCode::NOT_FOUND, "Story not found").into_response()
@@ -46,35 +59,96 @@ async fn get_hnstory(
 }
 
 // HNStory list select handler
-#[derive(Deserialize)]
+/// Number of stories of a page at most
+const PAGE_SIZE_MAX: u32 = 100;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct PaginationParams {
+    /// From 1 to 100
     page_size: u32,
+    /// The `next_paging_state` of the previous page
+    /// (a filtered page may be short, even empty: continue while it is set)
     paging_state: Option<String>,
+    /// Only the stories with this tag (e.g., `rust`, `show_hn`)
+    tag: Option<String>,
+    /// Only the stories of this author
+    author: Option<String>,
+    /// Only the stories with at least these points
+    min_points: Option<i32>,
+    /// Only the stories created since this unix time (seconds, 365 days ago at most, 31 for `top`)
+    since: Option<i64>,
+    /// `newest` (default) or `top` (the most points, within a week by default, not paged)
+    sort: Option<HNStorySort>,
+}
+
+impl PaginationParams {
+    /// Whether the stories are filtered or sorted (i.e., read from the query tables)
+    fn has_query(&self) -> bool {
+        self.tag.is_some()
+            || self.author.is_some()
+            || self.min_points.is_some()
+            || self.since.is_some()
+            || self.sort.is_some()
+    }
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct PaginatedResponse<T> {
     data: Vec<T>,
     next_paging_state: Option<String>,
 }
 
-async fn list_hnstories(
+#[utoipa::path(
+    get,
+    path = "/api/v2/hnstories",
+    tag = "hnstories",
+    params(PaginationParams),
+    responses(
+        (status = 200, description = "A page of stories", body = PaginatedResponse<HNStory>),
+        (status = 400, description = "Invalid page size or paging state", body = String),
+        (status = 500, description = "Failed to retrieve stories", body = String),
+    )
+)]
+pub(crate) async fn list_hnstories(
     State((sm, rm)): State<(Arc<ScyllaManager>, Arc<RedisManager>)>,
     Query(params): Query<PaginationParams>,
 ) -> impl IntoResponse {
     debug!("--> Route_HNStory: Listing HNStories with pagination");
-    let session = sm.session();
-
+    if !(1..=PAGE_SIZE_MAX).contains(&params.page_size) {
+        return (
+            StatusCode::BAD_REQUEST,
+            "The `page_size` should be from 1 to 100",
+        )
+            .into_response();
+    }
+    let page_size = params.page_size as i32;
+    let sort = params.sort.unwrap_or_default();
+    let has_query = params.has_query();
+    let filter = HNStoryFilter {
+        tag: params.tag,
+        author: params.author,
+        min_points: params.min_points,
+        since: params.since,
+    };
     let paging_state = params.paging_state.map(PagingState::new);
 
-    match select_all_hnstories_with_pagination(
-        session,
-        rm.as_ref(),
-        params.page_size as i32,
-        paging_state,
-    )
-    .await
-    {
+    let result = if has_query {
+        select_hnstories_by(
+            sm.as_ref(),
+            rm.as_ref(),
+            filter,
+            sort,
+            page_size,
+            paging_state,
+        )
+        .await
+    } else {
+        select_all_hnstories_with_pagination(sm.as_ref(), rm.as_ref(), page_size, paging_state)
+            .await
+    };
+
+    match result {
         Ok((stories, new_paging_state)) => {
             debug!(
                 "--> Route_HNStory: Successfully retrieved {} stories",
@@ -86,6 +160,9 @@ async fn list_hnstories(
             })
             .into_response()
         }
+        Err(scylla::Error::PagingStateInvalid(_)) => {
+            (StatusCode::BAD_REQUEST, "Invalid paging state").into_response()
+        }
         Err(e) => {
             error!(
                 "--> Route_HNStory: Failed to retrieve stories. Error: {:?}",
@@ -100,6 +177,63 @@ async fn list_hnstories(
     }
 }
 
+// HNStory search handler
+/// Number of search hits returned by default, and at most
+const SEARCH_LIMIT_DEFAULT: u32 = 20;
+const SEARCH_LIMIT_MAX: u32 = 100;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SearchParams {
+    /// The searched words (in the title, story_text or author)
+    q: String,
+    /// 20 by default, 100 at most
+    limit: Option<u32>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v2/hnstories/search",
+    tag = "hnstories",
+    params(SearchParams),
+    responses(
+        (status = 200, description = "The matching stories, the most relevant first", body = Vec<HNStorySearchHit>),
+        (status = 400, description = "Invalid search params", body = String),
+        (status = 500, description = "Failed to search stories", body = String),
+    )
+)]
+pub(crate) async fn search_hnstories(
+    State((sm, rm)): State<(Arc<ScyllaManager>, Arc<RedisManager>)>,
+    Query(params): Query<SearchParams>,
+) -> impl IntoResponse {
+    debug!("--> Route_HNStory: Searching HNStories: {}", params.q);
+    let limit = params.limit.unwrap_or(SEARCH_LIMIT_DEFAULT);
+    if params.q.trim().is_empty() || limit > SEARCH_LIMIT_MAX {
+        return (
+            StatusCode::BAD_REQUEST,
+            "Search needs a non-empty `q` and a `limit` of 100 at most",
+        )
+            .into_response();
+    }
+
+    match hnstory_search::search_hnstories(sm.as_ref(), rm.as_ref(), &params.q, limit as usize)
+        .await
+    {
+        Ok(hits) => Json(hits).into_response(),
+        Err(e) => {
+            error!(
+                "--> Route_HNStory: Failed to search stories. Error: {:?}",
+                e
+            );
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to search stories",
+            )
+                .into_response()
+        }
+    }
+}
+
 async fn handle_options() -> impl IntoResponse {
     StatusCode::NO_CONTENT
 }
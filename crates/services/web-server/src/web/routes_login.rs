@@ -1,35 +1,138 @@
-use crate::web::{self, remove_token_cookie, Error, Result};
+use crate::web::routes_docs::ClientErrorBody;
+use crate::web::{
+    self, remove_refresh_token_cookie, remove_token_cookie, Error, Result, REFRESH_TOKEN,
+};
 use axum::extract::State;
 use axum::routing::post;
 use axum::Json;
 use axum::Router;
+use axum_extra::extract::WithRejection;
 use lib_auth::pwd::{self, ContentToHash, SchemeStatus};
+use lib_auth::token::{
+    generate_refresh_token, generate_web_token, refresh_token_exp, validate_refresh_token_sign,
+    RefreshToken, Token,
+};
 use lib_core::ctx::Ctx;
-use lib_core::model::psql::user::{UserBmc, UserForLogin};
+use lib_core::model::psql::user::{UserBmc, UserForAuth, UserForLogin};
+use lib_core::model::psql::user_session::{UserSession, UserSessionBmc, UserSessionForCreate};
 use lib_core::model::psql::ModelManager;
+use lib_utils::time::now_utc;
 use serde::Deserialize;
 use serde_json::{json, Value};
 use tower_cookies::Cookies;
 use tracing::debug;
+use utoipa::ToSchema;
 
 /// Create the Login Routes and return the Router
 pub fn routes(mm: ModelManager) -> Router {
     // Create the Login Route with the POST method and the api_login handler
     Router::new()
         .route("/api/v2/login", post(api_login_handler))
+        .route("/api/v2/login/token", post(api_login_token_handler))
+        .route("/api/v2/refresh", post(api_refresh_handler))
+        .route("/api/v2/refresh/token", post(api_refresh_token_handler))
         .route("/api/v2/logoff", post(api_logoff_handler))
         .with_state(mm) // mm is passed to the State that can be accessed in the handler with State(ModelManager)
 }
 
 // region:    --- Login
 /// Login Handler that returns a JSON response with a status
-async fn api_login_handler(
+#[utoipa::path(
+    post,
+    path = "/api/v2/login",
+    tag = "auth",
+    request_body = LoginPayload,
+    responses(
+        (status = 200, description = "Logged in, the token cookies are set", body = Value,
+            example = json!({"result": {"success": true}})),
+        (status = 403, description = "LOGIN_FAIL", body = ClientErrorBody),
+    )
+)]
+pub(crate) async fn api_login_handler(
     State(mm): State<ModelManager>,
     cookies: Cookies,
-    Json(payload): Json<LoginPayload>,
+    WithRejection(Json(payload), _): WithRejection<Json<LoginPayload>, Error>,
 ) -> Result<Json<Value>> {
     debug!("{:<12} - api_login_handler", "HANDLER");
 
+    let user = validate_login(&mm, payload).await?;
+    let session = open_session(&mm, user.id).await?;
+
+    // Set the token cookies
+    web::set_token_cookie(&cookies, &user.username, user.token_salt)?;
+    let refresh_token = generate_refresh_token(session.uuid, session.generation, user.token_salt)?;
+    web::set_refresh_token_cookie(&cookies, &refresh_token);
+
+    // Create the response body
+    let body = Json(json!({
+        "result": {
+            "success": true
+        }
+    }));
+
+    Ok(body)
+}
+
+/// Token Login Handler that returns the web and refresh tokens in the JSON response (no cookie is set)
+/// (For the non browser clients, the token is sent back with the `Authorization: Bearer <token>` header,
+///  and the refresh token to `/api/v2/refresh/token`)
+#[utoipa::path(
+    post,
+    path = "/api/v2/login/token",
+    tag = "auth",
+    request_body = LoginPayload,
+    responses(
+        (status = 200, description = "The web and refresh tokens", body = Value,
+            example = json!({"result": {"token": "<token>", "token_type": "Bearer", "expires_at": "2024-01-01T00:15:00Z", "refresh_token": "<refresh_token>"}})),
+        (status = 403, description = "LOGIN_FAIL", body = ClientErrorBody),
+    )
+)]
+pub(crate) async fn api_login_token_handler(
+    State(mm): State<ModelManager>,
+    WithRejection(Json(payload), _): WithRejection<Json<LoginPayload>, Error>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_login_token_handler", "HANDLER");
+
+    let user = validate_login(&mm, payload).await?;
+    let session = open_session(&mm, user.id).await?;
+
+    let token = generate_web_token(&user.username, user.token_salt)?;
+    let refresh_token = generate_refresh_token(session.uuid, session.generation, user.token_salt)?;
+
+    Ok(tokens_body(&token, &refresh_token))
+}
+
+/// Open a new session (refresh token family) for the user
+async fn open_session(mm: &ModelManager, user_id: i64) -> Result<UserSession> {
+    let root_ctx = Ctx::root_ctx();
+
+    let session_id = UserSessionBmc::create(
+        &root_ctx,
+        mm,
+        UserSessionForCreate {
+            user_id,
+            expires_at: refresh_token_exp(),
+        },
+    )
+    .await?;
+
+    Ok(UserSessionBmc::get(&root_ctx, mm, session_id).await?)
+}
+
+/// The response body of the token login and refresh (for the non browser clients)
+fn tokens_body(token: &Token, refresh_token: &RefreshToken) -> Json<Value> {
+    Json(json!({
+        "result": {
+            "token": token.to_string(),
+            "token_type": "Bearer",
+            "expires_at": token.exp,
+            "refresh_token": refresh_token.to_string(),
+        }
+    }))
+}
+
+/// Validate the login credentials and return the logged in user
+async fn validate_login(mm: &ModelManager, payload: LoginPayload) -> Result<UserForLogin> {
     let LoginPayload {
         username,
         // password is the clear text password
@@ -38,13 +141,13 @@ async fn api_login_handler(
 
     let root_ctx = Ctx::root_ctx();
 
-    let user: UserForLogin = UserBmc::first_by_username(&root_ctx, &mm, &username)
+    let user: UserForLogin = UserBmc::first_by_username(&root_ctx, mm, &username)
         .await?
         .ok_or(Error::LoginFailUsernameNotFound)?;
 
     let user_id = user.id;
     // Check if the user has a password
-    let Some(pwd) = user.password else {
+    let Some(pwd) = user.password.clone() else {
         return Err(Error::LoginFailUserHasNoPwd { user_id });
     };
 
@@ -61,10 +164,54 @@ async fn api_login_handler(
     // Update password scheme if needed
     if let SchemeStatus::Outdated = scheme_status {
         debug!("Updating password scheme for user {}", user_id);
-        UserBmc::update_pwd(&root_ctx, &mm, user_id, &pwd_clear).await?;
+        UserBmc::update_pwd(&root_ctx, mm, user_id, &pwd_clear).await?;
     }
-    // Set the token cookie
+
+    Ok(user)
+}
+
+/// Login Payload Struct for Deserialization
+#[derive(Debug, Deserialize, ToSchema)]
+pub(crate) struct LoginPayload {
+    username: String,
+    password: String,
+}
+// endregion: --- Login
+
+// region:    --- Refresh
+/// Refresh Handler that rotates the refresh token and issues a new web token
+#[utoipa::path(
+    post,
+    path = "/api/v2/refresh",
+    tag = "auth",
+    security(("refresh_token_cookie" = [])),
+    responses(
+        (status = 200, description = "Rotated, the token cookies are set", body = Value,
+            example = json!({"result": {"success": true}})),
+        (status = 403, description = "NO_AUTH (a reused refresh token revokes its session)", body = ClientErrorBody),
+    )
+)]
+pub(crate) async fn api_refresh_handler(
+    State(mm): State<ModelManager>,
+    cookies: Cookies,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_refresh_handler", "HANDLER");
+
+    let refresh_token = cookies
+        .get(REFRESH_TOKEN)
+        .map(|c| c.value().to_string())
+        .ok_or(Error::RefreshTokenNotInCookie)?;
+
+    let rotated = rotate_refresh_token(&mm, &refresh_token).await;
+    if let Err(Error::RefreshTokenReused { .. }) = rotated {
+        remove_token_cookie(&cookies)?;
+        remove_refresh_token_cookie(&cookies);
+    }
+    let (user, refresh_token) = rotated?;
+
+    // Set the token cookies
     web::set_token_cookie(&cookies, &user.username, user.token_salt)?;
+    web::set_refresh_token_cookie(&cookies, &refresh_token);
 
     // Create the response body
     let body = Json(json!({
@@ -76,25 +223,133 @@ async fn api_login_handler(
     Ok(body)
 }
 
-/// Login Payload Struct for Deserialization
-#[derive(Debug, Deserialize)]
-struct LoginPayload {
-    username: String,
-    password: String,
+/// Token Refresh Handler that rotates the refresh token of the body and returns the new tokens
+/// (the non browser clients counterpart of `/api/v2/refresh`, no cookie is read or set)
+#[utoipa::path(
+    post,
+    path = "/api/v2/refresh/token",
+    tag = "auth",
+    request_body = RefreshPayload,
+    responses(
+        (status = 200, description = "Rotated, the new web and refresh tokens", body = Value,
+            example = json!({"result": {"token": "<token>", "token_type": "Bearer", "expires_at": "2024-01-01T00:15:00Z", "refresh_token": "<refresh_token>"}})),
+        (status = 403, description = "NO_AUTH (a reused refresh token revokes its session)", body = ClientErrorBody),
+    )
+)]
+pub(crate) async fn api_refresh_token_handler(
+    State(mm): State<ModelManager>,
+    WithRejection(Json(payload), _): WithRejection<Json<RefreshPayload>, Error>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_refresh_token_handler", "HANDLER");
+
+    let (user, refresh_token) = rotate_refresh_token(&mm, &payload.refresh_token).await?;
+    let token = generate_web_token(&user.username, user.token_salt)?;
+
+    Ok(tokens_body(&token, &refresh_token))
 }
-// endregion: --- Login
+
+/// Refresh Payload Struct for Deserialization
+#[derive(Debug, Deserialize, ToSchema)]
+pub(crate) struct RefreshPayload {
+    refresh_token: String,
+}
+
+/// Rotate the session of the refresh token, and return its user with the next refresh token.
+/// An older generation means the refresh token was already used (stolen or replayed),
+/// so the whole session (token family) is revoked.
+async fn rotate_refresh_token(
+    mm: &ModelManager,
+    refresh_token: &str,
+) -> Result<(UserForAuth, RefreshToken)> {
+    let root_ctx = Ctx::root_ctx();
+
+    let (token, session, user) = resolve_refresh_token(mm, refresh_token).await?;
+    let session_id = session.id;
+
+    if session.revoked_at.is_some() {
+        return Err(Error::RefreshSessionRevoked { session_id });
+    }
+    if session.expires_at <= now_utc() {
+        return Err(Error::RefreshSessionExpired { session_id });
+    }
+
+    let rotated = token.generation == session.generation
+        && UserSessionBmc::rotate(
+            &root_ctx,
+            mm,
+            session_id,
+            session.generation,
+            refresh_token_exp(),
+        )
+        .await?;
+    if !rotated {
+        UserSessionBmc::revoke(&root_ctx, mm, session_id, "refresh_token_reused").await?;
+        return Err(Error::RefreshTokenReused { session_id });
+    }
+
+    let refresh_token =
+        generate_refresh_token(session.uuid, session.generation + 1, user.token_salt)?;
+
+    Ok((user, refresh_token))
+}
+
+/// Parse the refresh token and return it with its session and user
+/// (The signature is validated, the session state is not)
+async fn resolve_refresh_token(
+    mm: &ModelManager,
+    refresh_token: &str,
+) -> Result<(RefreshToken, UserSession, UserForAuth)> {
+    let root_ctx = Ctx::root_ctx();
+
+    let token: RefreshToken = refresh_token
+        .parse()
+        .map_err(|_| Error::RefreshTokenWrongFormat)?;
+
+    let session = UserSessionBmc::first_by_uuid(&root_ctx, mm, token.session_uuid)
+        .await?
+        .ok_or(Error::RefreshSessionNotFound)?;
+
+    let user: UserForAuth = UserBmc::get(&root_ctx, mm, session.user_id).await?;
+    validate_refresh_token_sign(&token, user.token_salt).map_err(|_| Error::RefreshFailValidate)?;
+
+    Ok((token, session, user))
+}
+// endregion: --- Refresh
 
 // region:    --- Logoff
-/// Logoff Handler that returns a JSON response with a status
-async fn api_logoff_handler(
+/// Logoff Handler that revokes the session and returns a JSON response with a status
+#[utoipa::path(
+    post,
+    path = "/api/v2/logoff",
+    tag = "auth",
+    request_body = LogoffPayload,
+    responses(
+        (status = 200, description = "The token cookies are removed", body = Value,
+            example = json!({"result": {"logoff": true}})),
+    )
+)]
+pub(crate) async fn api_logoff_handler(
+    State(mm): State<ModelManager>,
     cookies: Cookies,
-    Json(payload): Json<LogoffPayload>,
+    WithRejection(Json(payload), _): WithRejection<Json<LogoffPayload>, Error>,
 ) -> Result<Json<Value>> {
     debug!("{:<12} - api_logoff_handler", "HANDLER");
 
     let should_logoff = payload.logoff;
     if should_logoff {
+        // Revoke the session of the refresh token cookie (if any)
+        let refresh_token = cookies.get(REFRESH_TOKEN).map(|c| c.value().to_string());
+        let resolved = match refresh_token {
+            Some(refresh_token) => resolve_refresh_token(&mm, &refresh_token).await.ok(),
+            None => None,
+        };
+        if let Some((_, session, _)) = resolved {
+            if session.revoked_at.is_none() {
+                UserSessionBmc::revoke(&Ctx::root_ctx(), &mm, session.id, "logoff").await?;
+            }
+        }
         let _ = remove_token_cookie(&cookies);
+        remove_refresh_token_cookie(&cookies);
     }
 
     let body = Json(json!({
@@ -107,8 +362,8 @@ async fn api_logoff_handler(
 }
 
 /// Logoff Payload Struct for Deserialization
-#[derive(Debug, Deserialize)]
-struct LogoffPayload {
+#[derive(Debug, Deserialize, ToSchema)]
+pub(crate) struct LogoffPayload {
     logoff: bool,
 }
 // endregion: --- Logoff
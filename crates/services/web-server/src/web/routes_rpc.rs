@@ -1,15 +1,18 @@
+use crate::web;
 use crate::web::mw_auth::CtxW;
-use crate::web::Result;
+use crate::web::routes_docs::ClientErrorBody;
+use axum::body::Bytes;
 use axum::extract::State;
+use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use axum::routing::post;
 use axum::{Json, Router};
-use lib_core::ctx::Ctx;
 use lib_core::model::psql::ModelManager;
-use lib_rpc::{exec_rpc, RpcRequest};
-use serde_json::{json, Value};
+use lib_rpc::{exec_public_rpc, exec_rpc, RpcError, RpcRequest, RpcResponse, INTERNAL_ERROR};
+use serde_json::Value;
+use std::future::Future;
 use std::sync::Arc;
-use tracing::debug;
+use tracing::{debug, error};
 
 /// Routes for JSON-RPC 2.0
 pub fn routes(mm: ModelManager) -> Router {
@@ -18,45 +21,212 @@ pub fn routes(mm: ModelManager) -> Router {
         .with_state(mm)
 }
 
+/// Routes for the JSON-RPC 2.0 methods that do not require auth (e.g., user.signup)
+pub fn public_routes(mm: ModelManager) -> Router {
+    Router::new()
+        .route("/rpc/public", post(public_rpc_handler))
+        .with_state(mm)
+}
+
 /// RPC Info
+/// (Set on the response as an `Arc<Vec<RpcInfo>>`, one per executed request object
+///  of the payload, for the request log line)
 #[derive(Debug)]
 pub struct RpcInfo {
     pub id: Option<Value>,
     pub method: String,
+    /// The error of the call
+    /// (Sent back as a JSON-RPC error object, so only kept for the log)
+    pub error: Option<web::Error>,
 }
 
 /// JSON-RPC 2.0 Handler
-async fn rpc_handler(
+/// (The methods, their params and results are described by the OpenRPC document of `rpc.discover`)
+#[utoipa::path(
+    post,
+    path = "/api/v2/rpc",
+    tag = "rpc",
+    security(("bearer_auth" = []), ("cookie_auth" = [])),
+    request_body(content = Value, description = "A JSON-RPC 2.0 request object or a batch array",
+        example = json!({"jsonrpc": "2.0", "id": 1, "method": "rpc.discover"})),
+    responses(
+        (status = 200, description = "A JSON-RPC 2.0 response object (result or error) or a batch array", body = Value),
+        (status = 204, description = "Notifications only (no id)"),
+        (status = 403, description = "NO_AUTH", body = ClientErrorBody),
+    )
+)]
+pub(crate) async fn rpc_handler(
     State(mm): State<ModelManager>,
     ctx: CtxW,
-    Json(rpc_req): Json<RpcRequest>,
+    body: Bytes,
 ) -> Response {
     let ctx = ctx.0;
-    let rpc_info = RpcInfo {
+
+    exec_rpc_payload(&body, |rpc_req| exec_rpc(ctx.clone(), mm.clone(), rpc_req)).await
+}
+
+/// Public JSON-RPC 2.0 Handler (no ctx)
+#[utoipa::path(
+    post,
+    path = "/api/v2/rpc/public",
+    tag = "rpc",
+    request_body(content = Value, description = "A JSON-RPC 2.0 request object or a batch array",
+        example = json!({"jsonrpc": "2.0", "id": 1, "method": "user.check_username", "params": {"username": "demo1"}})),
+    responses(
+        (status = 200, description = "A JSON-RPC 2.0 response object (result or error) or a batch array", body = Value),
+        (status = 204, description = "Notifications only (no id)"),
+    )
+)]
+pub(crate) async fn public_rpc_handler(State(mm): State<ModelManager>, body: Bytes) -> Response {
+    exec_rpc_payload(&body, |rpc_req| exec_public_rpc(mm.clone(), rpc_req)).await
+}
+
+/// Execute a JSON-RPC 2.0 payload (a request object or a batch array of request objects)
+/// (The errors are returned as JSON-RPC error objects with a 200 OK,
+///  and a payload of notifications only has no content)
+async fn exec_rpc_payload<F, Fut>(body: &[u8], exec_fn: F) -> Response
+where
+    F: Fn(RpcRequest) -> Fut,
+    Fut: Future<Output = lib_rpc::Result<Value>>,
+{
+    let Ok(payload) = serde_json::from_slice::<Value>(body) else {
+        return Json(RpcResponse::error(Value::Null, RpcError::parse_error())).into_response();
+    };
+
+    match payload {
+        // -- Batch
+        Value::Array(rpc_values) => {
+            if rpc_values.is_empty() {
+                let rpc_error = RpcError::invalid_request("empty batch");
+                return Json(RpcResponse::error(Value::Null, rpc_error)).into_response();
+            }
+
+            // The requests of a batch are executed in order
+            let mut rpc_responses = Vec::new();
+            let mut rpc_infos = Vec::new();
+            for rpc_value in rpc_values {
+                let (rpc_response, rpc_info) = exec_rpc_value(rpc_value, &exec_fn).await;
+                rpc_responses.extend(rpc_response);
+                rpc_infos.extend(rpc_info);
+            }
+
+            let mut res = if rpc_responses.is_empty() {
+                StatusCode::NO_CONTENT.into_response()
+            } else {
+                Json(rpc_responses).into_response()
+            };
+            res.extensions_mut().insert(Arc::new(rpc_infos));
+
+            res
+        }
+
+        // -- Single
+        rpc_value => {
+            let (rpc_response, rpc_info) = exec_rpc_value(rpc_value, &exec_fn).await;
+
+            let mut res = match rpc_response {
+                Some(rpc_response) => Json(rpc_response).into_response(),
+                None => StatusCode::NO_CONTENT.into_response(),
+            };
+            let rpc_infos: Vec<RpcInfo> = rpc_info.into_iter().collect();
+            res.extensions_mut().insert(Arc::new(rpc_infos));
+
+            res
+        }
+    }
+}
+
+/// Execute one request object
+/// (Return no response for a notification)
+async fn exec_rpc_value<F, Fut>(
+    rpc_value: Value,
+    exec_fn: &F,
+) -> (Option<RpcResponse>, Option<RpcInfo>)
+where
+    F: Fn(RpcRequest) -> Fut,
+    Fut: Future<Output = lib_rpc::Result<Value>>,
+{
+    let rpc_req = match RpcRequest::from_value(rpc_value) {
+        Ok(rpc_req) => rpc_req,
+        Err(rpc_response) => return (Some(rpc_response), None),
+    };
+
+    let mut rpc_info = RpcInfo {
         id: rpc_req.id.clone(),
         method: rpc_req.method.clone(),
+        error: None,
     };
+    debug!(
+        "{:<12} - exec_rpc_value - method: {}",
+        "HANDLER", rpc_info.method
+    );
 
-    let mut res = _rpc_handler(ctx, mm, rpc_req).await.into_response();
-    res.extensions_mut().insert(Arc::new(rpc_info));
+    let is_notification = rpc_req.is_notification();
+    let rpc_id = rpc_req.id.clone().unwrap_or_default();
+    let rpc_response = match exec_fn(rpc_req).await {
+        Ok(result) => RpcResponse::result(rpc_id, result),
+        Err(err) => {
+            let rpc_error = RpcError::from(&err);
+            // The unmapped errors are internal errors, the others are client errors
+            if rpc_error.code == INTERNAL_ERROR {
+                error!("{:<12} - rpc internal error - {err:?}", "HANDLER");
+            } else {
+                debug!("{:<12} - rpc error - {err:?}", "HANDLER");
+            }
+            rpc_info.error = Some(web::Error::Rpc(err));
+            RpcResponse::error(rpc_id, rpc_error)
+        }
+    };
 
-    res
+    if is_notification {
+        (None, Some(rpc_info))
+    } else {
+        (Some(rpc_response), Some(rpc_info))
+    }
 }
 
-/// RPC Handler
-async fn _rpc_handler(ctx: Ctx, mm: ModelManager, rpc_req: RpcRequest) -> Result<Json<Value>> {
-    let rpc_method = rpc_req.method.clone();
-    let rpc_id = rpc_req.id.clone();
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{Context, Result};
+    use serde_json::json;
 
-    debug!("{:<12} - _rpc_handler - method: {rpc_method}", "HANDLER");
+    #[tokio::test]
+    async fn test_exec_rpc_payload_batch_rpc_infos_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_body = json!([
+            {"jsonrpc": "2.0", "id": 1, "method": "ok"},
+            {"jsonrpc": "2.0", "id": 2, "method": "unknown"},
+            {"jsonrpc": "2.0", "method": "ok"},
+        ])
+        .to_string();
+        let exec_fn = |rpc_req: RpcRequest| async move {
+            match rpc_req.method.as_str() {
+                "ok" => Ok(json!("ok")),
+                method => Err(lib_rpc::Error::RpcMethodUnknown(method.to_string())),
+            }
+        };
 
-    let result = exec_rpc(ctx, mm, rpc_req).await?;
+        // -- Exec
+        let res = exec_rpc_payload(fx_body.as_bytes(), exec_fn).await;
 
-    // The benefit of using JSON-RPC is that the response is always in the same format and the client can easily parse it.
-    let body_response = json!({
-        "id": rpc_id,
-        "result": result,
-    });
+        // -- Check
+        assert_eq!(res.status(), StatusCode::OK);
+        let rpc_infos = res
+            .extensions()
+            .get::<Arc<Vec<RpcInfo>>>()
+            .context("Should have the rpc infos")?;
+        let methods: Vec<&str> = rpc_infos.iter().map(|rpc| rpc.method.as_str()).collect();
+        assert_eq!(methods, ["ok", "unknown", "ok"]);
+        assert!(rpc_infos[0].error.is_none());
+        assert!(matches!(
+            rpc_infos[1].error,
+            Some(web::Error::Rpc(lib_rpc::Error::RpcMethodUnknown(_)))
+        ));
+        assert!(rpc_infos[2].error.is_none());
 
-    Ok(Json(body_response))
+        Ok(())
+    }
 }
+// endregion: --- Tests
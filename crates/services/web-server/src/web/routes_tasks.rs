@@ -0,0 +1,256 @@
+use crate::web::mw_auth::CtxW;
+use crate::web::routes_docs::ClientErrorBody;
+use crate::web::{Error, Result};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use axum_extra::extract::WithRejection;
+use lib_core::ctx::Ctx;
+use lib_core::model::psql::role::perm::{TASK_READ, TASK_WRITE};
+use lib_core::model::psql::task::{Task, TaskBmc, TaskFilter, TaskForCreate, TaskForUpdate};
+use lib_core::model::psql::{ModelManager, Page, PageOptions};
+use modql::filter::ListOptions;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::debug;
+use utoipa::IntoParams;
+
+/// Routes for the tasks REST API (same auth and error mapping as the JSON-RPC API)
+pub fn routes(mm: ModelManager) -> Router {
+    Router::new()
+        .route("/tasks", get(list_tasks_handler).post(create_task_handler))
+        .route(
+            "/tasks/:id",
+            get(get_task_handler)
+                .patch(update_task_handler)
+                .delete(delete_task_handler),
+        )
+        .with_state(mm)
+}
+
+// region:    --- Handlers
+/// List the tasks of the ctx user
+/// e.g., `GET /api/v2/tasks?filters={"title":{"$startsWith":"test"}}&order_bys=!id&limit=10&with_total=true`
+#[utoipa::path(
+    get,
+    path = "/api/v2/tasks",
+    tag = "tasks",
+    security(("bearer_auth" = []), ("cookie_auth" = [])),
+    params(ListQuery),
+    responses(
+        (status = 200, description = "A page of tasks", body = Page<Task>),
+        (status = 400, description = "INVALID_PARAMS", body = ClientErrorBody),
+        (status = 403, description = "NO_AUTH or PERMISSION_DENIED", body = ClientErrorBody),
+    )
+)]
+pub(crate) async fn list_tasks_handler(
+    State(mm): State<ModelManager>,
+    ctx: CtxW,
+    WithRejection(Query(query), _): WithRejection<Query<ListQuery>, Error>,
+) -> Result<Json<Page<Task>>> {
+    debug!("{:<12} - list_tasks_handler", "HANDLER");
+    let ctx = ctx.0;
+    check_permission(&ctx, TASK_READ)?;
+
+    let (filters, list_options, page_options) = query.into_list_params::<TaskFilter>()?;
+    let page =
+        TaskBmc::list_page(&ctx, &mm, filters, Some(list_options), Some(page_options)).await?;
+
+    Ok(Json(page))
+}
+
+/// Get a task with the given id
+#[utoipa::path(
+    get,
+    path = "/api/v2/tasks/{id}",
+    tag = "tasks",
+    security(("bearer_auth" = []), ("cookie_auth" = [])),
+    params(("id" = i64, Path, description = "Task id")),
+    responses(
+        (status = 200, description = "The task", body = Task),
+        (status = 400, description = "ENTITY_NOT_FOUND", body = ClientErrorBody),
+        (status = 403, description = "NO_AUTH or PERMISSION_DENIED", body = ClientErrorBody),
+    )
+)]
+pub(crate) async fn get_task_handler(
+    State(mm): State<ModelManager>,
+    ctx: CtxW,
+    WithRejection(Path(id), _): WithRejection<Path<i64>, Error>,
+) -> Result<Json<Task>> {
+    debug!("{:<12} - get_task_handler", "HANDLER");
+    let ctx = ctx.0;
+    check_permission(&ctx, TASK_READ)?;
+
+    let task = TaskBmc::get(&ctx, &mm, id).await?;
+
+    Ok(Json(task))
+}
+
+/// Create a task and return it with `201 Created`
+#[utoipa::path(
+    post,
+    path = "/api/v2/tasks",
+    tag = "tasks",
+    security(("bearer_auth" = []), ("cookie_auth" = [])),
+    request_body = TaskForCreate,
+    responses(
+        (status = 201, description = "The created task", body = Task),
+        (status = 403, description = "NO_AUTH or PERMISSION_DENIED", body = ClientErrorBody),
+    )
+)]
+pub(crate) async fn create_task_handler(
+    State(mm): State<ModelManager>,
+    ctx: CtxW,
+    WithRejection(Json(task_c), _): WithRejection<Json<TaskForCreate>, Error>,
+) -> Result<(StatusCode, Json<Task>)> {
+    debug!("{:<12} - create_task_handler", "HANDLER");
+    let ctx = ctx.0;
+    check_permission(&ctx, TASK_WRITE)?;
+
+    // Create and read back in one transaction (rolled back on error, when dropped)
+    let mm = mm.new_with_txn();
+    mm.begin_txn().await?;
+    let id = TaskBmc::create(&ctx, &mm, task_c).await?;
+    let task = TaskBmc::get(&ctx, &mm, id).await?;
+    mm.commit_txn().await?;
+
+    Ok((StatusCode::CREATED, Json(task)))
+}
+
+/// Update a task with the given fields (the absent fields are not changed)
+#[utoipa::path(
+    patch,
+    path = "/api/v2/tasks/{id}",
+    tag = "tasks",
+    security(("bearer_auth" = []), ("cookie_auth" = [])),
+    params(("id" = i64, Path, description = "Task id")),
+    request_body = TaskForUpdate,
+    responses(
+        (status = 200, description = "The updated task", body = Task),
+        (status = 400, description = "ENTITY_NOT_FOUND", body = ClientErrorBody),
+        (status = 409, description = "VERSION_CONFLICT (the task was updated since `version`)", body = ClientErrorBody),
+        (status = 403, description = "NO_AUTH or PERMISSION_DENIED", body = ClientErrorBody),
+    )
+)]
+pub(crate) async fn update_task_handler(
+    State(mm): State<ModelManager>,
+    ctx: CtxW,
+    WithRejection(Path(id), _): WithRejection<Path<i64>, Error>,
+    WithRejection(Json(task_u), _): WithRejection<Json<TaskForUpdate>, Error>,
+) -> Result<Json<Task>> {
+    debug!("{:<12} - update_task_handler", "HANDLER");
+    let ctx = ctx.0;
+    check_permission(&ctx, TASK_WRITE)?;
+
+    // Update and read back in one transaction (rolled back on error, when dropped)
+    let mm = mm.new_with_txn();
+    mm.begin_txn().await?;
+    TaskBmc::update(&ctx, &mm, id, task_u).await?;
+    let task = TaskBmc::get(&ctx, &mm, id).await?;
+    mm.commit_txn().await?;
+
+    Ok(Json(task))
+}
+
+/// Delete a task (moved to the trash, see `task.restore`) and return `204 No Content`
+#[utoipa::path(
+    delete,
+    path = "/api/v2/tasks/{id}",
+    tag = "tasks",
+    security(("bearer_auth" = []), ("cookie_auth" = [])),
+    params(("id" = i64, Path, description = "Task id")),
+    responses(
+        (status = 204, description = "Moved to the trash"),
+        (status = 400, description = "ENTITY_NOT_FOUND", body = ClientErrorBody),
+        (status = 403, description = "NO_AUTH or PERMISSION_DENIED", body = ClientErrorBody),
+    )
+)]
+pub(crate) async fn delete_task_handler(
+    State(mm): State<ModelManager>,
+    ctx: CtxW,
+    WithRejection(Path(id), _): WithRejection<Path<i64>, Error>,
+) -> Result<StatusCode> {
+    debug!("{:<12} - delete_task_handler", "HANDLER");
+    let ctx = ctx.0;
+    check_permission(&ctx, TASK_WRITE)?;
+
+    TaskBmc::delete(&ctx, &mm, id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn check_permission(ctx: &Ctx, permission: &'static str) -> Result<()> {
+    if ctx.has_permission(permission) {
+        Ok(())
+    } else {
+        Err(Error::PermissionDenied { permission })
+    }
+}
+// endregion: --- Handlers
+
+// region:    --- List Query
+/// The query string of the list routes
+/// (Same filters and list options as the `task.list_page` RPC method)
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct ListQuery {
+    /// One filter object or an array of filter objects (ORed), as JSON,
+    /// e.g., `{"title":{"$startsWith":"test"}}`
+    filters: Option<String>,
+    limit: Option<i64>,
+    /// Not supported by the keyset paging (an invalid params error)
+    offset: Option<i64>,
+    /// `id` or `!id` (descending), the only orders of the keyset paging
+    order_bys: Option<String>,
+    /// The `next_cursor` of the previous page (ordered by `id` or `!id`, without offset)
+    cursor: Option<String>,
+    /// Count all the tasks matching the filters
+    with_total: Option<bool>,
+}
+
+impl ListQuery {
+    fn into_list_params<F>(self) -> Result<(Option<Vec<F>>, ListOptions, PageOptions)>
+    where
+        F: DeserializeOwned,
+    {
+        let filters = self
+            .filters
+            .map(|filters| parse_filters::<F>(&filters))
+            .transpose()
+            .map_err(|ex| Error::QueryParamsInvalid {
+                detail: ex.to_string(),
+            })?;
+
+        let order_bys = self.order_bys.map(|order_bys| {
+            order_bys
+                .split(',')
+                .map(str::trim)
+                .filter(|order_by| !order_by.is_empty())
+                .collect::<Vec<_>>()
+                .into()
+        });
+
+        let list_options = ListOptions {
+            limit: self.limit,
+            offset: self.offset,
+            order_bys,
+        };
+
+        let page_options = PageOptions {
+            cursor: self.cursor,
+            with_total: self.with_total,
+        };
+
+        Ok((filters, list_options, page_options))
+    }
+}
+
+fn parse_filters<F: DeserializeOwned>(filters: &str) -> serde_json::Result<Vec<F>> {
+    match serde_json::from_str::<Value>(filters)? {
+        Value::Array(filters) => filters.into_iter().map(serde_json::from_value).collect(),
+        filter => Ok(vec![serde_json::from_value(filter)?]),
+    }
+}
+// endregion: --- List Query
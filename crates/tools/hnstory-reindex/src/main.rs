@@ -0,0 +1,22 @@
+use anyhow::Result;
+use lib_core::model::scylla::hnstory::reindex_hnstories;
+use lib_core::model::scylla::ScyllaManager;
+use tracing_subscriber::EnvFilter;
+
+/// Add the stored HN stories to the search index and the query tables
+/// (e.g., once after deploying them, the new stories are added to both when added)
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .without_time()
+        .with_target(false)
+        .with_env_filter(EnvFilter::from_default_env())
+        .init();
+
+    // Connects with the SCYLLA_DB_* env (and applies the pending migrations)
+    let sm = ScyllaManager::new().await?;
+    let count = reindex_hnstories(&sm).await?;
+    println!("\nReindexed HN stories: {count}");
+
+    Ok(())
+}
@@ -1,6 +0,0 @@
--- Create the Keyspace
-CREATE KEYSPACE IF NOT EXISTS fast_logger
-WITH REPLICATION = {
-  'class': 'NetworkTopologyStrategy',
-  'replication_factor': 1
-};
\ No newline at end of file
@@ -0,0 +1,3 @@
+-- Add a term of a story to the search index
+INSERT INTO hnstory_term (term, story_id, weight)
+VALUES (?, ?, ?);
\ No newline at end of file
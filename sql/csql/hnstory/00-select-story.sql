@@ -1,3 +0,0 @@
--- Get story by id
-SELECT * FROM fast_logger.hnstory
-WHERE id = ?;
\ No newline at end of file
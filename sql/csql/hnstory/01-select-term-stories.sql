@@ -0,0 +1,3 @@
+-- Get the stories of a term (paged)
+SELECT story_id, weight FROM hnstory_term
+WHERE term = ?;
\ No newline at end of file
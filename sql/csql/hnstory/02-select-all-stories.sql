@@ -1 +0,0 @@
-SELECT * FROM fast_logger.hnstory LIMIT 100;
\ No newline at end of file
@@ -0,0 +1,5 @@
+-- Get the stories of a tag, newest first (created since ?, with ? points at least)
+SELECT id, title, author, url, story_text, tags, points, created_at
+FROM hnstory_by_tag
+WHERE tag = ? AND created_at >= ? AND points >= ?
+ALLOW FILTERING;
\ No newline at end of file
@@ -1,2 +0,0 @@
-SELECT id, title, author, url, story_text, tags, points
-FROM fast_logger.hnstory
\ No newline at end of file
@@ -0,0 +1,4 @@
+-- Create the Keyspace
+-- (the keyspace and replication placeholders are replaced by SERVICE_SCYLLA_KEYSPACE and SERVICE_SCYLLA_REPLICATION)
+CREATE KEYSPACE IF NOT EXISTS {keyspace}
+WITH REPLICATION = {replication};
@@ -1,5 +1,5 @@
--- Create the schema
-CREATE TABLE IF NOT EXISTS fast_logger.hnstory (
+-- Create the stories table
+CREATE TABLE IF NOT EXISTS hnstory (
    id text PRIMARY KEY,
    title text,
    author text,
@@ -7,4 +7,4 @@ CREATE TABLE IF NOT EXISTS fast_logger.hnstory (
    story_text text,
    tags list<text>,
    points int
-);
\ No newline at end of file
+);
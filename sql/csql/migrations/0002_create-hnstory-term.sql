@@ -0,0 +1,8 @@
+-- Create the search index of the stories
+-- (an inverted index: the stories of each term, weighted by the fields it appears in)
+CREATE TABLE IF NOT EXISTS hnstory_term (
+   term text,
+   story_id text,
+   weight int,
+   PRIMARY KEY (term, story_id)
+);
\ No newline at end of file
@@ -0,0 +1,2 @@
+-- Add the creation time of the stories (unix time, in seconds)
+ALTER TABLE hnstory ADD created_at bigint;
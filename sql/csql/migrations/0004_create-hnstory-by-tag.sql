@@ -0,0 +1,14 @@
+-- Create the stories by tag query table (newest first)
+-- (a story is in the partition of each of its tags)
+CREATE TABLE IF NOT EXISTS hnstory_by_tag (
+   tag text,
+   id text,
+   title text,
+   author text,
+   url text,
+   story_text text,
+   tags list<text>,
+   points int,
+   created_at bigint,
+   PRIMARY KEY ((tag), created_at, id)
+) WITH CLUSTERING ORDER BY (created_at DESC, id ASC);
\ No newline at end of file
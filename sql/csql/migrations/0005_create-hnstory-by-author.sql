@@ -0,0 +1,12 @@
+-- Create the stories by author query table (newest first)
+CREATE TABLE IF NOT EXISTS hnstory_by_author (
+   id text,
+   title text,
+   author text,
+   url text,
+   story_text text,
+   tags list<text>,
+   points int,
+   created_at bigint,
+   PRIMARY KEY ((author), created_at, id)
+) WITH CLUSTERING ORDER BY (created_at DESC, id ASC);
\ No newline at end of file
@@ -0,0 +1,14 @@
+-- Create the stories by day query table (newest first)
+-- (day is the UTC date of created_at, e.g., '2024-06-30')
+CREATE TABLE IF NOT EXISTS hnstory_by_day (
+   day text,
+   id text,
+   title text,
+   author text,
+   url text,
+   story_text text,
+   tags list<text>,
+   points int,
+   created_at bigint,
+   PRIMARY KEY ((day), created_at, id)
+) WITH CLUSTERING ORDER BY (created_at DESC, id ASC);
\ No newline at end of file
@@ -0,0 +1,3 @@
+-- Drop the schema version table of the previous schema versioning
+-- (replaced by the schema_migration table, see `migration`)
+DROP TABLE IF EXISTS schema_version;
@@ -0,0 +1,7 @@
+-- Create the migration table (the applied migrations, see `migration::MIGRATIONS`)
+CREATE TABLE IF NOT EXISTS schema_migration (
+   version int PRIMARY KEY,
+   name text,
+   checksum text,
+   applied_at bigint
+);
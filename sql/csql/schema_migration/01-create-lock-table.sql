@@ -0,0 +1,5 @@
+-- Create the migration lock table (one migration runner at a time, see `04-lock.sql`)
+CREATE TABLE IF NOT EXISTS schema_migration_lock (
+   name text PRIMARY KEY,
+   owner text
+);
@@ -0,0 +1,2 @@
+-- Get the applied migrations
+SELECT version, checksum FROM schema_migration;
@@ -0,0 +1,3 @@
+-- Record an applied migration
+INSERT INTO schema_migration (version, name, checksum, applied_at)
+VALUES (?, ?, ?, ?);
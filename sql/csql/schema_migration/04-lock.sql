@@ -0,0 +1,5 @@
+-- Take the migration lock (lightweight transaction, released after ? seconds if not unlocked)
+INSERT INTO schema_migration_lock (name, owner)
+VALUES ('migration', ?)
+IF NOT EXISTS
+USING TTL ?;
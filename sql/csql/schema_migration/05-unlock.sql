@@ -0,0 +1,4 @@
+-- Release the migration lock (if still owned)
+DELETE FROM schema_migration_lock
+WHERE name = 'migration'
+IF owner = ?;
@@ -0,0 +1,3 @@
+-- Get a column of the schema (e.g., to check that an added column exists)
+SELECT column_name FROM system_schema.columns
+WHERE keyspace_name = ? AND table_name = ? AND column_name = ?;
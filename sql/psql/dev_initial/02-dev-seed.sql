@@ -1,2 +1,9 @@
 -- Insert demo1 user
-INSERT INTO "user" (username) VALUES ('demo1');
\ No newline at end of file
+INSERT INTO "user" (username, cid, ctime, mid, mtime) VALUES ('demo1', 0, now(), 0, now());
+-- demo1 is an admin
+INSERT INTO user_role (user_id, role_id) SELECT "user".id, role.id FROM "user", role WHERE "user".username = 'demo1' AND role.name = 'admin';
+
+-- Insert demo2 user (the non owner of the ownership tests)
+INSERT INTO "user" (username, cid, ctime, mid, mtime) VALUES ('demo2', 0, now(), 0, now());
+-- demo2 is a member
+INSERT INTO user_role (user_id, role_id) SELECT "user".id, role.id FROM "user", role WHERE "user".username = 'demo2' AND role.name = 'member';
@@ -0,0 +1,3 @@
+-- Drop the tables in the reverse order of creation
+DROP TABLE IF EXISTS task;
+DROP TABLE IF EXISTS "user";
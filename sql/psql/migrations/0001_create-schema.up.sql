@@ -12,4 +12,4 @@ CREATE TABLE IF NOT EXISTS task (
     id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
     title VARCHAR(256) NOT NULL,
     done bool NOT NULL DEFAULT FALSE
-);
\ No newline at end of file
+);
@@ -0,0 +1,3 @@
+ALTER TABLE task DROP CONSTRAINT IF EXISTS fk_task_project;
+ALTER TABLE task DROP COLUMN IF EXISTS project_id;
+DROP TABLE IF EXISTS project;
@@ -0,0 +1,34 @@
+-- Create Project Table
+CREATE TABLE IF NOT EXISTS project (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+    owner_id BIGINT NOT NULL,
+    name VARCHAR(256) NOT NULL,
+
+    -- Timestamps
+    cid BIGINT NOT NULL,
+    ctime TIMESTAMP WITH TIME ZONE NOT NULL,
+    mid BIGINT NOT NULL,
+    mtime TIMESTAMP WITH TIME ZONE NOT NULL,
+
+    CONSTRAINT fk_project_owner FOREIGN KEY (owner_id) REFERENCES "user"(id) ON DELETE CASCADE
+);
+
+-- Add the Task Project
+ALTER TABLE task ADD COLUMN IF NOT EXISTS project_id BIGINT;
+
+-- The existing tasks are moved to an 'Imported' project owned by the first user
+-- (fails if there are tasks but no user to own them)
+WITH imported AS (
+    INSERT INTO project (owner_id, name, cid, ctime, mid, mtime)
+    SELECT min(id), 'Imported', 0, now(), 0, now()
+    FROM "user"
+    HAVING EXISTS (SELECT 1 FROM task WHERE project_id IS NULL)
+    RETURNING id
+)
+UPDATE task SET project_id = imported.id
+FROM imported
+WHERE task.project_id IS NULL;
+
+ALTER TABLE task ALTER COLUMN project_id SET NOT NULL;
+ALTER TABLE task
+    ADD CONSTRAINT fk_task_project FOREIGN KEY (project_id) REFERENCES project(id) ON DELETE CASCADE;
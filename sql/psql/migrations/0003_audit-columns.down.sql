@@ -0,0 +1,11 @@
+ALTER TABLE task
+    DROP COLUMN IF EXISTS mtime,
+    DROP COLUMN IF EXISTS mid,
+    DROP COLUMN IF EXISTS ctime,
+    DROP COLUMN IF EXISTS cid;
+
+ALTER TABLE "user"
+    DROP COLUMN IF EXISTS mtime,
+    DROP COLUMN IF EXISTS mid,
+    DROP COLUMN IF EXISTS ctime,
+    DROP COLUMN IF EXISTS cid;
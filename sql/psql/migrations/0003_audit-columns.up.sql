@@ -0,0 +1,26 @@
+-- Add the Timestamps (creator/modifier id and time)
+-- (the existing rows get the root user id 0 and the migration time)
+ALTER TABLE "user"
+    ADD COLUMN IF NOT EXISTS cid BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS ctime TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+    ADD COLUMN IF NOT EXISTS mid BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS mtime TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now();
+
+ALTER TABLE task
+    ADD COLUMN IF NOT EXISTS cid BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS ctime TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+    ADD COLUMN IF NOT EXISTS mid BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS mtime TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now();
+
+-- The new rows get them from the ctx (see `base::create`)
+ALTER TABLE "user"
+    ALTER COLUMN cid DROP DEFAULT,
+    ALTER COLUMN ctime DROP DEFAULT,
+    ALTER COLUMN mid DROP DEFAULT,
+    ALTER COLUMN mtime DROP DEFAULT;
+
+ALTER TABLE task
+    ALTER COLUMN cid DROP DEFAULT,
+    ALTER COLUMN ctime DROP DEFAULT,
+    ALTER COLUMN mid DROP DEFAULT,
+    ALTER COLUMN mtime DROP DEFAULT;
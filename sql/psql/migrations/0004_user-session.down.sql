@@ -0,0 +1,2 @@
+-- Drop the User Session Table
+DROP TABLE IF EXISTS user_session;
@@ -0,0 +1,22 @@
+-- Create User Session Table
+-- (one row per login, i.e., per refresh token family)
+CREATE TABLE IF NOT EXISTS user_session (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+    uuid uuid NOT NULL UNIQUE DEFAULT gen_random_uuid(),
+    user_id BIGINT NOT NULL,
+    -- Incremented on each refresh token rotation
+    generation BIGINT NOT NULL DEFAULT 0,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    revoked_at TIMESTAMP WITH TIME ZONE,
+    revoke_reason VARCHAR(64),
+
+    -- Timestamps
+    cid BIGINT NOT NULL,
+    ctime TIMESTAMP WITH TIME ZONE NOT NULL,
+    mid BIGINT NOT NULL,
+    mtime TIMESTAMP WITH TIME ZONE NOT NULL,
+
+    CONSTRAINT fk_user_session_user FOREIGN KEY (user_id) REFERENCES "user"(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_session_user_id ON user_session (user_id);
@@ -0,0 +1,51 @@
+-- Create Role Table
+CREATE TABLE IF NOT EXISTS role (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+    name VARCHAR(64) NOT NULL UNIQUE
+);
+
+-- Create Role Permission Table
+-- (permission format: `<resource>:<action>`, e.g., `task:write`)
+CREATE TABLE IF NOT EXISTS role_permission (
+    role_id BIGINT NOT NULL,
+    permission VARCHAR(64) NOT NULL,
+
+    PRIMARY KEY (role_id, permission),
+    CONSTRAINT fk_role_permission_role FOREIGN KEY (role_id) REFERENCES role(id) ON DELETE CASCADE
+);
+
+-- Create User Role Table
+CREATE TABLE IF NOT EXISTS user_role (
+    user_id BIGINT NOT NULL,
+    role_id BIGINT NOT NULL,
+
+    PRIMARY KEY (user_id, role_id),
+    CONSTRAINT fk_user_role_user FOREIGN KEY (user_id) REFERENCES "user"(id) ON DELETE CASCADE,
+    CONSTRAINT fk_user_role_role FOREIGN KEY (role_id) REFERENCES role(id) ON DELETE CASCADE
+);
+
+-- Built-in roles
+INSERT INTO role (name) VALUES ('viewer'), ('member'), ('admin');
+
+INSERT INTO role_permission (role_id, permission)
+SELECT role.id, perm.permission
+FROM role
+JOIN (VALUES
+    ('viewer', 'project:read'),
+    ('viewer', 'task:read'),
+    ('member', 'project:read'),
+    ('member', 'project:write'),
+    ('member', 'task:read'),
+    ('member', 'task:write'),
+    ('admin', 'project:read'),
+    ('admin', 'project:write'),
+    ('admin', 'task:read'),
+    ('admin', 'task:write'),
+    ('admin', 'role:manage')
+) AS perm (role_name, permission) ON perm.role_name = role.name;
+
+-- The existing users keep their access as members
+INSERT INTO user_role (user_id, role_id)
+SELECT "user".id, role.id
+FROM "user", role
+WHERE role.name = 'member';
@@ -0,0 +1,3 @@
+-- Add the Task Version
+-- (incremented on each update, an update can require the version read by the client)
+ALTER TABLE task ADD COLUMN IF NOT EXISTS version BIGINT NOT NULL DEFAULT 1;
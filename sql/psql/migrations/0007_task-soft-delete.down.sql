@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_task_deleted_at;
+ALTER TABLE task DROP COLUMN IF EXISTS deleted_at;
@@ -0,0 +1,5 @@
+-- Add the Task Soft Delete
+-- (a deleted task keeps its row until purged, so it can be restored)
+ALTER TABLE task ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMP WITH TIME ZONE;
+
+CREATE INDEX IF NOT EXISTS idx_task_deleted_at ON task (deleted_at) WHERE deleted_at IS NOT NULL;
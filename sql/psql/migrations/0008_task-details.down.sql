@@ -0,0 +1,14 @@
+DROP TABLE IF EXISTS task_label;
+DROP TABLE IF EXISTS label;
+
+ALTER TABLE task DROP COLUMN IF EXISTS done;
+ALTER TABLE task ADD COLUMN done BOOLEAN NOT NULL DEFAULT FALSE;
+UPDATE task SET done = (status = 'done');
+
+DROP INDEX IF EXISTS idx_task_assignee_id;
+ALTER TABLE task
+    DROP COLUMN IF EXISTS assignee_id,
+    DROP COLUMN IF EXISTS status,
+    DROP COLUMN IF EXISTS priority,
+    DROP COLUMN IF EXISTS due_at,
+    DROP COLUMN IF EXISTS description;
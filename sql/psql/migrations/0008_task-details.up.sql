@@ -0,0 +1,49 @@
+-- Add the Task Details
+-- (priority: 0 none, 1 low, 2 medium, 3 high, 4 urgent)
+ALTER TABLE task
+    ADD COLUMN IF NOT EXISTS description TEXT,
+    ADD COLUMN IF NOT EXISTS due_at TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN IF NOT EXISTS priority INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS status VARCHAR(16) NOT NULL DEFAULT 'todo',
+    ADD COLUMN IF NOT EXISTS assignee_id BIGINT;
+
+ALTER TABLE task
+    ADD CONSTRAINT ck_task_priority CHECK (priority BETWEEN 0 AND 4),
+    ADD CONSTRAINT ck_task_status CHECK (status IN ('todo', 'in_progress', 'done')),
+    ADD CONSTRAINT fk_task_assignee FOREIGN KEY (assignee_id) REFERENCES "user"(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_task_assignee_id ON task (assignee_id);
+
+-- The status is the source of truth, `done` is derived from it
+UPDATE task SET status = 'done' WHERE done;
+ALTER TABLE task DROP COLUMN done;
+ALTER TABLE task ADD COLUMN done BOOLEAN GENERATED ALWAYS AS (status = 'done') STORED;
+
+-- Create Label Table
+-- (the labels of a project, given to its tasks)
+CREATE TABLE IF NOT EXISTS label (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+    project_id BIGINT NOT NULL,
+    name VARCHAR(64) NOT NULL,
+
+    -- Timestamps
+    cid BIGINT NOT NULL,
+    ctime TIMESTAMP WITH TIME ZONE NOT NULL,
+    mid BIGINT NOT NULL,
+    mtime TIMESTAMP WITH TIME ZONE NOT NULL,
+
+    CONSTRAINT uq_label_project_name UNIQUE (project_id, name),
+    CONSTRAINT fk_label_project FOREIGN KEY (project_id) REFERENCES project(id) ON DELETE CASCADE
+);
+
+-- Create Task Label Table
+CREATE TABLE IF NOT EXISTS task_label (
+    task_id BIGINT NOT NULL,
+    label_id BIGINT NOT NULL,
+
+    PRIMARY KEY (task_id, label_id),
+    CONSTRAINT fk_task_label_task FOREIGN KEY (task_id) REFERENCES task(id) ON DELETE CASCADE,
+    CONSTRAINT fk_task_label_label FOREIGN KEY (label_id) REFERENCES label(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_label_label_id ON task_label (label_id);
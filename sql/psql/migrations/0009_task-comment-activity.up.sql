@@ -0,0 +1,34 @@
+-- Create Task Comment Table
+CREATE TABLE IF NOT EXISTS task_comment (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+    task_id BIGINT NOT NULL,
+    content TEXT NOT NULL,
+
+    -- Timestamps
+    cid BIGINT NOT NULL,
+    ctime TIMESTAMP WITH TIME ZONE NOT NULL,
+    mid BIGINT NOT NULL,
+    mtime TIMESTAMP WITH TIME ZONE NOT NULL,
+
+    CONSTRAINT fk_task_comment_task FOREIGN KEY (task_id) REFERENCES task(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_comment_task_id ON task_comment (task_id);
+
+-- Create Task Activity Table
+-- (append only: the field-level changes of each task write, as `[{"field", "before", "after"}]`.
+--  No foreign key to the task: the activities are kept when their task is purged)
+CREATE TABLE IF NOT EXISTS task_activity (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+    task_id BIGINT NOT NULL,
+    kind VARCHAR(16) NOT NULL,
+    changes JSONB NOT NULL DEFAULT '[]',
+
+    -- Timestamps (no modifier, the rows are never updated)
+    cid BIGINT NOT NULL,
+    ctime TIMESTAMP WITH TIME ZONE NOT NULL,
+
+    CONSTRAINT ck_task_activity_kind CHECK (kind IN ('create', 'update', 'delete', 'restore'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_activity_task_id ON task_activity (task_id);
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_task_search_tsv;
+ALTER TABLE task DROP COLUMN IF EXISTS search_tsv;
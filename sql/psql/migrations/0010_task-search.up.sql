@@ -0,0 +1,8 @@
+-- Add the Task Full-Text Search
+-- (the title weighs more than the description in the ranking)
+ALTER TABLE task ADD COLUMN IF NOT EXISTS search_tsv tsvector GENERATED ALWAYS AS (
+    setweight(to_tsvector('english', coalesce(title, '')), 'A') ||
+    setweight(to_tsvector('english', coalesce(description, '')), 'B')
+) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_task_search_tsv ON task USING GIN (search_tsv);